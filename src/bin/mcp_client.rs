@@ -24,6 +24,7 @@ async fn main() -> eyre::Result<()> {
     println!("✓ Connected successfully!\n");
 
     let mut client = TestClient::new(reader, writer);
+    client.initialize().await?;
 
     loop {
         println!();
@@ -112,6 +113,42 @@ impl TestClient {
         Ok(())
     }
 
+    async fn send_notification(&mut self, notification: Value) -> eyre::Result<()> {
+        let notification_json = serde_json::to_string(&notification)?;
+        self.writer.write_all(notification_json.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// 执行 MCP 握手：initialize 请求 + initialized 通知
+    async fn initialize(&mut self) -> eyre::Result<()> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "mcp-client",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            },
+            "id": self.request_id
+        });
+
+        self.send_request(request).await?;
+
+        self.send_notification(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        }))
+        .await?;
+
+        println!("\n✓ MCP session initialized");
+        Ok(())
+    }
+
     async fn get_balance(&mut self) -> eyre::Result<()> {
         println!();
         println!("╔═══════════════════════════════════════════════════════╗");
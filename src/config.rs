@@ -1,40 +1,292 @@
 use crate::error::{EthereumError, Result};
+use crate::network::{NetworkProfile, NETWORKS};
+use crate::rpc::cache::CacheConfig;
+use crate::rpc::retry::RetryPolicy;
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// 主 RPC 端点
     pub rpc_url: String,
+    /// 主端点故障时按顺序切换的备用端点
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// 历史查询（指定区块或时间戳）优先使用的归档节点端点
+    #[serde(default)]
+    pub archive_rpc_urls: Vec<String>,
     pub private_key: Option<String>,
     pub chain_id: u64,
+    /// Uniswap 格式代币列表的文件路径或 URL，按优先级从高到低排列
+    #[serde(default)]
+    pub token_lists: Vec<String>,
+    /// 持久化数据（如运行时解析到的代币元数据）所在目录，未设置时只保存在内存中
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub rpc: RpcClientConfig,
+    /// 除默认链（`chain_id`）之外同时服务的其他链
+    #[serde(default)]
+    pub additional_chains: Vec<ChainConfig>,
+}
+
+/// 一条链的 RPC 端点配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub archive_rpc_urls: Vec<String>,
+}
+
+impl ChainConfig {
+    /// 按优先级排列的全部 RPC 端点（主端点在前）
+    pub fn rpc_endpoints(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
+
+    /// `chain_id` 对应的网络配置
+    pub fn network(&self) -> Result<&'static NetworkProfile> {
+        NetworkProfile::by_chain_id(self.chain_id).ok_or_else(|| unsupported_chain(self.chain_id))
+    }
+}
+
+/// RPC HTTP 连接池配置
+///
+/// `RpcClient` 在创建时据此构建唯一的 HTTP 客户端，所有 RPC 调用共享其连接池。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcClientConfig {
+    /// 单个 RPC 请求的总超时
+    pub request_timeout: Duration,
+    /// 建立 TCP/TLS 连接的超时
+    pub connect_timeout: Duration,
+    /// 空闲连接在池中保留的时间
+    pub pool_idle_timeout: Duration,
+    /// 每个主机保留的最大空闲连接数
+    pub pool_max_idle_per_host: usize,
+    /// TCP keep-alive 间隔，`None` 表示关闭
+    pub tcp_keepalive: Option<Duration>,
+    /// 端点连续失败多少次后标记为不健康
+    pub unhealthy_after: u32,
+    /// 后台探测不健康端点的间隔
+    pub probe_interval: Duration,
+    /// 瞬时错误（限流、超时、节点落后等）的重试策略
+    pub retry: RetryPolicy,
+    /// 内存缓存（代币元数据、区块级数据）的容量和有效期
+    pub cache: CacheConfig,
+}
+
+impl Default for RpcClientConfig {
+    fn default() -> Self {
+        RpcClientConfig {
+            request_timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 32,
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            unhealthy_after: 3,
+            probe_interval: Duration::from_secs(15),
+            retry: RetryPolicy::default(),
+            cache: CacheConfig::default(),
+        }
+    }
+}
+
+impl RpcClientConfig {
+    /// 从环境变量读取，未设置的项使用默认值
+    ///
+    /// - `RPC_TIMEOUT_SECS`、`RPC_CONNECT_TIMEOUT_SECS`、`RPC_POOL_IDLE_TIMEOUT_SECS`
+    /// - `RPC_POOL_MAX_IDLE_PER_HOST`
+    /// - `RPC_TCP_KEEPALIVE_SECS`（`0` 表示关闭）
+    /// - `RPC_UNHEALTHY_AFTER`、`RPC_PROBE_INTERVAL_SECS`
+    /// - `RPC_MAX_RETRIES`、`RPC_RETRY_BASE_DELAY_MS`、`RPC_RETRY_MAX_DELAY_MS`
+    /// - `RPC_CACHE_METADATA_ENTRIES`、`RPC_CACHE_BLOCK_ENTRIES`（`0` 表示不缓存）、`RPC_CACHE_BLOCK_TTL_SECS`
+    pub fn from_env() -> Result<Self> {
+        let defaults = RpcClientConfig::default();
+
+        Ok(RpcClientConfig {
+            request_timeout: env_parse("RPC_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.request_timeout),
+            connect_timeout: env_parse("RPC_CONNECT_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.connect_timeout),
+            pool_idle_timeout: env_parse("RPC_POOL_IDLE_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.pool_idle_timeout),
+            pool_max_idle_per_host: env_parse("RPC_POOL_MAX_IDLE_PER_HOST")?
+                .unwrap_or(defaults.pool_max_idle_per_host),
+            tcp_keepalive: match env_parse::<u64>("RPC_TCP_KEEPALIVE_SECS")? {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => defaults.tcp_keepalive,
+            },
+            unhealthy_after: env_parse("RPC_UNHEALTHY_AFTER")?.unwrap_or(defaults.unhealthy_after),
+            probe_interval: env_parse("RPC_PROBE_INTERVAL_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.probe_interval),
+            retry: RetryPolicy {
+                max_retries: env_parse("RPC_MAX_RETRIES")?.unwrap_or(defaults.retry.max_retries),
+                base_delay: env_parse("RPC_RETRY_BASE_DELAY_MS")?
+                    .map(Duration::from_millis)
+                    .unwrap_or(defaults.retry.base_delay),
+                max_delay: env_parse("RPC_RETRY_MAX_DELAY_MS")?
+                    .map(Duration::from_millis)
+                    .unwrap_or(defaults.retry.max_delay),
+                ..defaults.retry
+            },
+            cache: CacheConfig {
+                max_metadata_entries: env_parse("RPC_CACHE_METADATA_ENTRIES")?
+                    .unwrap_or(defaults.cache.max_metadata_entries),
+                max_block_entries: env_parse("RPC_CACHE_BLOCK_ENTRIES")?
+                    .unwrap_or(defaults.cache.max_block_entries),
+                block_ttl: env_parse("RPC_CACHE_BLOCK_TTL_SECS")?
+                    .map(Duration::from_secs)
+                    .unwrap_or(defaults.cache.block_ttl),
+            },
+        })
+    }
+}
+
+/// 读取并解析可选的环境变量
+fn env_parse<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|e| EthereumError::ConfigError(format!("Invalid {}: {}", name, e))),
+        Err(_) => Ok(None),
+    }
+}
+
+fn unsupported_chain(chain_id: u64) -> EthereumError {
+    EthereumError::ConfigError(format!(
+        "Unsupported CHAIN_ID {}, supported chains: {}",
+        chain_id,
+        NetworkProfile::supported_chain_ids()
+    ))
+}
+
+/// 读取逗号分隔的列表，未设置时为空
+fn env_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .map(|values| {
+            values
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl Config {
+    /// 按优先级排列的全部 RPC 端点（主端点在前）
+    pub fn rpc_endpoints(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
+
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok();
 
         let rpc_url = env::var("RPC_URL")
             .map_err(|_| EthereumError::ConfigError("RPC_URL not set".to_string()))?;
 
+        // 逗号分隔的备用端点和归档端点列表
+        let fallback_rpc_urls = env_list("RPC_FALLBACK_URLS");
+        let archive_rpc_urls = env_list("ARCHIVE_RPC_URLS");
+        let token_lists = env_list("TOKEN_LISTS");
+
         let private_key = env::var("PRIVATE_KEY").ok();
 
         let chain_id = env::var("CHAIN_ID")
             .unwrap_or_else(|_| "1".to_string())
             .parse::<u64>()
             .map_err(|e| EthereumError::ConfigError(format!("Invalid CHAIN_ID: {}", e)))?;
+        NetworkProfile::by_chain_id(chain_id).ok_or_else(|| unsupported_chain(chain_id))?;
+
+        let data_dir = env::var("DATA_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(PathBuf::from);
+
+        // 其他链：设置了 RPC_URL_<NAME>（如 RPC_URL_BASE）的网络同时提供服务
+        let mut additional_chains = Vec::new();
+        for network in &NETWORKS {
+            let suffix = network.name.to_uppercase();
+            let Ok(rpc_url) = env::var(format!("RPC_URL_{}", suffix)) else {
+                continue;
+            };
+            if network.chain_id == chain_id {
+                return Err(EthereumError::ConfigError(format!(
+                    "RPC_URL_{} duplicates the default chain (CHAIN_ID={}), use RPC_URL instead",
+                    suffix, chain_id
+                )));
+            }
+            additional_chains.push(ChainConfig {
+                chain_id: network.chain_id,
+                rpc_url,
+                fallback_rpc_urls: env_list(&format!("RPC_FALLBACK_URLS_{}", suffix)),
+                archive_rpc_urls: env_list(&format!("ARCHIVE_RPC_URLS_{}", suffix)),
+            });
+        }
 
         Ok(Config {
             rpc_url,
+            fallback_rpc_urls,
+            archive_rpc_urls,
             private_key,
             chain_id,
+            token_lists,
+            data_dir,
+            rpc: RpcClientConfig::from_env()?,
+            additional_chains,
+        })
+    }
+
+    /// `chain_id` 对应的网络配置
+    pub fn network(&self) -> Result<&'static NetworkProfile> {
+        NetworkProfile::by_chain_id(self.chain_id).ok_or_else(|| unsupported_chain(self.chain_id))
+    }
+
+    /// 服务的全部链，默认链在前
+    pub fn chains(&self) -> Vec<ChainConfig> {
+        std::iter::once(ChainConfig {
+            chain_id: self.chain_id,
+            rpc_url: self.rpc_url.clone(),
+            fallback_rpc_urls: self.fallback_rpc_urls.clone(),
+            archive_rpc_urls: self.archive_rpc_urls.clone(),
         })
+        .chain(self.additional_chains.iter().cloned())
+        .collect()
     }
 
     pub fn from_url(rpc_url: String) -> Self {
         Config {
             rpc_url,
+            fallback_rpc_urls: Vec::new(),
+            archive_rpc_urls: Vec::new(),
             private_key: None,
             chain_id: 1,
+            token_lists: Vec::new(),
+            data_dir: None,
+            rpc: RpcClientConfig::default(),
+            additional_chains: Vec::new(),
         }
     }
 }
@@ -48,5 +300,42 @@ mod tests {
         let config = Config::from_url("https://eth.llamarpc.com".to_string());
         assert_eq!(config.rpc_url, "https://eth.llamarpc.com");
         assert_eq!(config.chain_id, 1);
+        assert_eq!(config.rpc, RpcClientConfig::default());
+        assert_eq!(config.network().unwrap().name, "ethereum");
+
+        let unsupported = Config {
+            chain_id: 56,
+            ..config
+        };
+        assert!(unsupported.network().is_err());
+    }
+
+    #[test]
+    fn test_chains_lists_default_chain_first() {
+        let mut config = Config::from_url("https://eth.example".to_string());
+        config.fallback_rpc_urls = vec!["https://eth-backup.example".to_string()];
+        config.additional_chains.push(ChainConfig {
+            chain_id: 8453,
+            rpc_url: "https://base.example".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            archive_rpc_urls: Vec::new(),
+        });
+
+        let chains = config.chains();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].chain_id, 1);
+        assert_eq!(chains[0].rpc_endpoints(), config.rpc_endpoints());
+        assert_eq!(chains[1].network().unwrap().name, "base");
+    }
+
+    #[test]
+    fn test_rpc_client_config_deserializes_with_defaults() {
+        let config: RpcClientConfig =
+            serde_json::from_str(r#"{"pool_max_idle_per_host": 4}"#).unwrap();
+        assert_eq!(config.pool_max_idle_per_host, 4);
+        assert_eq!(
+            config.request_timeout,
+            RpcClientConfig::default().request_timeout
+        );
     }
 }
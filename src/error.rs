@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::rpc::classify::{ClassifyError, RpcErrorKind};
+
 #[derive(Error, Debug)]
 pub enum EthereumError {
     #[error("无效地址: {0}")]
@@ -8,8 +10,8 @@ pub enum EthereumError {
     #[error("无效数量: {0}")]
     InvalidAmount(String),
 
-    #[error("RPC错误: {0}")]
-    RpcError(String),
+    #[error("RPC错误: {message}")]
+    RpcError { kind: RpcErrorKind, message: String },
 
     #[error("余额不足: 需要 {required}，可用 {available}")]
     InsufficientBalance { required: String, available: String },
@@ -38,6 +40,21 @@ pub enum EthereumError {
     #[error("无效代币对: {0}")]
     InvalidTokenPair(String),
 
+    #[error("无效区块: {0}")]
+    InvalidBlock(String),
+
+    #[error("区块不存在: {0}")]
+    BlockNotFound(String),
+
+    #[error("历史状态不可用（节点已裁剪该区块的状态，请配置归档节点 ARCHIVE_RPC_URLS）: {0}")]
+    StateUnavailable(String),
+
+    #[error("代币列表错误: {0}")]
+    TokenListError(String),
+
+    #[error("存储错误: {0}")]
+    StorageError(String),
+
     #[error("网络错误: {0}")]
     NetworkError(String),
 
@@ -45,4 +62,26 @@ pub enum EthereumError {
     Unknown(String),
 }
 
+impl EthereumError {
+    /// 由已分类的 RPC 错误构造，保留错误类型供调用方判断
+    ///
+    /// 历史状态被裁剪的错误单独成为 [`EthereumError::StateUnavailable`]，便于给出明确提示。
+    pub fn rpc<E: ClassifyError>(context: &str, error: E) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error.kind() {
+            RpcErrorKind::StateUnavailable => EthereumError::StateUnavailable(message),
+            kind => EthereumError::RpcError { kind, message },
+        }
+    }
+
+    /// RPC 错误的类型，其他错误返回 `None`
+    pub fn rpc_kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            EthereumError::RpcError { kind, .. } => Some(*kind),
+            EthereumError::StateUnavailable(_) => Some(RpcErrorKind::StateUnavailable),
+            _ => None,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, EthereumError>;
@@ -1,6 +1,8 @@
 pub mod config;
 pub mod error;
+pub mod network;
 pub mod precision;
+pub mod pricing;
 pub mod rpc;
 pub mod server;
 pub mod tokens;
@@ -8,5 +10,6 @@ pub mod tools;
 
 pub use config::Config;
 pub use error::{EthereumError, Result};
+pub use network::NetworkProfile;
 pub use rpc::RpcClient;
 pub use server::McpServer;
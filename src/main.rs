@@ -1,15 +1,91 @@
+use ethereum_trading_mcp_server::server::http::{self, HttpConfig};
+use ethereum_trading_mcp_server::server::transport::{
+    self, Transport, DEFAULT_MAX_IN_FLIGHT, DEFAULT_TCP_ADDR,
+};
 use ethereum_trading_mcp_server::{Config, McpServer};
-use serde_json::json;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::TcpListener;
+use std::time::Duration;
 use tracing::{error, info};
 
+/// 命令行参数
+struct Args {
+    transport: Transport,
+    addr: SocketAddr,
+    max_in_flight: usize,
+    http: HttpConfig,
+}
+
+impl Args {
+    /// 解析 `--transport <tcp|stdio|http>`、`--addr <host:port>`、`--max-in-flight <n>`，
+    /// 以及 HTTP 传输的 `--max-sessions <n>`、`--session-idle-secs <n>` 和 `--allowed-origins <origin,...>`
+    ///
+    /// 未指定时依次回退到环境变量 `MCP_TRANSPORT` / `MCP_ADDR` / `MCP_MAX_IN_FLIGHT` /
+    /// `MCP_MAX_SESSIONS` / `MCP_SESSION_IDLE_SECS` / `MCP_ALLOWED_ORIGINS` 和默认值。
+    fn parse() -> eyre::Result<Self> {
+        let mut transport = std::env::var("MCP_TRANSPORT").ok();
+        let mut addr = std::env::var("MCP_ADDR").ok();
+        let mut max_in_flight = std::env::var("MCP_MAX_IN_FLIGHT").ok();
+        let mut max_sessions = std::env::var("MCP_MAX_SESSIONS").ok();
+        let mut session_idle_secs = std::env::var("MCP_SESSION_IDLE_SECS").ok();
+        let mut allowed_origins = std::env::var("MCP_ALLOWED_ORIGINS").ok();
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--transport" => transport = args.next(),
+                "--addr" => addr = args.next(),
+                "--max-in-flight" => max_in_flight = args.next(),
+                "--max-sessions" => max_sessions = args.next(),
+                "--session-idle-secs" => session_idle_secs = args.next(),
+                "--allowed-origins" => allowed_origins = args.next(),
+                other => eyre::bail!(
+                    "未知参数: {}（用法: ethereum-mcp-server [--transport tcp|stdio|http] [--addr host:port] [--max-in-flight n] [--max-sessions n] [--session-idle-secs n] [--allowed-origins origin,...]）",
+                    other
+                ),
+            }
+        }
+
+        Ok(Args {
+            transport: transport.as_deref().unwrap_or("tcp").parse()?,
+            addr: addr.as_deref().unwrap_or(DEFAULT_TCP_ADDR).parse()?,
+            max_in_flight: match max_in_flight {
+                Some(n) => n.parse()?,
+                None => DEFAULT_MAX_IN_FLIGHT,
+            },
+            http: HttpConfig {
+                idle_timeout: match session_idle_secs {
+                    Some(secs) => Duration::from_secs(secs.parse()?),
+                    None => http::DEFAULT_SESSION_IDLE_TIMEOUT,
+                },
+                max_sessions: match max_sessions {
+                    Some(n) => n.parse()?,
+                    None => http::DEFAULT_MAX_SESSIONS,
+                },
+                allowed_origins: allowed_origins
+                    .map(|origins| {
+                        origins
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|origin| !origin.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
+        })
+    }
+}
+
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
+    let args = Args::parse()?;
+
     // 初始化追踪
+    // 日志统一输出到 stderr，stdio 模式下 stdout 仅用于协议消息
     tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_ansi(args.transport != Transport::Stdio)
         .with_target(false)
         .with_thread_ids(false)
         .with_line_number(true)
@@ -18,11 +94,19 @@ async fn main() -> eyre::Result<()> {
 
     info!("启动以太坊交易 MCP 服务器...");
 
-    // 从环境加载配置
-    let config = Config::from_env().unwrap_or_else(|_| {
-        info!("使用默认配置（未找到 RPC_URL 环境变量）");
-        Config::from_url("https://eth.llamarpc.com".to_string())
-    });
+    // 从环境加载配置：只有未设置 RPC_URL 时使用公共主网端点，其他配置错误直接退出，
+    // 避免在运营者指定的网络之外提供服务
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(_) if std::env::var("RPC_URL").is_err() => {
+            info!("使用默认配置（未找到 RPC_URL 环境变量）");
+            Config::from_url("https://eth.llamarpc.com".to_string())
+        }
+        Err(e) => {
+            error!("配置无效: {}", e);
+            return Err(e.into());
+        }
+    };
 
     // 创建并初始化 MCP 服务器
     let mcp_server = Arc::new(McpServer::new(config));
@@ -35,75 +119,17 @@ async fn main() -> eyre::Result<()> {
         }
     }
 
-    // 启动 TCP 服务器
-    let addr: SocketAddr = "127.0.0.1:8080".parse()?;
-    let listener = TcpListener::bind(&addr).await?;
-
-    info!("MCP 服务器监听 http://{}", addr);
-    info!("可用工具: get_balance, get_token_price, swap_tokens");
-
-    loop {
-        let (socket, peer_addr) = listener.accept().await?;
-        let mcp_server = Arc::clone(&mcp_server);
-
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(socket, mcp_server).await {
-                error!("Error handling connection from {}: {}", peer_addr, e);
-            }
-        });
+    let tool_names: Vec<String> = mcp_server
+        .get_tool_definitions()
+        .await
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect();
+    info!("可用工具: {}", tool_names.join(", "));
+
+    match args.transport {
+        Transport::Tcp => transport::serve_tcp(args.addr, mcp_server, args.max_in_flight).await,
+        Transport::Stdio => transport::serve_stdio(mcp_server, args.max_in_flight).await,
+        Transport::Http => http::serve_http(args.addr, mcp_server, args.http).await,
     }
 }
-
-async fn handle_connection(
-    socket: tokio::net::TcpStream,
-    mcp_server: Arc<McpServer>,
-) -> eyre::Result<()> {
-    let (reader, mut writer) = socket.into_split();
-    let mut buf_reader = BufReader::new(reader);
-    let mut line = String::new();
-
-    while buf_reader.read_line(&mut line).await? > 0 {
-        let trimmed = line.trim();
-
-        if trimmed.is_empty() {
-            line.clear();
-            continue;
-        }
-
-        // 解析 JSON-RPC 请求
-        match serde_json::from_str::<ethereum_trading_mcp_server::server::JsonRpcRequest>(trimmed) {
-            Ok(request) => {
-                info!("收到请求: {} (id: {:?})", request.method, request.id);
-
-                let response = mcp_server.handle_request(request).await;
-
-                let response_json = serde_json::to_string(&response)?;
-                writer.write_all(response_json.as_bytes()).await?;
-                writer.write_all(b"\n").await?;
-                writer.flush().await?;
-            }
-            Err(e) => {
-                error!("无法解析 JSON-RPC 请求: {}", e);
-
-                let error_response = json!({
-                    "jsonrpc": "2.0",
-                    "error": {
-                        "code": -32700,
-                        "message": "Parse error",
-                        "data": e.to_string()
-                    },
-                    "id": null
-                });
-
-                let response_json = serde_json::to_string(&error_response)?;
-                writer.write_all(response_json.as_bytes()).await?;
-                writer.write_all(b"\n").await?;
-                writer.flush().await?;
-            }
-        }
-
-        line.clear();
-    }
-
-    Ok(())
-}
@@ -0,0 +1,586 @@
+use alloy::primitives::{address, b256, keccak256, Address, B256};
+use std::time::Duration;
+
+use crate::rpc::multicall::MULTICALL3_ADDRESS;
+
+/// 报价或路由中使用的代币
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteToken {
+    pub symbol: &'static str,
+    pub address: Address,
+    pub decimals: u8,
+}
+
+/// Uniswap V2（及同构分叉）的部署地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniswapV2Deployment {
+    pub factory: Address,
+    pub router: Address,
+    /// Pair 合约的 init code hash，用于按 CREATE2 规则计算交易对地址
+    pub pair_init_code_hash: B256,
+}
+
+impl UniswapV2Deployment {
+    /// 在本地计算交易对地址，省去 `getPair` 调用
+    pub fn pair_address(&self, token_a: Address, token_b: Address) -> Address {
+        let (token0, token1) = if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        let salt = keccak256([token0.as_slice(), token1.as_slice()].concat());
+        self.factory.create2(salt, self.pair_init_code_hash)
+    }
+}
+
+/// Uniswap V3 的部署地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniswapV3Deployment {
+    pub factory: Address,
+    /// Pool 合约的 init code hash，用于按 CREATE2 规则计算池地址
+    pub pool_init_code_hash: B256,
+}
+
+impl UniswapV3Deployment {
+    /// 在本地计算 `fee` 费率档位的池地址，省去 `getPool` 调用
+    ///
+    /// `fee` 以百万分之一为单位，如 `3000` 表示 0.3%。
+    pub fn pool_address(&self, token_a: Address, token_b: Address, fee: u32) -> Address {
+        let (token0, token1) = if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        // salt = keccak256(abi.encode(token0, token1, fee))
+        let mut encoded = [0u8; 96];
+        encoded[12..32].copy_from_slice(token0.as_slice());
+        encoded[44..64].copy_from_slice(token1.as_slice());
+        encoded[92..96].copy_from_slice(&fee.to_be_bytes());
+        self.factory
+            .create2(keccak256(encoded), self.pool_init_code_hash)
+    }
+}
+
+/// Chainlink 喂价的计价货币
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedQuote {
+    Usd,
+    Eth,
+}
+
+impl FeedQuote {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FeedQuote::Usd => "USD",
+            FeedQuote::Eth => "ETH",
+        }
+    }
+}
+
+/// Chainlink 喂价（`AggregatorV3Interface` 代理合约）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainlinkFeed {
+    /// 喂价的基础代币，ETH 以包装原生代币表示
+    pub base: Address,
+    pub quote: FeedQuote,
+    pub address: Address,
+    /// 喂价至少每隔多久更新一次，距上次更新超过该时长即视为过期
+    pub heartbeat: Duration,
+}
+
+const fn feed(
+    base: Address,
+    quote: FeedQuote,
+    address: Address,
+    heartbeat_secs: u64,
+) -> ChainlinkFeed {
+    ChainlinkFeed {
+        base,
+        quote,
+        address,
+        heartbeat: Duration::from_secs(heartbeat_secs),
+    }
+}
+
+/// 一条链的网络配置：包装原生代币、稳定币、DEX 部署、Multicall 地址和出块时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkProfile {
+    pub chain_id: u64,
+    /// 简短名称（如 `ethereum`、`arbitrum`）
+    pub name: &'static str,
+    /// 也可用于选择该网络的其他名称
+    pub aliases: &'static [&'static str],
+    pub display_name: &'static str,
+    pub native_symbol: &'static str,
+    /// 包装原生代币（WETH），原生代币在 DEX 中以它交易
+    pub wrapped_native: QuoteToken,
+    /// USD 报价使用的稳定币，第一个为首选
+    pub stablecoins: &'static [QuoteToken],
+    pub uniswap_v2: UniswapV2Deployment,
+    pub uniswap_v3: UniswapV3Deployment,
+    /// 可用的 Chainlink 喂价
+    pub chainlink_feeds: &'static [ChainlinkFeed],
+    pub multicall3: Address,
+    /// 平均出块时间
+    pub block_time: Duration,
+}
+
+/// Uniswap V2 官方部署共用的 Pair init code hash
+const UNISWAP_V2_PAIR_INIT_CODE_HASH: B256 =
+    b256!("96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f");
+
+/// Uniswap V3 官方部署共用的 Pool init code hash
+const UNISWAP_V3_POOL_INIT_CODE_HASH: B256 =
+    b256!("e34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54");
+
+/// 主网、Arbitrum 和 OP Mainnet 上相同的 Uniswap V3 Factory 地址
+const UNISWAP_V3_FACTORY: Address = address!("1F98431c8aD98523631AE4a59f267346ea31F984");
+
+const MAINNET_FEEDS: &[ChainlinkFeed] = &[
+    // WETH
+    feed(
+        address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        FeedQuote::Usd,
+        address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+        3600,
+    ),
+    // USDC
+    feed(
+        address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+        FeedQuote::Usd,
+        address!("8fFfFfd4AfB6115b954Bd326cbe7B4BA576818f6"),
+        86400,
+    ),
+    feed(
+        address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+        FeedQuote::Eth,
+        address!("986b5E1e1755e3C2440e960477f25201B0a8bbD4"),
+        86400,
+    ),
+    // USDT
+    feed(
+        address!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+        FeedQuote::Usd,
+        address!("3E7d1eAB13ad0104d2750B8863b489D65364e32D"),
+        86400,
+    ),
+    // DAI
+    feed(
+        address!("6B175474E89094C44Da98b954EedeAC495271d0F"),
+        FeedQuote::Usd,
+        address!("Aed0c38402a5d19df6E4c03F4E2DceD6e29c1ee9"),
+        3600,
+    ),
+    // LINK
+    feed(
+        address!("514910771AF9Ca656af840dff83E8264EcF986CA"),
+        FeedQuote::Usd,
+        address!("2c1d072e956AFFC0D435Cb7AC38EF18d24d9127c"),
+        3600,
+    ),
+    feed(
+        address!("514910771AF9Ca656af840dff83E8264EcF986CA"),
+        FeedQuote::Eth,
+        address!("DC530D9457755926550b59e8ECcdaE7624181557"),
+        21600,
+    ),
+    // UNI
+    feed(
+        address!("1f9840a85d5aF5bf1D1762F925BDADdC4201F984"),
+        FeedQuote::Usd,
+        address!("553303d460EE0afB37EdFf9bE42922D8FF63220e"),
+        3600,
+    ),
+    // AAVE
+    feed(
+        address!("7fc66500C84a76AD7e9C93437e434122a1f9AcdD"),
+        FeedQuote::Usd,
+        address!("547a514d5e3769680Ce22B2361c10Ea13619e8a9"),
+        3600,
+    ),
+];
+
+const ARBITRUM_FEEDS: &[ChainlinkFeed] = &[
+    // WETH
+    feed(
+        address!("82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+        FeedQuote::Usd,
+        address!("639Fe6ab55C921f74e7fac1ee960C0B6293ba612"),
+        86400,
+    ),
+    // USDC
+    feed(
+        address!("af88d065e77c8cC2239327C5EDb3A432268e5831"),
+        FeedQuote::Usd,
+        address!("50834F3163758fcC1Df9973b6e91f0F0F0434aD3"),
+        86400,
+    ),
+];
+
+const BASE_FEEDS: &[ChainlinkFeed] = &[
+    // WETH
+    feed(
+        address!("4200000000000000000000000000000000000006"),
+        FeedQuote::Usd,
+        address!("71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70"),
+        1200,
+    ),
+    // USDC
+    feed(
+        address!("833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+        FeedQuote::Usd,
+        address!("7e860098F58bBFC8648a4311b374B1D669a2bc6B"),
+        86400,
+    ),
+];
+
+const OPTIMISM_FEEDS: &[ChainlinkFeed] = &[
+    // WETH
+    feed(
+        address!("4200000000000000000000000000000000000006"),
+        FeedQuote::Usd,
+        address!("13e3Ee699D1909E989722E753853AE30b17e08c5"),
+        1200,
+    ),
+    // USDC
+    feed(
+        address!("0b2C639c533813f4Aa9D7837CAf62653d097Ff85"),
+        FeedQuote::Usd,
+        address!("16a9FA2FDa030272Ce99B29CF780dFA30361E0f3"),
+        86400,
+    ),
+];
+
+const SEPOLIA_FEEDS: &[ChainlinkFeed] = &[
+    // WETH
+    feed(
+        address!("fFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
+        FeedQuote::Usd,
+        address!("694AA1769357215DE4FAC081bf1f309aDC325306"),
+        3600,
+    ),
+];
+
+/// 支持的网络
+pub static NETWORKS: [NetworkProfile; 5] = [
+    NetworkProfile {
+        chain_id: 1,
+        name: "ethereum",
+        aliases: &["mainnet", "eth"],
+        display_name: "Ethereum Mainnet",
+        native_symbol: "ETH",
+        wrapped_native: QuoteToken {
+            symbol: "WETH",
+            address: address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            decimals: 18,
+        },
+        stablecoins: &[
+            QuoteToken {
+                symbol: "USDC",
+                address: address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "USDT",
+                address: address!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "DAI",
+                address: address!("6B175474E89094C44Da98b954EedeAC495271d0F"),
+                decimals: 18,
+            },
+        ],
+        uniswap_v2: UniswapV2Deployment {
+            factory: address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"),
+            router: address!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D"),
+            pair_init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH,
+        },
+        uniswap_v3: UniswapV3Deployment {
+            factory: UNISWAP_V3_FACTORY,
+            pool_init_code_hash: UNISWAP_V3_POOL_INIT_CODE_HASH,
+        },
+        chainlink_feeds: MAINNET_FEEDS,
+        multicall3: MULTICALL3_ADDRESS,
+        block_time: Duration::from_secs(12),
+    },
+    NetworkProfile {
+        chain_id: 42161,
+        name: "arbitrum",
+        aliases: &["arbitrum-one", "arb"],
+        display_name: "Arbitrum One",
+        native_symbol: "ETH",
+        wrapped_native: QuoteToken {
+            symbol: "WETH",
+            address: address!("82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+            decimals: 18,
+        },
+        stablecoins: &[
+            QuoteToken {
+                symbol: "USDC",
+                address: address!("af88d065e77c8cC2239327C5EDb3A432268e5831"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "USDT",
+                address: address!("Fd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "DAI",
+                address: address!("DA10009cBd5D07dd0CeCc66161FC93D7c9000da1"),
+                decimals: 18,
+            },
+        ],
+        uniswap_v2: UniswapV2Deployment {
+            factory: address!("f1D7CC64Fb4452F05c498126312eBE29f30Fbcf9"),
+            router: address!("4752ba5DBc23f44D87826276BF6Fd6b1C372aD24"),
+            pair_init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH,
+        },
+        uniswap_v3: UniswapV3Deployment {
+            factory: UNISWAP_V3_FACTORY,
+            pool_init_code_hash: UNISWAP_V3_POOL_INIT_CODE_HASH,
+        },
+        chainlink_feeds: ARBITRUM_FEEDS,
+        multicall3: MULTICALL3_ADDRESS,
+        block_time: Duration::from_millis(250),
+    },
+    NetworkProfile {
+        chain_id: 8453,
+        name: "base",
+        aliases: &[],
+        display_name: "Base",
+        native_symbol: "ETH",
+        wrapped_native: QuoteToken {
+            symbol: "WETH",
+            address: address!("4200000000000000000000000000000000000006"),
+            decimals: 18,
+        },
+        stablecoins: &[
+            QuoteToken {
+                symbol: "USDC",
+                address: address!("833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "DAI",
+                address: address!("50c5725949A6F0c72E6C4a641F24049A917DB0Cb"),
+                decimals: 18,
+            },
+        ],
+        uniswap_v2: UniswapV2Deployment {
+            factory: address!("8909Dc15e40173Ff4699343b6eB8132c65e18eC6"),
+            router: address!("4752ba5DBc23f44D87826276BF6Fd6b1C372aD24"),
+            pair_init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH,
+        },
+        uniswap_v3: UniswapV3Deployment {
+            factory: address!("33128a8fC17869897dcE68Ed026d694621f6FDfD"),
+            pool_init_code_hash: UNISWAP_V3_POOL_INIT_CODE_HASH,
+        },
+        chainlink_feeds: BASE_FEEDS,
+        multicall3: MULTICALL3_ADDRESS,
+        block_time: Duration::from_secs(2),
+    },
+    NetworkProfile {
+        chain_id: 10,
+        name: "optimism",
+        aliases: &["op", "op-mainnet"],
+        display_name: "OP Mainnet",
+        native_symbol: "ETH",
+        wrapped_native: QuoteToken {
+            symbol: "WETH",
+            address: address!("4200000000000000000000000000000000000006"),
+            decimals: 18,
+        },
+        stablecoins: &[
+            QuoteToken {
+                symbol: "USDC",
+                address: address!("0b2C639c533813f4Aa9D7837CAf62653d097Ff85"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "USDT",
+                address: address!("94b008aA00579c1307B0EF2c499aD98a8ce58e58"),
+                decimals: 6,
+            },
+            QuoteToken {
+                symbol: "DAI",
+                address: address!("DA10009cBd5D07dd0CeCc66161FC93D7c9000da1"),
+                decimals: 18,
+            },
+        ],
+        uniswap_v2: UniswapV2Deployment {
+            factory: address!("0c3c1c532F1e39EdF36BE9Fe0bE1410313E074Bf"),
+            router: address!("4A7b5Da61326A6379179b40d00F57E5bbDC962c2"),
+            pair_init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH,
+        },
+        uniswap_v3: UniswapV3Deployment {
+            factory: UNISWAP_V3_FACTORY,
+            pool_init_code_hash: UNISWAP_V3_POOL_INIT_CODE_HASH,
+        },
+        chainlink_feeds: OPTIMISM_FEEDS,
+        multicall3: MULTICALL3_ADDRESS,
+        block_time: Duration::from_secs(2),
+    },
+    NetworkProfile {
+        chain_id: 11155111,
+        name: "sepolia",
+        aliases: &[],
+        display_name: "Sepolia Testnet",
+        native_symbol: "ETH",
+        wrapped_native: QuoteToken {
+            symbol: "WETH",
+            address: address!("fFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
+            decimals: 18,
+        },
+        stablecoins: &[QuoteToken {
+            symbol: "USDC",
+            address: address!("1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"),
+            decimals: 6,
+        }],
+        uniswap_v2: UniswapV2Deployment {
+            factory: address!("F62c03E08ada871A0bEb309762E260a7a6a880E6"),
+            router: address!("eE567Fe1712Faf6149d80dA1E6934E354124CfE3"),
+            pair_init_code_hash: UNISWAP_V2_PAIR_INIT_CODE_HASH,
+        },
+        uniswap_v3: UniswapV3Deployment {
+            factory: address!("0227628f3F023bb0B980b67D528571c95c6DaC1c"),
+            pool_init_code_hash: UNISWAP_V3_POOL_INIT_CODE_HASH,
+        },
+        chainlink_feeds: SEPOLIA_FEEDS,
+        multicall3: MULTICALL3_ADDRESS,
+        block_time: Duration::from_secs(12),
+    },
+];
+
+impl NetworkProfile {
+    /// 以太坊主网
+    pub fn mainnet() -> &'static NetworkProfile {
+        &NETWORKS[0]
+    }
+
+    /// 按链 ID 查找网络配置
+    pub fn by_chain_id(chain_id: u64) -> Option<&'static NetworkProfile> {
+        NETWORKS.iter().find(|network| network.chain_id == chain_id)
+    }
+
+    /// 按名称或别名（不区分大小写）或十进制链 ID 查找网络配置
+    pub fn lookup(chain: &str) -> Option<&'static NetworkProfile> {
+        let chain = chain.trim();
+        if let Ok(chain_id) = chain.parse::<u64>() {
+            return Self::by_chain_id(chain_id);
+        }
+        NETWORKS.iter().find(|network| {
+            std::iter::once(&network.name)
+                .chain(network.aliases)
+                .any(|name| name.eq_ignore_ascii_case(chain))
+        })
+    }
+
+    /// `base` 以 `quote` 计价的 Chainlink 喂价
+    pub fn chainlink_feed(&self, base: Address, quote: FeedQuote) -> Option<&ChainlinkFeed> {
+        self.chainlink_feeds
+            .iter()
+            .find(|feed| feed.base == base && feed.quote == quote)
+    }
+
+    /// USD 报价的首选稳定币
+    pub fn usd_quote(&self) -> &QuoteToken {
+        &self.stablecoins[0]
+    }
+
+    /// 所有支持的链 ID（用于错误提示）
+    pub fn supported_chain_ids() -> String {
+        NETWORKS
+            .iter()
+            .map(|network| format!("{} ({})", network.chain_id, network.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::TokenRegistry;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_profiles_are_consistent() {
+        let chain_ids: HashSet<u64> = NETWORKS.iter().map(|n| n.chain_id).collect();
+        assert_eq!(chain_ids.len(), NETWORKS.len());
+
+        // 内置代币列表为每条链提供原生代币、包装原生代币和稳定币的符号
+        let registry = TokenRegistry::new();
+        for network in &NETWORKS {
+            assert!(!network.stablecoins.is_empty(), "{}", network.name);
+            assert!(network.block_time > Duration::ZERO);
+            assert_eq!(
+                registry.symbol_to_address(network.chain_id, network.wrapped_native.symbol),
+                Some(network.wrapped_native.address),
+                "{}",
+                network.name
+            );
+            for stablecoin in network.stablecoins {
+                let metadata = registry
+                    .metadata(network.chain_id, stablecoin.address)
+                    .unwrap_or_else(|| panic!("{} {}", network.name, stablecoin.symbol));
+                assert_eq!(metadata.decimals, stablecoin.decimals);
+            }
+            // 喂价的基础代币都在内置列表中，且每条链都有 ETH/USD 喂价
+            for feed in network.chainlink_feeds {
+                assert!(
+                    registry.metadata(network.chain_id, feed.base).is_some(),
+                    "{} {}",
+                    network.name,
+                    feed.address
+                );
+            }
+            assert!(network
+                .chainlink_feed(network.wrapped_native.address, FeedQuote::Usd)
+                .is_some());
+        }
+    }
+
+    #[test]
+    fn test_lookup_by_chain_id_and_name() {
+        assert_eq!(
+            NetworkProfile::by_chain_id(1),
+            Some(NetworkProfile::mainnet())
+        );
+        assert_eq!(NetworkProfile::by_chain_id(8453).unwrap().name, "base");
+        assert!(NetworkProfile::by_chain_id(56).is_none());
+        assert_eq!(NetworkProfile::lookup("Base").unwrap().chain_id, 8453);
+        assert_eq!(NetworkProfile::lookup(" 42161 ").unwrap().name, "arbitrum");
+        assert_eq!(
+            NetworkProfile::lookup("mainnet"),
+            Some(NetworkProfile::mainnet())
+        );
+        assert_eq!(NetworkProfile::lookup("OP").unwrap().chain_id, 10);
+        assert!(NetworkProfile::lookup("polygon").is_none());
+        assert!(NetworkProfile::supported_chain_ids().contains("42161 (arbitrum)"));
+    }
+
+    #[test]
+    fn test_pair_address_matches_factory() {
+        let network = NetworkProfile::mainnet();
+        let pair = network
+            .uniswap_v2
+            .pair_address(network.wrapped_native.address, network.usd_quote().address);
+        assert_eq!(pair, address!("B4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"));
+    }
+
+    #[test]
+    fn test_pool_address_matches_factory() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        assert_eq!(
+            network.uniswap_v3.pool_address(weth, usdc, 500),
+            address!("88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")
+        );
+        assert_eq!(
+            network.uniswap_v3.pool_address(usdc, weth, 3000),
+            address!("8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8")
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use alloy::primitives::U256;
+use alloy::primitives::{U256, U512};
 use rust_decimal::prelude::*;
 
 use crate::error::{EthereumError, Result};
@@ -86,6 +86,39 @@ pub fn calculate_min_output_with_slippage(
         .ok_or_else(|| EthereumError::PrecisionError("Multiplication overflow".to_string()))
 }
 
+/// 将大整数比值 `numerator / denominator` 转换为十进制
+///
+/// 按长除法逐位展开小数部分，直到除尽、达到 28 位小数或尾数超出 `Decimal` 的 96 位精度，
+/// 用于 Uniswap V3 的 `sqrtPriceX96` 等超出 `Decimal` 范围的中间值。
+pub fn ratio_to_decimal(numerator: U512, denominator: U512) -> Result<Decimal> {
+    if denominator.is_zero() {
+        return Err(EthereumError::PrecisionError(
+            "Division by zero".to_string(),
+        ));
+    }
+    let max_mantissa = U512::from(u128::MAX >> 32);
+    let ten = U512::from(10u8);
+
+    let mut mantissa = numerator / denominator;
+    if mantissa > max_mantissa {
+        return Err(EthereumError::PrecisionError("数值过大".to_string()));
+    }
+    let mut remainder = numerator % denominator;
+    let mut scale = 0;
+    while !remainder.is_zero() && scale < 28 {
+        let next = mantissa * ten + remainder * ten / denominator;
+        if next > max_mantissa {
+            break;
+        }
+        mantissa = next;
+        remainder = remainder * ten % denominator;
+        scale += 1;
+    }
+
+    Decimal::try_from_i128_with_scale(mantissa.to::<i128>(), scale)
+        .map_err(|e| EthereumError::PrecisionError(e.to_string()))
+}
+
 /// 将 U256 转换为十进制，并进行适当的格式化
 pub fn u256_to_decimal(value: U256, decimals: u8) -> Result<String> {
     let decimal = to_decimal(value, decimals)?;
@@ -137,6 +170,25 @@ mod tests {
         assert_eq!(min_output, expected_min);
     }
 
+    #[test]
+    fn test_ratio_to_decimal() {
+        let ratio = |n: u64, d: u64| ratio_to_decimal(U512::from(n), U512::from(d));
+        assert_eq!(ratio(5, 2).unwrap(), Decimal::from_str("2.5").unwrap());
+        assert_eq!(
+            ratio(1, 3).unwrap(),
+            Decimal::from_str("0.3333333333333333333333333333").unwrap()
+        );
+        assert!(ratio(1, 0).is_err());
+
+        // 分子分母都超出 U256 时仍能得到精确结果
+        let big = U512::from(1u8) << 400;
+        assert_eq!(
+            ratio_to_decimal(big * U512::from(3u8), big * U512::from(4u8)).unwrap(),
+            Decimal::from_str("0.75").unwrap()
+        );
+        assert!(ratio_to_decimal(big, U512::from(1u8)).is_err());
+    }
+
     #[test]
     fn test_roundtrip_conversion() {
         let original = Decimal::from_str("123.456").unwrap();
@@ -0,0 +1,235 @@
+use alloy::primitives::U512;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::{EthereumError, Result};
+use crate::precision;
+
+/// 聚合时与中位数的默认最大偏离百分比
+pub const DEFAULT_MAX_DEVIATION_PERCENT: Decimal = Decimal::from_parts(2, 0, 0, false, 0);
+
+/// 置信度达到 1 所需的一致来源数量
+const FULL_CONFIDENCE_SOURCES: usize = 3;
+
+/// 参与聚合的价格来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// 每一跳的 Uniswap V2 交易对现货价格
+    UniswapV2Spot,
+    /// 每一跳流动性最深的 Uniswap V3 池现货价格
+    UniswapV3Spot,
+    /// 每一跳 Uniswap V3 池的时间加权平均价格
+    UniswapV3Twap,
+    /// Chainlink 喂价
+    Chainlink,
+}
+
+/// 各来源价格的聚合方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationMethod {
+    /// 一致来源的中位数
+    #[default]
+    Median,
+    /// 按流动性加权的一致 DEX 来源均价
+    LiquidityWeighted,
+}
+
+/// 一个来源给出的价格
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcePrice {
+    pub kind: SourceKind,
+    pub price: Decimal,
+    /// 第一跳池的流动性 L，DEX 来源的第一跳是同一交易对，可直接比较；喂价为 `None`
+    pub liquidity: Option<u128>,
+}
+
+/// 聚合结果，各向量与输入的来源一一对应
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub price: Decimal,
+    /// 与所有来源中位数的偏离百分比
+    pub deviations: Vec<Decimal>,
+    /// 偏离超过阈值、未参与计算价格的来源
+    pub outliers: Vec<bool>,
+    /// 按流动性加权时各来源的权重（合计为 1），未参与加权为 `None`
+    pub weights: Vec<Option<Decimal>>,
+    /// 0 到 1 的置信度
+    pub confidence: Decimal,
+}
+
+/// 聚合多个来源的价格
+///
+/// 先以所有来源的中位数为参考，偏离超过 `max_deviation_percent` 的来源标记为异常，
+/// 再按 `method` 用其余来源计算价格；所有来源都异常时退回参考中位数。
+/// 置信度 = min(一致来源数, 3) / 3 × 一致来源数 / 可用来源数，只有一个来源时不超过 0.33。
+pub fn aggregate(
+    prices: &[SourcePrice],
+    method: AggregationMethod,
+    max_deviation_percent: Decimal,
+) -> Result<Aggregate> {
+    let all: Vec<Decimal> = prices.iter().map(|p| p.price).collect();
+    let reference = median(&all)
+        .ok_or_else(|| EthereumError::PriceOracleError("没有可用的价格来源".to_string()))?;
+    if reference.is_zero() {
+        return Err(EthereumError::PriceOracleError(
+            "各来源的价格中位数为零".to_string(),
+        ));
+    }
+
+    let deviations: Vec<Decimal> = all
+        .iter()
+        .map(|price| ((*price - reference).abs() / reference * Decimal::ONE_HUNDRED).round_dp(4))
+        .collect();
+    let outliers: Vec<bool> = deviations
+        .iter()
+        .map(|deviation| *deviation > max_deviation_percent)
+        .collect();
+    for (source, deviation) in prices.iter().zip(&deviations) {
+        if *deviation > max_deviation_percent {
+            warn!(
+                "价格来源 {:?} 偏离中位数 {}%，超过阈值 {}%",
+                source.kind, deviation, max_deviation_percent
+            );
+        }
+    }
+
+    let agreeing: Vec<&SourcePrice> = prices
+        .iter()
+        .zip(&outliers)
+        .filter(|(_, outlier)| !**outlier)
+        .map(|(source, _)| source)
+        .collect();
+    let mut weights = vec![None; prices.len()];
+    let price = match method {
+        _ if agreeing.is_empty() => reference,
+        AggregationMethod::Median => {
+            median(&agreeing.iter().map(|s| s.price).collect::<Vec<_>>()).unwrap_or(reference)
+        }
+        AggregationMethod::LiquidityWeighted => {
+            let total: U512 = agreeing
+                .iter()
+                .filter_map(|s| s.liquidity)
+                .map(U512::from)
+                .fold(U512::ZERO, |sum, l| sum + l);
+            if total.is_zero() {
+                // 一致来源都没有流动性数据（如只剩喂价）时退回中位数
+                median(&agreeing.iter().map(|s| s.price).collect::<Vec<_>>()).unwrap_or(reference)
+            } else {
+                let mut price = Decimal::ZERO;
+                for (i, source) in prices.iter().enumerate() {
+                    if let (false, Some(liquidity)) = (outliers[i], source.liquidity) {
+                        let weight = precision::ratio_to_decimal(U512::from(liquidity), total)?;
+                        price += source.price * weight;
+                        weights[i] = Some(weight.round_dp(4));
+                    }
+                }
+                price
+            }
+        }
+    };
+
+    let agreeing = agreeing.len();
+    let confidence = (Decimal::from(agreeing.min(FULL_CONFIDENCE_SOURCES))
+        / Decimal::from(FULL_CONFIDENCE_SOURCES)
+        * Decimal::from(agreeing)
+        / Decimal::from(prices.len()))
+    .round_dp(2);
+    debug!(
+        "聚合 {} 个来源 ({} 个一致): {}，置信度 {}",
+        prices.len(),
+        agreeing,
+        price,
+        confidence
+    );
+
+    Ok(Aggregate {
+        price,
+        deviations,
+        outliers,
+        weights,
+        confidence,
+    })
+}
+
+/// 中位数，偶数个值时取中间两个的平均值
+pub fn median(values: &[Decimal]) -> Option<Decimal> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[mid]),
+        _ => Some((sorted[mid - 1] + sorted[mid]) / Decimal::TWO),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(kind: SourceKind, price: i64, liquidity: Option<u128>) -> SourcePrice {
+        SourcePrice {
+            kind,
+            price: Decimal::from(price),
+            liquidity,
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(
+            median(&[Decimal::from(3), Decimal::ONE, Decimal::TWO]),
+            Some(Decimal::TWO)
+        );
+        assert_eq!(
+            median(&[Decimal::from(4), Decimal::ONE]),
+            Some("2.5".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_aggregate_flags_outliers() {
+        // V2 池被操纵到 2600，V3 与喂价一致
+        let prices = [
+            source(SourceKind::UniswapV2Spot, 2600, Some(10)),
+            source(SourceKind::UniswapV3Spot, 2500, Some(30)),
+            source(SourceKind::Chainlink, 2510, None),
+        ];
+        let median = aggregate(&prices, AggregationMethod::Median, Decimal::TWO).unwrap();
+        assert_eq!(median.outliers, vec![true, false, false]);
+        // 相对中位数 2510 偏离 (2600 - 2510) / 2510
+        assert_eq!(median.deviations[0], "3.5857".parse().unwrap());
+        assert_eq!(median.price, Decimal::from(2505));
+        assert_eq!(median.confidence, "0.44".parse().unwrap());
+
+        // 按流动性加权时喂价只用于偏离检查
+        let weighted =
+            aggregate(&prices, AggregationMethod::LiquidityWeighted, Decimal::TWO).unwrap();
+        assert_eq!(weighted.price, Decimal::from(2500));
+        assert_eq!(weighted.weights, vec![None, Some(Decimal::ONE), None]);
+
+        let agreeing = aggregate(
+            &prices,
+            AggregationMethod::LiquidityWeighted,
+            Decimal::from(5),
+        )
+        .unwrap();
+        assert_eq!(agreeing.price, Decimal::from(2525));
+        assert_eq!(agreeing.confidence, Decimal::ONE);
+    }
+
+    #[test]
+    fn test_single_source_has_low_confidence() {
+        let prices = [source(SourceKind::UniswapV2Spot, 2500, Some(10))];
+        let result = aggregate(&prices, AggregationMethod::Median, Decimal::TWO).unwrap();
+        assert_eq!(result.price, Decimal::from(2500));
+        assert_eq!(result.confidence, "0.33".parse().unwrap());
+
+        assert!(aggregate(&[], AggregationMethod::Median, Decimal::TWO).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use alloy::primitives::{Address, U256};
+use alloy::sol;
+use rust_decimal::Decimal;
+use tracing::{debug, warn};
+
+use crate::error::{EthereumError, Result};
+use crate::network::{ChainlinkFeed, FeedQuote, NetworkProfile};
+use crate::precision;
+use crate::rpc::{CallResult, MulticallBatch};
+
+// Chainlink AggregatorV3Interface
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    contract AggregatorV3Interface {
+        function decimals() external view returns (uint8);
+        function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound);
+    }
+}
+
+/// 价格路径中的一个喂价，`invert` 时除以该喂价（如把 USD 价格换算为 ETH 价格时的 ETH/USD）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedLeg {
+    pub feed: &'static ChainlinkFeed,
+    pub invert: bool,
+}
+
+/// `token` 以 `quote` 计价的喂价路径
+///
+/// 优先使用直接喂价，否则经 ETH/USD 喂价换算；`token` 为包装原生代币且以 ETH 计价时路径为空（价格为 1）。
+/// 网络上没有所需喂价时返回 `None`。
+pub fn route(
+    network: &'static NetworkProfile,
+    token: Address,
+    quote: FeedQuote,
+) -> Option<Vec<FeedLeg>> {
+    let weth = network.wrapped_native.address;
+    let direct = |feed: &'static ChainlinkFeed| FeedLeg {
+        feed,
+        invert: false,
+    };
+    if quote == FeedQuote::Eth && token == weth {
+        return Some(Vec::new());
+    }
+    if let Some(feed) = network.chainlink_feed(token, quote) {
+        return Some(vec![direct(feed)]);
+    }
+
+    let eth_usd = network.chainlink_feed(weth, FeedQuote::Usd)?;
+    match quote {
+        FeedQuote::Usd => {
+            let token_eth = network.chainlink_feed(token, FeedQuote::Eth)?;
+            Some(vec![direct(token_eth), direct(eth_usd)])
+        }
+        FeedQuote::Eth => {
+            let token_usd = network.chainlink_feed(token, FeedQuote::Usd)?;
+            Some(vec![
+                direct(token_usd),
+                FeedLeg {
+                    feed: eth_usd,
+                    invert: true,
+                },
+            ])
+        }
+    }
+}
+
+/// 喂价在固定区块上的读数
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedPrice {
+    pub feed: &'static ChainlinkFeed,
+    /// 按喂价小数位数换算后的价格
+    pub price: Decimal,
+    pub decimals: u8,
+    pub round_id: u128,
+    /// 最近一次更新的 Unix 时间戳（秒）
+    pub updated_at: u64,
+}
+
+/// 一个喂价的 `decimals` 和 `latestRoundData` 调用
+pub struct FeedCalls {
+    feed: &'static ChainlinkFeed,
+    decimals: usize,
+    round: usize,
+}
+
+impl FeedCalls {
+    pub fn add(batch: &mut MulticallBatch, feed: &'static ChainlinkFeed) -> Self {
+        FeedCalls {
+            feed,
+            decimals: batch.add(feed.address, AggregatorV3Interface::decimalsCall {}),
+            round: batch.add(feed.address, AggregatorV3Interface::latestRoundDataCall {}),
+        }
+    }
+
+    /// 解码喂价并检查有效性
+    ///
+    /// 以区块时间戳 `block_timestamp` 计算喂价的新鲜度，历史查询同样按当时的区块判断；
+    /// 距上次更新超过喂价心跳、答案非正数或轮次未完成时返回错误。
+    pub fn price(&self, results: &[CallResult], block_timestamp: u64) -> Result<FeedPrice> {
+        let feed = self.feed.address;
+        let round = &results[self.round];
+        if round.return_data().is_empty() {
+            return Err(EthereumError::PriceOracleError(format!(
+                "Chainlink 喂价 {} 不存在",
+                feed
+            )));
+        }
+        let decode_error = |e: EthereumError| {
+            warn!("读取 Chainlink 喂价 {} 失败: {}", feed, e);
+            EthereumError::PriceOracleError(format!("无法读取 Chainlink 喂价 {}: {}", feed, e))
+        };
+        let decimals = results[self.decimals]
+            .decode::<AggregatorV3Interface::decimalsCall>()
+            .map_err(decode_error)?
+            ._0;
+        let round = round
+            .decode::<AggregatorV3Interface::latestRoundDataCall>()
+            .map_err(decode_error)?;
+
+        if !round.answer.is_positive() {
+            return Err(EthereumError::PriceOracleError(format!(
+                "Chainlink 喂价 {} 返回无效价格 {}",
+                feed, round.answer
+            )));
+        }
+        let updated_at = round.updatedAt.saturating_to::<u64>();
+        if updated_at == 0 || round.answeredInRound < round.roundId {
+            return Err(EthereumError::PriceOracleError(format!(
+                "Chainlink 喂价 {} 的轮次 {} 未完成",
+                feed, round.roundId
+            )));
+        }
+        let age = block_timestamp.saturating_sub(updated_at);
+        if age > self.feed.heartbeat.as_secs() {
+            return Err(EthereumError::PriceOracleError(format!(
+                "Chainlink 喂价 {} 已过期: 最后更新于 {} 秒前，超过心跳 {} 秒",
+                feed,
+                age,
+                self.feed.heartbeat.as_secs()
+            )));
+        }
+
+        let price = precision::to_decimal(U256::from(round.answer.into_raw()), decimals)?;
+        debug!(
+            "Chainlink 喂价 {}: {} (轮次 {}，{} 秒前更新)",
+            feed, price, round.roundId, age
+        );
+        Ok(FeedPrice {
+            feed: self.feed,
+            price,
+            decimals,
+            round_id: round.roundId.to::<u128>(),
+            updated_at,
+        })
+    }
+}
+
+/// `decimals` 和 `latestRoundData` 的模拟返回值（仅测试使用）
+#[cfg(test)]
+pub(crate) fn feed_results(
+    feed: Address,
+    decimals: u8,
+    answer: i128,
+    updated_at: u64,
+) -> [CallResult; 2] {
+    use alloy::primitives::aliases::U80;
+    use alloy::primitives::I256;
+    use alloy::sol_types::SolCall;
+
+    let round_id = U80::from(110_680_464_442_257_320_000u128);
+    let round = AggregatorV3Interface::latestRoundDataCall::abi_encode_returns(&(
+        round_id,
+        I256::try_from(answer).unwrap(),
+        U256::from(updated_at),
+        U256::from(updated_at),
+        round_id,
+    ));
+    [
+        CallResult::new(
+            feed,
+            true,
+            AggregatorV3Interface::decimalsCall::abi_encode_returns(&(decimals,)).into(),
+        ),
+        CallResult::new(feed, true, round.into()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    #[test]
+    fn test_route_converts_through_eth_usd() {
+        let network = NetworkProfile::mainnet();
+        let weth = network.wrapped_native.address;
+        let eth_usd = network.chainlink_feed(weth, FeedQuote::Usd).unwrap();
+        let uni = network
+            .chainlink_feed(
+                address!("1f9840a85d5aF5bf1D1762F925BDADdC4201F984"),
+                FeedQuote::Usd,
+            )
+            .unwrap();
+
+        assert_eq!(
+            route(network, weth, FeedQuote::Usd).unwrap(),
+            vec![FeedLeg {
+                feed: eth_usd,
+                invert: false
+            }]
+        );
+        assert!(route(network, weth, FeedQuote::Eth).unwrap().is_empty());
+        // UNI 只有 USD 喂价：UNI/ETH = UNI/USD ÷ ETH/USD
+        let legs = route(network, uni.base, FeedQuote::Eth).unwrap();
+        assert_eq!(legs.len(), 2);
+        assert_eq!(legs[0].feed, uni);
+        assert!(legs[1].invert);
+        assert!(route(network, Address::ZERO, FeedQuote::Usd).is_none());
+    }
+
+    #[test]
+    fn test_feed_price_checks_heartbeat() {
+        let network = NetworkProfile::mainnet();
+        let feed = network
+            .chainlink_feed(network.wrapped_native.address, FeedQuote::Usd)
+            .unwrap();
+        let mut batch = MulticallBatch::new();
+        let calls = FeedCalls::add(&mut batch, feed);
+        assert_eq!(batch.len(), 2);
+
+        // 8 位小数：2500.12345678 USD，10 分钟前更新
+        let results = feed_results(feed.address, 8, 250_012_345_678, 1735689000);
+        let reading = calls.price(&results, 1735689600).unwrap();
+        assert_eq!(reading.price, "2500.12345678".parse::<Decimal>().unwrap());
+        assert_eq!(reading.decimals, 8);
+        assert_eq!(reading.updated_at, 1735689000);
+
+        // ETH/USD 心跳为 1 小时
+        let err = calls.price(&results, 1735689000 + 3601).unwrap_err();
+        assert!(err.to_string().contains("已过期"));
+
+        let negative = feed_results(feed.address, 8, -1, 1735689000);
+        assert!(calls.price(&negative, 1735689600).is_err());
+    }
+}
@@ -0,0 +1,694 @@
+use alloy::primitives::aliases::I24;
+use alloy::primitives::{U256, U512};
+use rust_decimal::Decimal;
+use tracing::debug;
+
+use crate::error::{EthereumError, Result};
+use crate::precision;
+use crate::pricing::{v3, PoolPrice, PoolState};
+use crate::rpc::client::IERC20;
+use crate::rpc::{CallResult, MulticallBatch};
+
+/// 深度表的价格变动阈值（基点）：0.5%、1%、2%
+pub const IMPACT_THRESHOLDS_BPS: [u32; 3] = [50, 100, 200];
+
+/// 价格冲击表的标准交易规模（USD）
+pub const TRADE_SIZES_USD: [u64; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+/// Uniswap V2 交易手续费（百万分之一）
+pub const V2_FEE: u32 = 3000;
+
+const FEE_DENOMINATOR: u32 = 1_000_000;
+
+/// 当前 tick 所在位图字两侧各读取的字数，每个字覆盖 256 个 tick 间距
+const BITMAP_WORDS_EACH_SIDE: i16 = 2;
+
+/// 当前价格两侧各读取的已初始化 tick 数上限
+const MAX_TICKS_EACH_SIDE: usize = 64;
+
+/// 流动性 L 下价格在 `a` 与 `b`（a < b）之间移动对应的 token0 数量
+fn amount0(a: U256, b: U256, liquidity: u128) -> U256 {
+    if a.is_zero() {
+        return U256::MAX;
+    }
+    let numerator = (U512::from(liquidity) * U512::from(b - a)) << 96usize;
+    (numerator / U512::from(b) / U512::from(a)).saturating_to()
+}
+
+/// 流动性 L 下价格在 `a` 与 `b`（a < b）之间移动对应的 token1 数量
+fn amount1(a: U256, b: U256, liquidity: u128) -> U256 {
+    ((U512::from(liquidity) * U512::from(b - a)) >> 96usize).saturating_to()
+}
+
+/// 池在当前价格附近的流动性分布
+///
+/// 在相邻的已初始化 tick 之间流动性 L 不变，池的行为与虚拟储备量上的常数乘积池相同；
+/// V2 交易对相当于全价格范围内只有一段、L = sqrt(reserve0 * reserve1)。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityCurve {
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    /// 手续费（百万分之一）
+    fee: u32,
+    /// 当前价格之上已初始化 tick 的 (sqrtPriceX96, liquidityNet)，由近到远
+    above: Vec<(U256, i128)>,
+    /// 当前价格之下（含当前 tick）已初始化 tick 的 (sqrtPriceX96, liquidityNet)，由近到远
+    below: Vec<(U256, i128)>,
+    /// 已读取的 tick 覆盖的 sqrtPriceX96 范围，超出后流动性未知
+    lower_limit: U256,
+    upper_limit: U256,
+}
+
+impl LiquidityCurve {
+    /// V2 交易对的常数乘积曲线
+    pub fn constant_product(reserve0: u128, reserve1: u128) -> Result<Self> {
+        if reserve0 == 0 || reserve1 == 0 {
+            return Err(EthereumError::PriceOracleError(
+                "交易对储备为零".to_string(),
+            ));
+        }
+        let sqrt_price_x96 = ((U512::from(reserve1) << 192usize) / U512::from(reserve0))
+            .root(2)
+            .saturating_to();
+        let liquidity = (U256::from(reserve0) * U256::from(reserve1)).root(2);
+        Ok(LiquidityCurve {
+            sqrt_price_x96,
+            liquidity: liquidity.to(),
+            fee: V2_FEE,
+            above: Vec::new(),
+            below: Vec::new(),
+            lower_limit: U256::from(1u8),
+            upper_limit: U256::from(1u8) << 160,
+        })
+    }
+
+    /// 向上或向下移动价格时依次经过的 (起点, 终点, L) 区间，止于已读取范围的边界
+    fn segments(&self, up: bool) -> Vec<(U256, U256, u128)> {
+        let (ticks, limit) = if up {
+            (&self.above, self.upper_limit)
+        } else {
+            (&self.below, self.lower_limit)
+        };
+        let mut segments = Vec::new();
+        let mut start = self.sqrt_price_x96;
+        let mut liquidity = Some(self.liquidity);
+        for &(boundary, net) in ticks {
+            let within = if up {
+                boundary < limit
+            } else {
+                boundary > limit
+            };
+            let Some(current) = liquidity.filter(|_| within) else {
+                break;
+            };
+            segments.push((start, boundary, current));
+            start = boundary;
+            // 向上穿过 tick 时加上 liquidityNet，向下时减去
+            liquidity = if up {
+                current.checked_add_signed(net)
+            } else {
+                net.checked_neg()
+                    .and_then(|net| current.checked_add_signed(net))
+            };
+        }
+        if let Some(liquidity) = liquidity {
+            segments.push((start, limit, liquidity));
+        }
+        segments
+    }
+
+    fn with_fee(&self, amount: U256) -> U256 {
+        let kept = U512::from(FEE_DENOMINATOR - self.fee);
+        ((U512::from(amount) * U512::from(FEE_DENOMINATOR) + kept - U512::from(1u8)) / kept)
+            .saturating_to()
+    }
+
+    fn without_fee(&self, amount: U256) -> U256 {
+        (U512::from(amount) * U512::from(FEE_DENOMINATOR - self.fee) / U512::from(FEE_DENOMINATOR))
+            .saturating_to()
+    }
+
+    /// 把价格移到 `target` 需要投入（含手续费）和换出的数量；
+    /// 价格上涨时投入 token1、换出 token0，下跌时相反。超出已读取的 tick 范围时为 `None`
+    pub fn amounts_to(&self, target: U256) -> Option<(U256, U256)> {
+        let up = target > self.sqrt_price_x96;
+        let (mut amount_in, mut amount_out) = (U256::ZERO, U256::ZERO);
+        for (start, end, liquidity) in self.segments(up) {
+            let stop = if up { end.min(target) } else { end.max(target) };
+            let (amount_in_step, amount_out_step) = if up {
+                (
+                    amount1(start, stop, liquidity),
+                    amount0(start, stop, liquidity),
+                )
+            } else {
+                (
+                    amount0(stop, start, liquidity),
+                    amount1(stop, start, liquidity),
+                )
+            };
+            amount_in = amount_in.saturating_add(amount_in_step);
+            amount_out = amount_out.saturating_add(amount_out_step);
+            if stop == target {
+                return Some((self.with_fee(amount_in), amount_out));
+            }
+        }
+        None
+    }
+
+    /// 投入 `amount_in`（含手续费）换出的数量，`zero_for_one` 表示投入 token0；超出已读取的 tick 范围时为 `None`
+    pub fn swap(&self, zero_for_one: bool, amount_in: U256) -> Option<U256> {
+        let up = !zero_for_one;
+        let mut remaining = self.without_fee(amount_in);
+        let mut amount_out = U256::ZERO;
+        for (start, end, liquidity) in self.segments(up) {
+            let capacity = if up {
+                amount1(start, end, liquidity)
+            } else {
+                amount0(end, start, liquidity)
+            };
+            if remaining >= capacity {
+                remaining -= capacity;
+                amount_out = amount_out.saturating_add(if up {
+                    amount0(start, end, liquidity)
+                } else {
+                    amount1(end, start, liquidity)
+                });
+                continue;
+            }
+
+            // 在该区间内停下；capacity 大于 0 说明 L 不为零
+            let liquidity_x96 = U512::from(liquidity) << 96usize;
+            let stop: U256 = if up {
+                U512::from(start) + (U512::from(remaining) << 96usize) / U512::from(liquidity)
+            } else {
+                liquidity_x96 * U512::from(start)
+                    / (liquidity_x96 + U512::from(remaining) * U512::from(start))
+            }
+            .saturating_to();
+            return Some(amount_out.saturating_add(if up {
+                amount0(start, stop, liquidity)
+            } else {
+                amount1(stop, start, liquidity)
+            }));
+        }
+        None
+    }
+
+    /// `base` 价格相对当前变动 `bps` 基点后的 sqrtPriceX96，`up` 为上涨
+    fn target(&self, base_is_token0: bool, bps: u32, up: bool) -> U256 {
+        let moved = if up { 10_000 + bps } else { 10_000 - bps };
+        let (numerator, denominator) = if base_is_token0 {
+            (moved, 10_000)
+        } else {
+            (10_000, moved)
+        };
+        let factor = ((U512::from(numerator) << 192usize) / U512::from(denominator)).root(2);
+        ((U512::from(self.sqrt_price_x96) * factor) >> 96usize).saturating_to()
+    }
+}
+
+/// 一个池的深度读取
+///
+/// V2 交易对的储备量在报价时已经读取；V3 池先读两个代币在池中的余额和当前 tick 附近的位图，
+/// 再读位图中已初始化 tick 的 `liquidityNet`，两轮都在同一个固定区块上。
+pub struct DepthCalls {
+    pool: PoolPrice,
+    state: PoolState,
+    /// (token0 余额, token1 余额) 调用下标
+    balances: Option<(usize, usize)>,
+    /// (位图字位置, 调用下标)
+    words: Vec<(i16, usize)>,
+    /// (tick, 调用下标)
+    ticks: Vec<(i32, usize)>,
+    /// 已读取 tick 的覆盖范围
+    tick_range: (i32, i32),
+}
+
+impl DepthCalls {
+    /// 第一轮：V3 池的代币余额和当前 tick 两侧的位图
+    pub fn add(batch: &mut MulticallBatch, pool: PoolPrice, state: PoolState) -> Self {
+        let mut calls = DepthCalls {
+            pool,
+            state,
+            balances: None,
+            words: Vec::new(),
+            ticks: Vec::new(),
+            tick_range: (v3::MIN_TICK, v3::MAX_TICK),
+        };
+        let (PoolState::V3 { tick, .. }, Some(fee)) = (state, calls.pool.fee) else {
+            return calls;
+        };
+
+        let pool = calls.pool.pool;
+        let (token0, token1) = if calls.pool.token < calls.pool.quote {
+            (calls.pool.token, calls.pool.quote)
+        } else {
+            (calls.pool.quote, calls.pool.token)
+        };
+        calls.balances = Some((
+            batch.add(token0, IERC20::balanceOfCall { account: pool }),
+            batch.add(token1, IERC20::balanceOfCall { account: pool }),
+        ));
+
+        // 与 TickBitmap.position 一致：压缩 tick 向负无穷取整，高位为字位置
+        let spacing = v3::tick_spacing(fee);
+        let word = (tick.div_euclid(spacing) >> 8) as i16;
+        let (first, last) = (word - BITMAP_WORDS_EACH_SIDE, word + BITMAP_WORDS_EACH_SIDE);
+        calls.words = (first..=last)
+            .map(|position| {
+                (
+                    position,
+                    batch.add(
+                        pool,
+                        v3::IUniswapV3Pool::tickBitmapCall {
+                            wordPosition: position,
+                        },
+                    ),
+                )
+            })
+            .collect();
+        calls.tick_range = (
+            ((i32::from(first) << 8) * spacing).max(v3::MIN_TICK),
+            (((i32::from(last) + 1) << 8) * spacing).min(v3::MAX_TICK),
+        );
+        calls
+    }
+
+    pub fn pool(&self) -> &PoolPrice {
+        &self.pool
+    }
+
+    /// 第二轮：当前 tick 两侧最近的已初始化 tick
+    pub fn add_ticks(&mut self, results: &[CallResult], batch: &mut MulticallBatch) -> Result<()> {
+        let (PoolState::V3 { tick: current, .. }, Some(fee)) = (self.state, self.pool.fee) else {
+            return Ok(());
+        };
+        let spacing = v3::tick_spacing(fee);
+        let mut initialized = Vec::new();
+        for &(position, call) in &self.words {
+            let bitmap = results[call]
+                .decode::<v3::IUniswapV3Pool::tickBitmapCall>()
+                .map_err(|e| EthereumError::PriceOracleError(format!("无法读取 tick 位图: {}", e)))?
+                ._0;
+            for bit in 0..256 {
+                if bitmap.bit(bit) {
+                    initialized.push(((i32::from(position) << 8) + bit as i32) * spacing);
+                }
+            }
+        }
+
+        let above: Vec<i32> = initialized
+            .iter()
+            .copied()
+            .filter(|&tick| tick > current)
+            .take(MAX_TICKS_EACH_SIDE)
+            .collect();
+        let below: Vec<i32> = initialized
+            .iter()
+            .rev()
+            .copied()
+            .filter(|&tick| tick <= current)
+            .take(MAX_TICKS_EACH_SIDE)
+            .collect();
+        // 超出上限的 tick 未读取，覆盖范围止于最远的已读取 tick
+        if above.len() == MAX_TICKS_EACH_SIDE {
+            self.tick_range.1 = above[MAX_TICKS_EACH_SIDE - 1];
+        }
+        if below.len() == MAX_TICKS_EACH_SIDE {
+            self.tick_range.0 = below[MAX_TICKS_EACH_SIDE - 1];
+        }
+
+        let pool = self.pool.pool;
+        self.ticks = below
+            .into_iter()
+            .rev()
+            .chain(above)
+            .map(|tick| {
+                let call = batch.add(
+                    pool,
+                    v3::IUniswapV3Pool::ticksCall {
+                        tick: I24::try_from(tick).unwrap_or_default(),
+                    },
+                );
+                (tick, call)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// 池中的 (token0, token1) 数量和流动性分布
+    pub fn liquidity(
+        &self,
+        first: &[CallResult],
+        second: &[CallResult],
+    ) -> Result<(U256, U256, LiquidityCurve)> {
+        let decode_error =
+            |e: EthereumError| EthereumError::PriceOracleError(format!("无法读取池深度: {}", e));
+        match self.state {
+            PoolState::V2 { reserve0, reserve1 } => Ok((
+                U256::from(reserve0),
+                U256::from(reserve1),
+                LiquidityCurve::constant_product(reserve0, reserve1)?,
+            )),
+            PoolState::V3 {
+                sqrt_price_x96,
+                tick: current,
+                liquidity,
+            } => {
+                let (balance0, balance1) = match self.balances {
+                    Some((balance0, balance1)) => (
+                        first[balance0]
+                            .decode::<IERC20::balanceOfCall>()
+                            .map_err(decode_error)?
+                            ._0,
+                        first[balance1]
+                            .decode::<IERC20::balanceOfCall>()
+                            .map_err(decode_error)?
+                            ._0,
+                    ),
+                    None => (U256::ZERO, U256::ZERO),
+                };
+
+                let mut above = Vec::new();
+                let mut below = Vec::new();
+                for &(tick, call) in &self.ticks {
+                    let info = second[call]
+                        .decode::<v3::IUniswapV3Pool::ticksCall>()
+                        .map_err(decode_error)?;
+                    let entry = (v3::sqrt_price_at_tick(tick)?, info.liquidityNet);
+                    if tick > current {
+                        above.push(entry);
+                    } else {
+                        below.push(entry);
+                    }
+                }
+                // 调用按 tick 升序加入：上方由近到远，下方反转为由近到远
+                below.reverse();
+
+                let curve = LiquidityCurve {
+                    sqrt_price_x96,
+                    liquidity,
+                    fee: self.pool.fee.unwrap_or(V2_FEE),
+                    above,
+                    below,
+                    lower_limit: v3::sqrt_price_at_tick(self.tick_range.0)?,
+                    upper_limit: v3::sqrt_price_at_tick(self.tick_range.1)?,
+                };
+                Ok((balance0, balance1, curve))
+            }
+        }
+    }
+}
+
+/// 价格变动到某一阈值前可交易的数量
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthLevel {
+    pub bps: u32,
+    /// 把 `base` 价格压低该幅度需要卖出的 `base` 数量（含手续费）；超出已读取的 tick 范围时为 `None`
+    pub sell: Option<Decimal>,
+    pub sell_usd: Option<Decimal>,
+    /// 把 `base` 价格推高该幅度能买入的 `base` 数量
+    pub buy: Option<Decimal>,
+    /// 买入花费的报价代币的 USD 价值（含手续费）
+    pub buy_usd: Option<Decimal>,
+}
+
+/// 一笔标准规模交易的价格冲击：成交均价（含手续费）相对现货价格的偏离百分比
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeImpact {
+    pub size_usd: u64,
+    pub sell_percent: Option<Decimal>,
+    pub buy_percent: Option<Decimal>,
+}
+
+/// 池的储备量、TVL 和价格冲击
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolDepth {
+    pub pool: PoolPrice,
+    pub base_reserve: Decimal,
+    pub quote_reserve: Decimal,
+    pub base_reserve_usd: Decimal,
+    pub quote_reserve_usd: Decimal,
+    pub tvl_usd: Decimal,
+    pub levels: Vec<DepthLevel>,
+    pub impacts: Vec<TradeImpact>,
+}
+
+/// 由池中数量和流动性分布计算深度表
+///
+/// `base_usd` 和 `quote_usd` 为两个代币的 USD 价格；池中 `pool.token` 为 base、`pool.quote` 为报价代币。
+#[allow(clippy::too_many_arguments)]
+pub fn pool_depth(
+    pool: &PoolPrice,
+    amount0: U256,
+    amount1: U256,
+    curve: &LiquidityCurve,
+    base_decimals: u8,
+    quote_decimals: u8,
+    base_usd: Decimal,
+    quote_usd: Decimal,
+) -> Result<PoolDepth> {
+    let base_is_token0 = pool.token < pool.quote;
+    let (base_raw, quote_raw) = if base_is_token0 {
+        (amount0, amount1)
+    } else {
+        (amount1, amount0)
+    };
+    let base_reserve = precision::to_decimal(base_raw, base_decimals)?;
+    let quote_reserve = precision::to_decimal(quote_raw, quote_decimals)?;
+    let usd = |value: Decimal| value.round_dp(2);
+    let base_amount = |raw: U256| precision::to_decimal(raw, base_decimals).ok();
+    let quote_amount = |raw: U256| precision::to_decimal(raw, quote_decimals).ok();
+
+    let levels = IMPACT_THRESHOLDS_BPS
+        .iter()
+        .map(|&bps| {
+            // 卖出 base 使其价格下跌：投入 base、换出报价代币
+            let sell = curve
+                .amounts_to(curve.target(base_is_token0, bps, false))
+                .and_then(|(amount_in, _)| base_amount(amount_in));
+            // 买入 base 使其价格上涨：投入报价代币、换出 base
+            let buy = curve.amounts_to(curve.target(base_is_token0, bps, true));
+            let buy_cost = buy.and_then(|(amount_in, _)| quote_amount(amount_in));
+            DepthLevel {
+                bps,
+                sell,
+                sell_usd: sell.map(|amount| usd(amount * base_usd)),
+                buy: buy.and_then(|(_, amount_out)| base_amount(amount_out)),
+                buy_usd: buy_cost.map(|amount| usd(amount * quote_usd)),
+            }
+        })
+        .collect();
+
+    let spot = pool.price;
+    let impacts = TRADE_SIZES_USD
+        .iter()
+        .map(|&size_usd| {
+            let size = Decimal::from(size_usd);
+            let percent = |execution: Decimal| {
+                ((execution - spot).abs() / spot * Decimal::ONE_HUNDRED).round_dp(4)
+            };
+            let sell_percent = (!base_usd.is_zero() && !spot.is_zero())
+                .then(|| size / base_usd)
+                .and_then(|base_in| {
+                    let raw = precision::from_decimal(base_in, base_decimals).ok()?;
+                    let quote_out = quote_amount(curve.swap(base_is_token0, raw)?)?;
+                    Some(percent(quote_out / base_in))
+                });
+            let buy_percent = (!quote_usd.is_zero() && !spot.is_zero())
+                .then(|| size / quote_usd)
+                .and_then(|quote_in| {
+                    let raw = precision::from_decimal(quote_in, quote_decimals).ok()?;
+                    let base_out = base_amount(curve.swap(!base_is_token0, raw)?)?;
+                    (!base_out.is_zero()).then(|| percent(quote_in / base_out))
+                });
+            TradeImpact {
+                size_usd,
+                sell_percent,
+                buy_percent,
+            }
+        })
+        .collect();
+
+    let base_reserve_usd = usd(base_reserve * base_usd);
+    let quote_reserve_usd = usd(quote_reserve * quote_usd);
+    debug!(
+        "池 {} 的深度: TVL {} USD",
+        pool.pool,
+        base_reserve_usd + quote_reserve_usd
+    );
+    Ok(PoolDepth {
+        pool: pool.clone(),
+        base_reserve,
+        quote_reserve,
+        base_reserve_usd,
+        quote_reserve_usd,
+        tvl_usd: base_reserve_usd + quote_reserve_usd,
+        levels,
+        impacts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::NetworkProfile;
+    use crate::pricing::Protocol;
+    use alloy::primitives::aliases::{I56, U160};
+    use alloy::primitives::Address;
+    use alloy::sol_types::SolCall;
+
+    fn close(actual: Decimal, expected: &str) -> bool {
+        let expected: Decimal = expected.parse().unwrap();
+        (actual - expected).abs() <= expected.abs() / Decimal::from(10_000)
+    }
+
+    #[test]
+    fn test_constant_product_depth() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let pair = network.uniswap_v2.pair_address(weth, usdc);
+        // USDC 为 token0：2,000,000 USDC / 1,000 WETH，1 WETH = 2000 USDC
+        let (reserve0, reserve1) = (2_000_000_000_000u128, 1_000 * 10u128.pow(18));
+        let curve = LiquidityCurve::constant_product(reserve0, reserve1).unwrap();
+        let pool = PoolPrice {
+            protocol: Protocol::UniswapV2,
+            pool: pair,
+            fee: None,
+            token: weth,
+            quote: usdc,
+            price: Decimal::from(2000),
+            liquidity: curve.liquidity,
+        };
+
+        let depth = pool_depth(
+            &pool,
+            U256::from(reserve0),
+            U256::from(reserve1),
+            &curve,
+            18,
+            6,
+            Decimal::from(2000),
+            Decimal::ONE,
+        )
+        .unwrap();
+        assert_eq!(depth.base_reserve, Decimal::from(1000));
+        assert_eq!(depth.tvl_usd, Decimal::from(4_000_000));
+
+        // 价格下跌 1%：x' = x / sqrt(0.99)，卖出 1000 × (1/sqrt(0.99) - 1) / 0.997 ≈ 5.0530 WETH
+        let level = &depth.levels[1];
+        assert_eq!(level.bps, 100);
+        assert!(close(level.sell.unwrap(), "5.0530"));
+        // 价格上涨 1%：买入 1000 × (1 - 1/sqrt(1.01)) ≈ 4.9629 WETH，花费 2,000,000 × (sqrt(1.01) - 1) / 0.997 ≈ 10,005 USDC
+        assert!(close(level.buy.unwrap(), "4.9629"));
+        assert!(close(level.buy_usd.unwrap(), "10005.14"));
+
+        // 卖出 1 万美元（5 WETH）：换出 2,000,000 × 4.985 / 1004.985 ≈ 9920.55 USDC，成交价低于现货约 0.7945%
+        let impact = &depth.impacts[1];
+        assert_eq!(impact.size_usd, 10_000);
+        assert!(close(impact.sell_percent.unwrap(), "0.7945"));
+        assert!(depth.impacts[3].buy_percent.is_some());
+    }
+
+    #[test]
+    fn test_v3_depth_crosses_initialized_ticks() {
+        let pool = Address::repeat_byte(0x11);
+        let (token0, token1) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        let spot = PoolPrice {
+            protocol: Protocol::UniswapV3,
+            pool,
+            fee: Some(500),
+            token: token0,
+            quote: token1,
+            price: Decimal::ONE,
+            liquidity: 10u128.pow(18),
+        };
+        let state = PoolState::V3 {
+            sqrt_price_x96: U256::from(1u8) << 96,
+            tick: 0,
+            liquidity: 10u128.pow(18),
+        };
+
+        let mut batch = MulticallBatch::new();
+        let mut calls = DepthCalls::add(&mut batch, spot.clone(), state);
+        // 两个余额，tick 0 所在字两侧共 5 个位图字
+        assert_eq!(batch.len(), 2 + 5);
+
+        // 间距 10：tick 100 是第 0 字的第 10 位，tick -100 是第 -1 字的第 246 位
+        let balance = |amount: u64| {
+            CallResult::new(
+                token0,
+                true,
+                IERC20::balanceOfCall::abi_encode_returns(&(U256::from(amount),)).into(),
+            )
+        };
+        let word = |bits: U256| {
+            CallResult::new(
+                pool,
+                true,
+                v3::IUniswapV3Pool::tickBitmapCall::abi_encode_returns(&(bits,)).into(),
+            )
+        };
+        let first = vec![
+            balance(5_000),
+            balance(7_000),
+            word(U256::ZERO),
+            word(U256::from(1u8) << 246),
+            word(U256::from(1u8) << 10),
+            word(U256::ZERO),
+            word(U256::ZERO),
+        ];
+        let mut batch = MulticallBatch::new();
+        calls.add_ticks(&first, &mut batch).unwrap();
+        assert_eq!(batch.len(), 2);
+
+        // 价格区间 [-100, 100] 之外流动性减半
+        let tick = |net: i128| {
+            CallResult::new(
+                pool,
+                true,
+                v3::IUniswapV3Pool::ticksCall::abi_encode_returns(&(
+                    net.unsigned_abs(),
+                    net,
+                    U256::ZERO,
+                    U256::ZERO,
+                    I56::ZERO,
+                    U160::ZERO,
+                    0u32,
+                    true,
+                ))
+                .into(),
+            )
+        };
+        let second = vec![tick(5 * 10i128.pow(17)), tick(-5 * 10i128.pow(17))];
+        let (balance0, balance1, curve) = calls.liquidity(&first, &second).unwrap();
+        assert_eq!((balance0, balance1), (U256::from(5_000), U256::from(7_000)));
+
+        // 上涨 1% 仍在 tick 100 内，上涨 2% 需要穿过 tick 100
+        let current = U256::from(1u8) << 96usize;
+        let tick_100 = v3::sqrt_price_at_tick(100).unwrap();
+        let one_percent = curve.target(true, 100, true);
+        let two_percent = curve.target(true, 200, true);
+        assert!(one_percent < tick_100 && tick_100 < two_percent);
+        let (within, _) = curve.amounts_to(one_percent).unwrap();
+        assert_eq!(
+            within,
+            curve.with_fee(amount1(current, one_percent, 10u128.pow(18)))
+        );
+        let (crossing, _) = curve.amounts_to(two_percent).unwrap();
+        let expected = amount1(current, tick_100, 10u128.pow(18))
+            + amount1(tick_100, two_percent, 5 * 10u128.pow(17));
+        assert_eq!(crossing, curve.with_fee(expected));
+
+        // 换出数量与移动价格所需的投入互为反函数
+        let (amount_in, amount_out) = curve.amounts_to(two_percent).unwrap();
+        let swapped = curve.swap(false, amount_in).unwrap();
+        assert!(swapped.abs_diff(amount_out) <= U256::from(10u8).pow(U256::from(6u8)));
+
+        // 超出已读取的位图范围（两侧各 2 个字，tick -5120 到 7680）
+        assert!(curve
+            .amounts_to(v3::sqrt_price_at_tick(8000).unwrap())
+            .is_none());
+        assert!(curve.swap(false, U256::MAX >> 64).is_none());
+    }
+}
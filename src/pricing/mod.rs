@@ -0,0 +1,390 @@
+use alloy::primitives::{Address, U256};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::{EthereumError, Result};
+use crate::network::NetworkProfile;
+use crate::rpc::{CallResult, MulticallBatch};
+
+pub mod aggregate;
+pub mod chainlink;
+pub mod depth;
+pub mod v2;
+pub mod v3;
+
+/// 报价来自的 DEX 协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    UniswapV2,
+    UniswapV3,
+}
+
+impl Protocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::UniswapV2 => "Uniswap V2",
+            Protocol::UniswapV3 => "Uniswap V3",
+        }
+    }
+}
+
+/// 单个池给出的 `token` 以 `quote` 计价的现货价格
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolPrice {
+    pub protocol: Protocol,
+    pub pool: Address,
+    /// V3 费率档位（百万分之一），V2 为 `None`
+    pub fee: Option<u32>,
+    pub token: Address,
+    pub quote: Address,
+    pub price: Decimal,
+    /// 当前价格处的流动性 L（V2 为 sqrt(reserve0 * reserve1)），同一交易对的各池可直接比较深度
+    pub liquidity: u128,
+}
+
+/// 池在固定区块上的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolState {
+    V2 {
+        reserve0: u128,
+        reserve1: u128,
+    },
+    V3 {
+        sqrt_price_x96: U256,
+        tick: i32,
+        liquidity: u128,
+    },
+}
+
+/// 一对代币在 Uniswap V2 交易对和各 V3 费率档位池上的现货报价，以及可选的 V3 TWAP
+///
+/// 池地址在本地按 CREATE2 计算，所有读取加入同一个 [`MulticallBatch`]。
+pub struct SpotCalls {
+    token: Address,
+    quote: Address,
+    v2: (Address, usize),
+    v3: Vec<V3Calls>,
+    twap_window: Option<u32>,
+}
+
+/// 一个 V3 池的各调用在批次中的下标
+struct V3Calls {
+    fee: u32,
+    pool: Address,
+    slot0: usize,
+    liquidity: usize,
+    observe: Option<usize>,
+}
+
+impl SpotCalls {
+    /// 把 `token`/`quote` 所有候选池的读取加入 `batch`：
+    /// 先是 V2 的 `getReserves`，再按 [`v3::FEE_TIERS`] 顺序加入每个 V3 池的 `slot0` 和 `liquidity`，
+    /// 指定 `twap_window` 时每个 V3 池再加一个 `observe([twap_window, 0])`
+    pub fn add(
+        batch: &mut MulticallBatch,
+        network: &NetworkProfile,
+        token: Address,
+        quote: Address,
+        twap_window: Option<u32>,
+    ) -> Self {
+        let pair = network.uniswap_v2.pair_address(token, quote);
+        let v2 = (
+            pair,
+            batch.add(pair, v2::IUniswapV2Pair::getReservesCall {}),
+        );
+        let v3 = v3::FEE_TIERS
+            .iter()
+            .map(|&fee| {
+                let pool = network.uniswap_v3.pool_address(token, quote, fee);
+                V3Calls {
+                    fee,
+                    pool,
+                    slot0: batch.add(pool, v3::IUniswapV3Pool::slot0Call {}),
+                    liquidity: batch.add(pool, v3::IUniswapV3Pool::liquidityCall {}),
+                    observe: twap_window.map(|window| {
+                        batch.add(
+                            pool,
+                            v3::IUniswapV3Pool::observeCall {
+                                secondsAgos: vec![window, 0],
+                            },
+                        )
+                    }),
+                }
+            })
+            .collect();
+        SpotCalls {
+            token,
+            quote,
+            v2,
+            v3,
+            twap_window,
+        }
+    }
+
+    /// 各候选池的报价，V2 在前；不存在或没有流动性的池为错误
+    pub fn quotes(
+        &self,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+    ) -> Vec<Result<PoolPrice>> {
+        let (pair, reserves_call) = self.v2;
+        std::iter::once(v2::pool_price(
+            pair,
+            self.token,
+            self.quote,
+            &results[reserves_call],
+            token_decimals,
+            quote_decimals,
+        ))
+        .chain(
+            self.v3
+                .iter()
+                .map(|calls| self.v3_quote(calls, results, token_decimals, quote_decimals)),
+        )
+        .collect()
+    }
+
+    fn v3_quote(
+        &self,
+        calls: &V3Calls,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+    ) -> Result<PoolPrice> {
+        v3::pool_price(
+            calls.pool,
+            calls.fee,
+            self.token,
+            self.quote,
+            &results[calls.slot0],
+            &results[calls.liquidity],
+            token_decimals,
+            quote_decimals,
+        )
+    }
+
+    /// 候选池 `pool` 的储备量或 V3 价格与流动性
+    pub fn state(&self, results: &[CallResult], pool: &PoolPrice) -> Result<PoolState> {
+        let decode_error =
+            |e: EthereumError| EthereumError::PriceOracleError(format!("无法读取池状态: {}", e));
+        if pool.pool == self.v2.0 {
+            let (reserve0, reserve1) = v2::reserves(&results[self.v2.1])?;
+            return Ok(PoolState::V2 { reserve0, reserve1 });
+        }
+        let calls = self
+            .v3
+            .iter()
+            .find(|calls| calls.pool == pool.pool)
+            .ok_or_else(|| EthereumError::PriceOracleError(format!("{} 不是候选池", pool.pool)))?;
+        let slot0 = results[calls.slot0]
+            .decode::<v3::IUniswapV3Pool::slot0Call>()
+            .map_err(decode_error)?;
+        let liquidity = results[calls.liquidity]
+            .decode::<v3::IUniswapV3Pool::liquidityCall>()
+            .map_err(decode_error)?
+            ._0;
+        Ok(PoolState::V3 {
+            sqrt_price_x96: U256::from(slot0.sqrtPriceX96),
+            tick: slot0.tick.as_i32(),
+            liquidity,
+        })
+    }
+
+    /// 当前流动性最深、观测记录覆盖整个窗口的 V3 池的 TWAP
+    ///
+    /// 更深的池因观测基数不足被跳过时，返回的说明中逐一列出。
+    pub fn twap(
+        &self,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+    ) -> Result<(v3::PoolTwap, Vec<String>)> {
+        let window = self
+            .twap_window
+            .ok_or_else(|| EthereumError::PriceOracleError("没有读取 V3 观测记录".to_string()))?;
+        let mut pools: Vec<(&V3Calls, PoolPrice)> = self
+            .v3
+            .iter()
+            .filter_map(|calls| {
+                self.v3_quote(calls, results, token_decimals, quote_decimals)
+                    .ok()
+                    .map(|spot| (calls, spot))
+            })
+            .collect();
+        pools.sort_by_key(|(_, spot)| std::cmp::Reverse(spot.liquidity));
+
+        let mut notes = Vec::new();
+        for (calls, spot) in pools {
+            let Some(observe) = calls.observe else {
+                continue;
+            };
+            match v3::pool_twap(
+                &spot,
+                &results[calls.slot0],
+                &results[observe],
+                window,
+                token_decimals,
+                quote_decimals,
+            ) {
+                Ok(twap) => return Ok((twap, notes)),
+                Err(e) => notes.push(format!(
+                    "Uniswap V3 池 {} (费率 {}) 无法计算 TWAP: {}",
+                    calls.pool, calls.fee, e
+                )),
+            }
+        }
+        Err(EthereumError::PriceOracleError(if notes.is_empty() {
+            format!(
+                "{}/{} 没有可用的 Uniswap V3 流动性池",
+                self.token, self.quote
+            )
+        } else {
+            notes.join("; ")
+        }))
+    }
+
+    /// 流动性最深的池的报价
+    pub fn deepest(
+        &self,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+    ) -> Result<PoolPrice> {
+        self.deepest_matching(results, token_decimals, quote_decimals, None)
+    }
+
+    /// 只在协议 `protocol` 的池中取流动性最深的池的报价
+    pub fn deepest_of(
+        &self,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+        protocol: Protocol,
+    ) -> Result<PoolPrice> {
+        self.deepest_matching(results, token_decimals, quote_decimals, Some(protocol))
+    }
+
+    fn deepest_matching(
+        &self,
+        results: &[CallResult],
+        token_decimals: u8,
+        quote_decimals: u8,
+        protocol: Option<Protocol>,
+    ) -> Result<PoolPrice> {
+        self.quotes(results, token_decimals, quote_decimals)
+            .into_iter()
+            .filter_map(|quote| {
+                quote
+                    .map_err(|e| debug!("跳过 {}/{} 的候选池: {}", self.token, self.quote, e))
+                    .ok()
+            })
+            .filter(|quote| protocol.is_none_or(|protocol| quote.protocol == protocol))
+            .max_by_key(|quote| quote.liquidity)
+            .ok_or_else(|| {
+                EthereumError::PriceOracleError(format!(
+                    "{}/{} 没有可用的 {} 流动性池",
+                    self.token,
+                    self.quote,
+                    protocol.map_or("Uniswap", |protocol| protocol.name())
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Bytes, U256};
+
+    #[test]
+    fn test_deepest_pool_sets_price() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let mut batch = MulticallBatch::new();
+        let calls = SpotCalls::add(&mut batch, network, weth, usdc, None);
+        assert_eq!(batch.len(), 1 + 2 * v3::FEE_TIERS.len());
+
+        let pair = network.uniswap_v2.pair_address(weth, usdc);
+        let pool = |fee| network.uniswap_v3.pool_address(weth, usdc, fee);
+        let missing = |address| CallResult::new(address, true, Bytes::new());
+        // V2: 2000 USDC/WETH，L ≈ 4.5e16；0.05% 池: 2500，L = 1e18；0.3% 池: 1600，L = 1e17；其余未部署
+        let results = vec![
+            v2::reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18)),
+            missing(pool(100)),
+            missing(pool(100)),
+            v3::slot0_result(pool(500), U256::from(20_000u64) << 96),
+            v3::liquidity_result(pool(500), 10u128.pow(18)),
+            v3::slot0_result(pool(3000), U256::from(25_000u64) << 96),
+            v3::liquidity_result(pool(3000), 10u128.pow(17)),
+            missing(pool(10000)),
+            missing(pool(10000)),
+        ];
+
+        let quotes = calls.quotes(&results, 18, 6);
+        assert_eq!(quotes.len(), 5);
+        assert_eq!(quotes[0].as_ref().unwrap().price, Decimal::from(2000));
+        assert!(quotes[1].is_err());
+
+        let deepest = calls.deepest(&results, 18, 6).unwrap();
+        assert_eq!(deepest.pool, pool(500));
+        assert_eq!(deepest.fee, Some(500));
+        assert_eq!(deepest.price, Decimal::from(2500));
+        let v2 = calls
+            .deepest_of(&results, 18, 6, Protocol::UniswapV2)
+            .unwrap();
+        assert_eq!(v2.pool, pair);
+
+        let none: Vec<CallResult> = (0..batch.len()).map(|_| missing(pair)).collect();
+        assert!(calls.deepest(&none, 18, 6).is_err());
+    }
+
+    #[test]
+    fn test_twap_skips_pools_with_short_history() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let mut batch = MulticallBatch::new();
+        let calls = SpotCalls::add(&mut batch, network, weth, usdc, Some(1800));
+        assert_eq!(batch.len(), 1 + 3 * v3::FEE_TIERS.len());
+
+        let pair = network.uniswap_v2.pair_address(weth, usdc);
+        let pool = |fee| network.uniswap_v3.pool_address(weth, usdc, fee);
+        let missing = |address| CallResult::new(address, true, Bytes::new());
+        // 0.05% 池最深但观测记录不足 30 分钟（observe 回退），改用 0.3% 池
+        let mut results = vec![v2::reserves_result(
+            pair,
+            2_000_000_000_000,
+            1_000 * 10u128.pow(18),
+        )];
+        results.extend([missing(pool(100)), missing(pool(100)), missing(pool(100))]);
+        results.extend([
+            v3::slot0_result(pool(500), U256::from(20_000u64) << 96),
+            v3::liquidity_result(pool(500), 10u128.pow(18)),
+            CallResult::new(pool(500), false, Bytes::new()),
+        ]);
+        results.extend([
+            v3::slot0_result(pool(3000), U256::from(20_000u64) << 96),
+            v3::liquidity_result(pool(3000), 10u128.pow(17)),
+            v3::observe_result(pool(3000), 1800, 200000, 10u128.pow(17)),
+        ]);
+        results.extend([
+            missing(pool(10000)),
+            missing(pool(10000)),
+            missing(pool(10000)),
+        ]);
+
+        let (twap, notes) = calls.twap(&results, 18, 6).unwrap();
+        assert_eq!(twap.quote.fee, Some(3000));
+        assert_eq!(twap.mean_tick, 200000);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains(&pool(500).to_string()));
+        assert!(notes[0].contains("观测基数"));
+
+        // 没有请求 TWAP 时不读取观测记录
+        let spot_only = SpotCalls::add(&mut MulticallBatch::new(), network, weth, usdc, None);
+        assert!(spot_only.twap(&results, 18, 6).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use alloy::primitives::{Address, U256};
+use alloy::sol;
+use rust_decimal::Decimal;
+use tracing::{debug, warn};
+
+use crate::error::{EthereumError, Result};
+use crate::precision;
+use crate::pricing::{PoolPrice, Protocol};
+use crate::rpc::CallResult;
+
+// Uniswap V2 Pair contract interface
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    contract IUniswapV2Pair {
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+    }
+}
+
+/// 由交易对储备量计算 `token` 以 `quote` 计价的价格
+///
+/// 交易对中地址较小的代币为 token0，因此无需再查询 `token0()`。
+pub fn price_from_reserves(
+    token: Address,
+    quote: Address,
+    reserves: &CallResult,
+    token_decimals: u8,
+    quote_decimals: u8,
+) -> Result<Decimal> {
+    let reserves = decode_reserves(reserves)?;
+
+    // 确定储备量的顺序
+    let (reserve_token, reserve_quote) = if token < quote {
+        (reserves.reserve0, reserves.reserve1)
+    } else {
+        (reserves.reserve1, reserves.reserve0)
+    };
+
+    if reserve_quote == 0 {
+        return Err(EthereumError::PriceOracleError(
+            "报价代币储备为零".to_string(),
+        ));
+    }
+
+    // 计算价格: (reserve_quote / 10^quote_decimals) / (reserve_token / 10^token_decimals)
+    let reserve_token_decimal = precision::to_decimal(U256::from(reserve_token), token_decimals)?;
+    let reserve_quote_decimal = precision::to_decimal(U256::from(reserve_quote), quote_decimals)?;
+
+    if reserve_token_decimal.is_zero() {
+        return Err(EthereumError::PriceOracleError("代币储备为零".to_string()));
+    }
+    let price = reserve_quote_decimal / reserve_token_decimal;
+
+    debug!(
+        "从 Uniswap 池获取价格: {} = {} (报价代币 {})",
+        token, price, quote
+    );
+    Ok(price)
+}
+
+/// 交易对 `pair` 的报价，流动性按 sqrt(reserve0 * reserve1) 计算，与 V3 的 L 同一量纲
+pub fn pool_price(
+    pair: Address,
+    token: Address,
+    quote: Address,
+    reserves: &CallResult,
+    token_decimals: u8,
+    quote_decimals: u8,
+) -> Result<PoolPrice> {
+    let price = price_from_reserves(token, quote, reserves, token_decimals, quote_decimals)?;
+    let reserves = decode_reserves(reserves)?;
+    let liquidity = (U256::from(reserves.reserve0) * U256::from(reserves.reserve1)).root(2);
+
+    Ok(PoolPrice {
+        protocol: Protocol::UniswapV2,
+        pool: pair,
+        fee: None,
+        token,
+        quote,
+        price,
+        liquidity: liquidity.to::<u128>(),
+    })
+}
+
+/// 交易对的 (reserve0, reserve1)
+pub fn reserves(reserves: &CallResult) -> Result<(u128, u128)> {
+    let reserves = decode_reserves(reserves)?;
+    Ok((reserves.reserve0.to(), reserves.reserve1.to()))
+}
+
+fn decode_reserves(reserves: &CallResult) -> Result<IUniswapV2Pair::getReservesReturn> {
+    // 交易对未部署时，对该地址的调用成功但没有返回数据
+    if reserves.return_data().is_empty() {
+        return Err(EthereumError::PriceOracleError("交易对不存在".to_string()));
+    }
+    reserves
+        .decode::<IUniswapV2Pair::getReservesCall>()
+        .map_err(|e| {
+            warn!("获取储备量失败: {}", e);
+            EthereumError::PriceOracleError(format!("无法获取储备量: {}", e))
+        })
+}
+
+/// `getReserves` 的模拟返回值（仅测试使用）
+#[cfg(test)]
+pub(crate) fn reserves_result(pair: Address, reserve0: u128, reserve1: u128) -> CallResult {
+    use alloy::primitives::aliases::U112;
+    use alloy::sol_types::SolCall;
+
+    let data = IUniswapV2Pair::getReservesCall::abi_encode_returns(&(
+        U112::from(reserve0),
+        U112::from(reserve1),
+        0u32,
+    ));
+    CallResult::new(pair, true, data.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::NetworkProfile;
+    use alloy::primitives::Bytes;
+
+    #[test]
+    fn test_price_from_reserves_orders_tokens() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let pair = network.uniswap_v2.pair_address(weth, usdc);
+        // USDC 地址较小，为 token0：2,000,000 USDC / 1,000 WETH
+        let reserves = reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18));
+
+        let eth_in_usdc = price_from_reserves(weth, usdc, &reserves, 18, 6).unwrap();
+        assert_eq!(eth_in_usdc, Decimal::from(2000));
+
+        let usdc_in_eth = price_from_reserves(usdc, weth, &reserves, 6, 18).unwrap();
+        assert_eq!(usdc_in_eth, Decimal::new(5, 4));
+
+        let missing = CallResult::new(pair, true, Bytes::new());
+        let err = price_from_reserves(weth, usdc, &missing, 18, 6).unwrap_err();
+        assert!(err.to_string().contains("交易对不存在"));
+
+        // sqrt(2e12 * 1e21)
+        let quote = pool_price(pair, weth, usdc, &reserves, 18, 6).unwrap();
+        assert_eq!(quote.protocol, Protocol::UniswapV2);
+        assert_eq!(quote.liquidity, 44_721_359_549_995_793);
+    }
+}
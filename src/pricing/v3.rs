@@ -0,0 +1,486 @@
+use alloy::primitives::aliases::U160;
+use alloy::primitives::{Address, U256, U512};
+use alloy::sol;
+use rust_decimal::Decimal;
+use tracing::{debug, warn};
+
+use crate::error::{EthereumError, Result};
+use crate::precision;
+use crate::pricing::{PoolPrice, Protocol};
+use crate::rpc::CallResult;
+
+// Uniswap V3 Pool contract interface
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    contract IUniswapV3Pool {
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked);
+        function liquidity() external view returns (uint128);
+        function tickBitmap(int16 wordPosition) external view returns (uint256);
+        function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized);
+        function observe(uint32[] calldata secondsAgos) external view returns (int56[] memory tickCumulatives, uint160[] memory secondsPerLiquidityCumulativeX128s);
+    }
+}
+
+/// Uniswap V3 Factory 默认启用的费率档位（百万分之一）：0.01%、0.05%、0.3%、1%
+pub const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+/// 费率档位对应的 tick 间距
+pub fn tick_spacing(fee: u32) -> i32 {
+    match fee {
+        100 => 1,
+        500 => 10,
+        3000 => 60,
+        _ => 200,
+    }
+}
+
+/// 默认的 TWAP 窗口（秒）
+pub const DEFAULT_TWAP_WINDOW: u32 = 1800;
+
+/// TickMath 允许的最小 tick
+pub const MIN_TICK: i32 = -887272;
+/// TickMath 允许的最大 tick
+pub const MAX_TICK: i32 = 887272;
+
+/// 1/sqrt(1.0001)^(2^i) 的 Q128.128 值，与 TickMath.getSqrtRatioAtTick 使用的常量一致
+const TICK_RATIOS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// `tick` 处的 `sqrtPriceX96`，逐位移植自 TickMath.getSqrtRatioAtTick
+pub fn sqrt_price_at_tick(tick: i32) -> Result<U256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(EthereumError::PriceOracleError(format!(
+            "tick {} 超出范围",
+            tick
+        )));
+    }
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(TICK_RATIOS[0])
+    } else {
+        U256::from(1u8) << 128
+    };
+    for (bit, &factor) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // Q128.128 转为 Q64.96，向上取整
+    let round_up = !(ratio % (U256::from(1u8) << 32usize)).is_zero();
+    Ok((ratio >> 32) + U256::from(round_up as u8))
+}
+
+/// 池在 `window` 秒内的时间加权平均报价
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolTwap {
+    /// 价格为 TWAP，流动性为当前价格处的 L
+    pub quote: PoolPrice,
+    pub window: u32,
+    /// 窗口内的算术平均 tick
+    pub mean_tick: i32,
+    /// 窗口内的调和平均流动性
+    pub harmonic_mean_liquidity: u128,
+    pub observation_cardinality: u16,
+    pub observation_cardinality_next: u16,
+}
+
+/// 由 `observe([window, 0])` 的返回值计算算术平均 tick 和调和平均流动性，与 OracleLibrary.consult 一致
+pub fn consult(observations: &IUniswapV3Pool::observeReturn, window: u32) -> Result<(i32, u128)> {
+    let invalid = || EthereumError::PriceOracleError("observe 返回的观测数量无效".to_string());
+    let (ticks, seconds_per_liquidity) = match (
+        observations.tickCumulatives.as_slice(),
+        observations.secondsPerLiquidityCumulativeX128s.as_slice(),
+    ) {
+        ([start, end], [spl_start, spl_end]) => (
+            (
+                i64::try_from(*start).map_err(|_| invalid())?,
+                i64::try_from(*end).map_err(|_| invalid())?,
+            ),
+            spl_end.wrapping_sub(*spl_start),
+        ),
+        _ => return Err(invalid()),
+    };
+
+    // 向负无穷取整
+    let delta = ticks.1 - ticks.0;
+    let window_i64 = i64::from(window);
+    let mut mean_tick = delta / window_i64;
+    if delta < 0 && delta % window_i64 != 0 {
+        mean_tick -= 1;
+    }
+    let mean_tick = i32::try_from(mean_tick).map_err(|_| invalid())?;
+
+    if seconds_per_liquidity.is_zero() {
+        return Err(EthereumError::PriceOracleError(
+            "窗口内池中没有流动性".to_string(),
+        ));
+    }
+    let harmonic_mean_liquidity: U256 =
+        U256::from(window) * U256::from(U160::MAX) / (U256::from(seconds_per_liquidity) << 32usize);
+    Ok((mean_tick, harmonic_mean_liquidity.saturating_to::<u128>()))
+}
+
+/// 现货报价 `spot` 所在的池在 `window` 秒内的 TWAP
+///
+/// `observe` 回退说明池的观测记录覆盖不了整个窗口，错误中给出当前观测基数。
+pub fn pool_twap(
+    spot: &PoolPrice,
+    slot0: &CallResult,
+    observe: &CallResult,
+    window: u32,
+    token_decimals: u8,
+    quote_decimals: u8,
+) -> Result<PoolTwap> {
+    let slot0 = slot0
+        .decode::<IUniswapV3Pool::slot0Call>()
+        .map_err(|e| EthereumError::PriceOracleError(format!("无法读取池状态: {}", e)))?;
+    if !observe.success() {
+        return Err(EthereumError::PriceOracleError(format!(
+            "观测基数 {}（下一基数 {}）不足以覆盖 {} 秒窗口",
+            slot0.observationCardinality, slot0.observationCardinalityNext, window
+        )));
+    }
+    let observations = observe
+        .decode::<IUniswapV3Pool::observeCall>()
+        .map_err(|e| EthereumError::PriceOracleError(format!("无法读取观测记录: {}", e)))?;
+    let (mean_tick, harmonic_mean_liquidity) = consult(&observations, window)?;
+
+    let price = price_from_sqrt_price(
+        sqrt_price_at_tick(mean_tick)?,
+        spot.token,
+        spot.quote,
+        token_decimals,
+        quote_decimals,
+    )?;
+    debug!(
+        "Uniswap V3 池 {} 的 {} 秒 TWAP: tick {}，价格 {}",
+        spot.pool, window, mean_tick, price
+    );
+
+    Ok(PoolTwap {
+        quote: PoolPrice {
+            price,
+            ..spot.clone()
+        },
+        window,
+        mean_tick,
+        harmonic_mean_liquidity,
+        observation_cardinality: slot0.observationCardinality,
+        observation_cardinality_next: slot0.observationCardinalityNext,
+    })
+}
+
+/// 由 `sqrtPriceX96` 计算 `token` 以 `quote` 计价的价格
+///
+/// 池价格为 token1/token0 的原始数量比 `(sqrtPriceX96 / 2^96)^2`，再按两个代币的小数位数换算。
+/// 中间值超出 `Decimal` 范围，因此在 U512 上计算比值。
+pub fn price_from_sqrt_price(
+    sqrt_price_x96: U256,
+    token: Address,
+    quote: Address,
+    token_decimals: u8,
+    quote_decimals: u8,
+) -> Result<Decimal> {
+    if sqrt_price_x96.is_zero() {
+        return Err(EthereumError::PriceOracleError("池未初始化".to_string()));
+    }
+    let overflow = || EthereumError::PrecisionError("小数位数过大".to_string());
+    let pow10 = |decimals: u8| {
+        U512::from(10u8)
+            .checked_pow(U512::from(decimals))
+            .ok_or_else(overflow)
+    };
+
+    let sqrt_price = U512::from(sqrt_price_x96);
+    let price_x192 = sqrt_price * sqrt_price;
+    let q192 = U512::from(1u8) << 192;
+    // token 为 token0 时价格为 token1/token0，否则取倒数
+    let (numerator, denominator) = if token < quote {
+        (price_x192, q192)
+    } else {
+        (q192, price_x192)
+    };
+
+    let numerator = numerator
+        .checked_mul(pow10(token_decimals)?)
+        .ok_or_else(overflow)?;
+    let denominator = denominator
+        .checked_mul(pow10(quote_decimals)?)
+        .ok_or_else(overflow)?;
+    precision::ratio_to_decimal(numerator, denominator)
+}
+
+/// 费率档位 `fee` 的池 `pool` 的报价
+///
+/// 池未部署（调用没有返回数据）、未初始化或当前价格区间没有流动性时返回错误。
+#[allow(clippy::too_many_arguments)]
+pub fn pool_price(
+    pool: Address,
+    fee: u32,
+    token: Address,
+    quote: Address,
+    slot0: &CallResult,
+    liquidity: &CallResult,
+    token_decimals: u8,
+    quote_decimals: u8,
+) -> Result<PoolPrice> {
+    if slot0.return_data().is_empty() {
+        return Err(EthereumError::PriceOracleError("池不存在".to_string()));
+    }
+    let decode_error = |e: EthereumError| {
+        warn!("读取 V3 池 {} 失败: {}", pool, e);
+        EthereumError::PriceOracleError(format!("无法读取池状态: {}", e))
+    };
+    let slot0 = slot0
+        .decode::<IUniswapV3Pool::slot0Call>()
+        .map_err(decode_error)?;
+    let liquidity = liquidity
+        .decode::<IUniswapV3Pool::liquidityCall>()
+        .map_err(decode_error)?
+        ._0;
+    if liquidity == 0 {
+        return Err(EthereumError::PriceOracleError(
+            "池中没有流动性".to_string(),
+        ));
+    }
+
+    let price = price_from_sqrt_price(
+        U256::from(slot0.sqrtPriceX96),
+        token,
+        quote,
+        token_decimals,
+        quote_decimals,
+    )?;
+    debug!(
+        "从 Uniswap V3 池 {} (费率 {}) 获取价格: {} = {} (报价代币 {})",
+        pool, fee, token, price, quote
+    );
+
+    Ok(PoolPrice {
+        protocol: Protocol::UniswapV3,
+        pool,
+        fee: Some(fee),
+        token,
+        quote,
+        price,
+        liquidity,
+    })
+}
+
+/// `slot0` 的模拟返回值（仅测试使用）
+#[cfg(test)]
+pub(crate) fn slot0_result(pool: Address, sqrt_price_x96: U256) -> CallResult {
+    use alloy::primitives::aliases::{I24, U160};
+    use alloy::sol_types::SolCall;
+
+    let data = IUniswapV3Pool::slot0Call::abi_encode_returns(&(
+        U160::from(sqrt_price_x96),
+        I24::ZERO,
+        0u16,
+        1u16,
+        1u16,
+        0u8,
+        true,
+    ));
+    CallResult::new(pool, true, data.into())
+}
+
+/// `observe([window, 0])` 的模拟返回值：窗口内平均 tick 为 `mean_tick`、调和平均流动性约为 `liquidity`（仅测试使用）
+#[cfg(test)]
+pub(crate) fn observe_result(
+    pool: Address,
+    window: u32,
+    mean_tick: i32,
+    liquidity: u128,
+) -> CallResult {
+    use alloy::primitives::aliases::I56;
+    use alloy::sol_types::SolCall;
+
+    let start = 1_000_000_000i64;
+    let end = start + i64::from(mean_tick) * i64::from(window);
+    let seconds_per_liquidity: U256 =
+        U256::from(window) * U256::from(U160::MAX) / (U256::from(liquidity) << 32usize);
+    let data = IUniswapV3Pool::observeCall::abi_encode_returns(&(
+        vec![I56::try_from(start).unwrap(), I56::try_from(end).unwrap()],
+        vec![
+            U160::from(1u8),
+            U160::from(seconds_per_liquidity) + U160::from(1u8),
+        ],
+    ));
+    CallResult::new(pool, true, data.into())
+}
+
+/// `liquidity` 的模拟返回值（仅测试使用）
+#[cfg(test)]
+pub(crate) fn liquidity_result(pool: Address, liquidity: u128) -> CallResult {
+    use alloy::sol_types::SolCall;
+
+    let data = IUniswapV3Pool::liquidityCall::abi_encode_returns(&(liquidity,));
+    CallResult::new(pool, true, data.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::NetworkProfile;
+    use alloy::primitives::Bytes;
+
+    #[test]
+    fn test_price_from_sqrt_price() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        // USDC 为 token0：1 WETH = 2500 USDC 时原始价格 token1/token0 = 1e18 / 2500e6 = 4e8
+        let sqrt_price = U256::from(20_000u64) << 96;
+
+        let eth_in_usdc = price_from_sqrt_price(sqrt_price, weth, usdc, 18, 6).unwrap();
+        assert_eq!(eth_in_usdc, Decimal::from(2500));
+        let usdc_in_eth = price_from_sqrt_price(sqrt_price, usdc, weth, 6, 18).unwrap();
+        assert_eq!(usdc_in_eth, Decimal::new(4, 4));
+
+        assert!(price_from_sqrt_price(U256::ZERO, weth, usdc, 18, 6).is_err());
+    }
+
+    #[test]
+    fn test_sqrt_price_at_tick_matches_tick_math() {
+        assert_eq!(
+            sqrt_price_at_tick(MIN_TICK).unwrap(),
+            U256::from(4295128739u64)
+        );
+        assert_eq!(
+            sqrt_price_at_tick(MAX_TICK).unwrap(),
+            "1461446703485210103287273052203988822378723970342"
+                .parse::<U256>()
+                .unwrap()
+        );
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), U256::from(1u8) << 96);
+        assert_eq!(
+            sqrt_price_at_tick(-1).unwrap(),
+            "79224201403219477170569942574".parse::<U256>().unwrap()
+        );
+        assert_eq!(
+            sqrt_price_at_tick(200000).unwrap(),
+            "1744244129640337381386292603617838"
+                .parse::<U256>()
+                .unwrap()
+        );
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn test_consult_rounds_mean_tick_down() {
+        use alloy::primitives::aliases::I56;
+
+        let observations = |start: i64, end: i64| IUniswapV3Pool::observeReturn {
+            tickCumulatives: vec![I56::try_from(start).unwrap(), I56::try_from(end).unwrap()],
+            secondsPerLiquidityCumulativeX128s: vec![U160::ZERO, U160::from(1u64) << 100],
+        };
+        assert_eq!(
+            consult(&observations(0, 1800 * 100 + 5), 1800).unwrap().0,
+            100
+        );
+        assert_eq!(
+            consult(&observations(0, -1800 * 100 - 5), 1800).unwrap().0,
+            -101
+        );
+        assert_eq!(
+            consult(&observations(0, -1800 * 100), 1800).unwrap().0,
+            -100
+        );
+
+        let flat = IUniswapV3Pool::observeReturn {
+            tickCumulatives: vec![I56::ZERO, I56::ZERO],
+            secondsPerLiquidityCumulativeX128s: vec![U160::from(7u8), U160::from(7u8)],
+        };
+        assert!(consult(&flat, 1800).is_err());
+    }
+
+    #[test]
+    fn test_pool_twap_reports_cardinality() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let pool = network.uniswap_v3.pool_address(weth, usdc, 500);
+        let slot0 = slot0_result(pool, U256::from(20_000u64) << 96);
+        let spot = pool_price(
+            pool,
+            500,
+            weth,
+            usdc,
+            &slot0,
+            &liquidity_result(pool, 10u128.pow(18)),
+            18,
+            6,
+        )
+        .unwrap();
+
+        // USDC 为 token0，tick 200000 时 1 WETH = 1e12 / 1.0001^200000 ≈ 2063.2 USDC
+        let observe = observe_result(pool, 1800, 200000, 10u128.pow(18));
+        let twap = pool_twap(&spot, &slot0, &observe, 1800, 18, 6).unwrap();
+        assert_eq!(twap.mean_tick, 200000);
+        assert_eq!(twap.quote.pool, pool);
+        assert_eq!(
+            twap.quote.price.round_dp(1),
+            "2063.2".parse::<Decimal>().unwrap()
+        );
+        let error = twap.harmonic_mean_liquidity.abs_diff(10u128.pow(18));
+        assert!(error < 10u128.pow(9));
+
+        let reverted = CallResult::new(pool, false, Bytes::new());
+        let err = pool_twap(&spot, &slot0, &reverted, 1800, 18, 6).unwrap_err();
+        assert!(err.to_string().contains("观测基数 1"));
+    }
+
+    #[test]
+    fn test_pool_price_rejects_missing_or_empty_pools() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc) = (network.wrapped_native.address, network.usd_quote().address);
+        let pool = network.uniswap_v3.pool_address(weth, usdc, 500);
+        let slot0 = slot0_result(pool, U256::from(20_000u64) << 96);
+
+        let quote = pool_price(
+            pool,
+            500,
+            weth,
+            usdc,
+            &slot0,
+            &liquidity_result(pool, 10u128.pow(18)),
+            18,
+            6,
+        )
+        .unwrap();
+        assert_eq!(quote.protocol, Protocol::UniswapV3);
+        assert_eq!(quote.fee, Some(500));
+        assert_eq!(quote.price, Decimal::from(2500));
+
+        let missing = CallResult::new(pool, true, Bytes::new());
+        let err = pool_price(pool, 500, weth, usdc, &missing, &missing, 18, 6).unwrap_err();
+        assert!(err.to_string().contains("池不存在"));
+
+        let empty = liquidity_result(pool, 0);
+        let err = pool_price(pool, 500, weth, usdc, &slot0, &empty, 18, 6).unwrap_err();
+        assert!(err.to_string().contains("没有流动性"));
+    }
+}
@@ -0,0 +1,284 @@
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy::primitives::B256;
+use chrono::{DateTime, NaiveDate};
+use std::future::Future;
+use tracing::debug;
+
+use crate::error::{EthereumError, Result};
+
+/// 解析区块参数
+///
+/// 支持十进制或 `0x` 十六进制区块号、32 字节区块哈希，以及
+/// `latest` / `safe` / `finalized` / `earliest` / `pending` 标签。
+pub fn parse_block_id(value: &str) -> Result<BlockId> {
+    let value = value.trim().to_lowercase();
+    value
+        .parse::<BlockId>()
+        .map_err(|_| EthereumError::InvalidBlock(format!("无法解析区块参数: {}", value)))
+}
+
+/// 解析时间戳：Unix 秒、RFC 3339（如 `2024-06-30T00:00:00Z`）或日期（按 UTC 零点）
+pub fn parse_timestamp(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let timestamp = if let Ok(seconds) = value.parse::<u64>() {
+        seconds
+    } else if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        u64::try_from(datetime.timestamp())
+            .map_err(|_| EthereumError::InvalidBlock(format!("时间戳早于 1970 年: {}", value)))?
+    } else if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        u64::try_from(midnight.and_utc().timestamp())
+            .map_err(|_| EthereumError::InvalidBlock(format!("时间戳早于 1970 年: {}", value)))?
+    } else {
+        return Err(EthereumError::InvalidBlock(format!(
+            "无法解析时间戳（支持 Unix 秒、RFC 3339 或 YYYY-MM-DD）: {}",
+            value
+        )));
+    };
+
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    if timestamp > now {
+        return Err(EthereumError::InvalidBlock(format!(
+            "时间戳晚于当前时间: {}",
+            value
+        )));
+    }
+    Ok(timestamp)
+}
+
+/// 工具请求选择的区块：区块参数或时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSelector {
+    Block(BlockId),
+    /// 时间戳不晚于该值的最后一个区块
+    Timestamp(u64),
+}
+
+impl BlockSelector {
+    /// 由工具参数构造，`block` 与 `timestamp` 最多指定一个，都未指定时为 `latest`
+    pub fn from_request(block: Option<&str>, timestamp: Option<&str>) -> Result<Self> {
+        match (block, timestamp) {
+            (Some(_), Some(_)) => Err(EthereumError::InvalidBlock(
+                "block 和 timestamp 只能指定一个".to_string(),
+            )),
+            (Some(block), None) => Ok(BlockSelector::Block(parse_block_id(block)?)),
+            (None, Some(timestamp)) => Ok(BlockSelector::Timestamp(parse_timestamp(timestamp)?)),
+            (None, None) => Ok(BlockSelector::Block(BlockId::latest())),
+        }
+    }
+
+    /// 是否为历史查询（具体区块号或哈希、`earliest`、时间戳），历史查询优先使用归档节点
+    pub fn is_historical(&self) -> bool {
+        match self {
+            BlockSelector::Timestamp(_) | BlockSelector::Block(BlockId::Hash(_)) => true,
+            BlockSelector::Block(BlockId::Number(number)) => matches!(
+                number,
+                BlockNumberOrTag::Number(_) | BlockNumberOrTag::Earliest
+            ),
+        }
+    }
+}
+
+/// 二分查找时间戳不晚于 `target` 的最后一个区块
+///
+/// `latest` 为当前最新区块，`fetch` 按区块号获取区块；共需 O(log n) 次查询。
+pub(crate) async fn search_block_by_timestamp<F, Fut>(
+    target: u64,
+    latest: PinnedBlock,
+    fetch: F,
+) -> Result<PinnedBlock>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<PinnedBlock>>,
+{
+    if target >= latest.timestamp {
+        return Ok(latest);
+    }
+
+    let genesis = fetch(0).await?;
+    if target < genesis.timestamp {
+        return Err(EthereumError::BlockNotFound(format!(
+            "时间戳 {} 早于创世区块",
+            target
+        )));
+    }
+
+    // 不变量：low.timestamp <= target < high.timestamp
+    let (mut low, mut high) = (genesis, latest);
+    let mut lookups = 1;
+    while high.number - low.number > 1 {
+        let middle = fetch(low.number + (high.number - low.number) / 2).await?;
+        lookups += 1;
+        if middle.timestamp <= target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    debug!(
+        "时间戳 {} 对应区块 {} ({} 次区块查询)",
+        target, low.number, lookups
+    );
+    Ok(low)
+}
+
+/// 工具调用中固定使用的区块
+///
+/// 工具开始时解析一次区块参数（默认 `latest`），之后的所有读取都通过区块哈希
+/// 指向同一区块，保证储备量、小数位数和 Gas 估算来自同一链上状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinnedBlock {
+    pub number: u64,
+    pub hash: B256,
+    /// 区块时间戳（Unix 秒）
+    pub timestamp: u64,
+    /// 区块的基础费用（wei），伦敦升级之前的区块为 `None`
+    pub base_fee_per_gas: Option<u64>,
+}
+
+impl PinnedBlock {
+    /// 指向该区块的 [`BlockId`]（EIP-1898 区块哈希）
+    pub fn id(&self) -> BlockId {
+        BlockId::hash(self.hash)
+    }
+}
+
+/// `eth_getBlockByNumber` / `eth_getBlockByHash` 的模拟响应（仅测试使用）
+#[cfg(test)]
+pub(crate) fn mock_block_response(number: u64, hash: B256, timestamp: u64) -> String {
+    let zero_hash = B256::ZERO;
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "result": {
+            "hash": hash,
+            "parentHash": zero_hash,
+            "sha3Uncles": zero_hash,
+            "miner": alloy::primitives::Address::ZERO,
+            "stateRoot": zero_hash,
+            "transactionsRoot": zero_hash,
+            "receiptsRoot": zero_hash,
+            "logsBloom": alloy::primitives::Bloom::ZERO,
+            "difficulty": "0x0",
+            "number": format!("{:#x}", number),
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "timestamp": format!("{:#x}", timestamp),
+            "extraData": "0x",
+            "mixHash": zero_hash,
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x3b9aca00",
+            "size": "0x0",
+            "uncles": [],
+            "transactions": []
+        }
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic_block(number: u64) -> PinnedBlock {
+        // 创世区块时间戳为 0，之后每 12 秒一个区块（与主网相同的形状）
+        PinnedBlock {
+            number,
+            hash: B256::with_last_byte(number as u8),
+            timestamp: if number == 0 {
+                0
+            } else {
+                1_438_269_988 + number * 12
+            },
+            base_fee_per_gas: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_block_by_timestamp() {
+        let latest = synthetic_block(1_000_000);
+        let fetch = |number| async move { Ok(synthetic_block(number)) };
+
+        let exact = synthetic_block(123_456).timestamp;
+        let found = search_block_by_timestamp(exact, latest, fetch)
+            .await
+            .unwrap();
+        assert_eq!(found.number, 123_456);
+
+        // 两个区块之间的时间戳取较早的区块
+        let found = search_block_by_timestamp(exact + 5, latest, fetch)
+            .await
+            .unwrap();
+        assert_eq!(found.number, 123_456);
+
+        let found = search_block_by_timestamp(latest.timestamp + 100, latest, fetch)
+            .await
+            .unwrap();
+        assert_eq!(found, latest);
+
+        // 创世区块与区块 1 之间
+        let found = search_block_by_timestamp(1_000, latest, fetch)
+            .await
+            .unwrap();
+        assert_eq!(found.number, 0);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1719705600").unwrap(), 1_719_705_600);
+        assert_eq!(
+            parse_timestamp("2024-06-30T00:00:00Z").unwrap(),
+            1_719_705_600
+        );
+        assert_eq!(
+            parse_timestamp("2024-06-30T08:00:00+08:00").unwrap(),
+            1_719_705_600
+        );
+        assert_eq!(parse_timestamp("2024-06-30").unwrap(), 1_719_705_600);
+        assert!(parse_timestamp("June 30").is_err());
+        assert!(parse_timestamp("4102444800").is_err());
+    }
+
+    #[test]
+    fn test_block_selector() {
+        let latest = BlockSelector::from_request(None, None).unwrap();
+        assert_eq!(latest, BlockSelector::Block(BlockId::latest()));
+        assert!(!latest.is_historical());
+        assert!(!BlockSelector::from_request(Some("finalized"), None)
+            .unwrap()
+            .is_historical());
+        assert!(BlockSelector::from_request(Some("19000000"), None)
+            .unwrap()
+            .is_historical());
+        assert!(BlockSelector::from_request(None, Some("2024-06-30"))
+            .unwrap()
+            .is_historical());
+        assert!(BlockSelector::from_request(Some("latest"), Some("2024-06-30")).is_err());
+    }
+
+    #[test]
+    fn test_parse_block_id() {
+        assert_eq!(
+            parse_block_id("19000000").unwrap(),
+            BlockId::number(19_000_000)
+        );
+        assert_eq!(parse_block_id("0x10").unwrap(), BlockId::number(16));
+        assert_eq!(
+            parse_block_id("Finalized").unwrap(),
+            BlockId::Number(BlockNumberOrTag::Finalized)
+        );
+        assert_eq!(parse_block_id("safe").unwrap(), BlockId::safe());
+
+        let hash = "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6";
+        assert_eq!(
+            parse_block_id(hash).unwrap(),
+            BlockId::hash(hash.parse().unwrap())
+        );
+
+        assert!(matches!(
+            parse_block_id("yesterday"),
+            Err(EthereumError::InvalidBlock(_))
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+use alloy::eips::BlockId;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::rpc::block::PinnedBlock;
+use crate::rpc::client::IERC20;
+use alloy::sol_types::SolCall;
+
+/// RPC 缓存配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// 不可变代币元数据（decimals、symbol、name）的最大条目数，`0` 表示不缓存
+    pub max_metadata_entries: usize,
+    /// 区块级数据（储备量、余额、Gas 价格等）的最大条目数，`0` 表示不缓存
+    pub max_block_entries: usize,
+    /// 区块级数据的最长保留时间，防止长时间没有观察到新区块时返回过期数据
+    pub block_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            max_metadata_entries: 10_000,
+            max_block_entries: 5_000,
+            block_ttl: Duration::from_secs(12),
+        }
+    }
+}
+
+/// 永不变化的只读调用：结果可以无限期缓存
+const IMMUTABLE_SELECTORS: [[u8; 4]; 3] = [
+    IERC20::decimalsCall::SELECTOR,
+    IERC20::symbolCall::SELECTOR,
+    IERC20::nameCall::SELECTOR,
+];
+
+/// 调用 `target` 的 `calldata` 是否只读取不可变的代币元数据
+pub(crate) fn is_immutable_call(calldata: &[u8]) -> bool {
+    calldata.len() == 4 && IMMUTABLE_SELECTORS.iter().any(|s| s[..] == calldata[..])
+}
+
+/// 区块级缓存的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum BlockKey {
+    Call(Address, Bytes),
+    EthBalance(Address),
+    /// ERC20 余额：（代币, 账户）
+    TokenBalance(Address, Address),
+    GasPrice,
+}
+
+/// 区块级缓存的值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BlockValue {
+    Call { success: bool, data: Bytes },
+    EthBalance(U256),
+    TokenBalance(U256),
+    GasPrice(u128),
+}
+
+/// 按插入顺序淘汰的有界映射
+#[derive(Debug)]
+struct BoundedMap<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    order: VecDeque<K>,
+}
+
+impl<K: Clone + Eq + Hash, V> BoundedMap<K, V> {
+    fn new(capacity: usize) -> Self {
+        BoundedMap {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    /// 插入一个条目，返回因容量限制被淘汰的条目数
+    fn insert(&mut self, key: K, value: V) -> u64 {
+        if self.capacity == 0 {
+            return 0;
+        }
+        if self.entries.insert(key.clone(), value).is_some() {
+            return 0;
+        }
+        self.order.push_back(key);
+
+        let mut evicted = 0;
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                    evicted += 1;
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// 只对当前最新区块有效的缓存
+#[derive(Debug)]
+struct BlockScoped {
+    /// 最近观察到的 `latest` 区块
+    latest: Option<(u64, B256)>,
+    entries: BoundedMap<BlockKey, (BlockValue, Instant)>,
+}
+
+/// 命中/未命中计数
+#[derive(Debug, Default)]
+struct Counters {
+    metadata_hits: AtomicU64,
+    metadata_misses: AtomicU64,
+    block_hits: AtomicU64,
+    block_misses: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+/// 缓存统计（诊断信息）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub metadata_entries: usize,
+    pub metadata_hits: u64,
+    pub metadata_misses: u64,
+    pub block_entries: usize,
+    pub block_hits: u64,
+    pub block_misses: u64,
+    /// 因容量限制淘汰的条目数
+    pub evictions: u64,
+    /// 新区块到达时清空区块级缓存的次数
+    pub invalidations: u64,
+    /// 区块级缓存当前对应的区块
+    pub block_number: Option<u64>,
+}
+
+/// `RpcClient` 共享的内存缓存
+///
+/// 不可变元数据无限期保留（只受容量限制）；区块级数据只对最近观察到的 `latest`
+/// 区块有效，新区块到达时整体失效，并受 `block_ttl` 限制。
+#[derive(Debug)]
+pub struct RpcCache {
+    block_ttl: Duration,
+    metadata: Mutex<BoundedMap<(Address, Bytes), Bytes>>,
+    block: Mutex<BlockScoped>,
+    counters: Counters,
+}
+
+impl RpcCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        RpcCache {
+            block_ttl: config.block_ttl,
+            metadata: Mutex::new(BoundedMap::new(config.max_metadata_entries)),
+            block: Mutex::new(BlockScoped {
+                latest: None,
+                entries: BoundedMap::new(config.max_block_entries),
+            }),
+            counters: Counters::default(),
+        }
+    }
+
+    /// 查询不可变元数据
+    pub(crate) fn metadata(&self, target: Address, calldata: &Bytes) -> Option<Bytes> {
+        let metadata = self.metadata.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = metadata.get(&(target, calldata.clone())).cloned();
+        Self::count(
+            &self.counters.metadata_hits,
+            &self.counters.metadata_misses,
+            cached.is_some(),
+        );
+        cached
+    }
+
+    /// 缓存不可变元数据（只缓存成功且非空的结果）
+    pub(crate) fn store_metadata(&self, target: Address, calldata: Bytes, data: Bytes) {
+        if data.is_empty() {
+            return;
+        }
+        let evicted = self
+            .metadata
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((target, calldata), data);
+        self.counters
+            .evictions
+            .fetch_add(evicted, Ordering::Relaxed);
+    }
+
+    /// 记录新观察到的 `latest` 区块，区块变化时清空区块级缓存
+    pub(crate) fn observe_latest(&self, block: &PinnedBlock) {
+        let mut scoped = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        match scoped.latest {
+            Some((_, hash)) if hash == block.hash => {}
+            // 落后的端点返回的旧区块不影响缓存
+            Some((number, _)) if number > block.number => {}
+            previous => {
+                if previous.is_some() && scoped.entries.len() > 0 {
+                    self.counters.invalidations.fetch_add(1, Ordering::Relaxed);
+                    debug!("新区块 {}，区块级缓存失效", block.number);
+                }
+                scoped.entries.clear();
+                scoped.latest = Some((block.number, block.hash));
+            }
+        }
+    }
+
+    /// 查询区块级数据
+    ///
+    /// `block` 为 `None` 表示“当前区块”（如 `eth_gasPrice`）；指定区块时只有它就是
+    /// 最近观察到的 `latest` 区块才会命中。
+    pub(crate) fn block_value(&self, block: Option<BlockId>, key: &BlockKey) -> Option<BlockValue> {
+        let scoped = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        if !Self::is_current(&scoped, block) {
+            return None;
+        }
+
+        let cached = scoped
+            .entries
+            .get(key)
+            .filter(|(_, stored_at)| stored_at.elapsed() < self.block_ttl)
+            .map(|(value, _)| value.clone());
+        Self::count(
+            &self.counters.block_hits,
+            &self.counters.block_misses,
+            cached.is_some(),
+        );
+        cached
+    }
+
+    /// 缓存区块级数据（只缓存当前区块的数据）
+    pub(crate) fn store_block_value(
+        &self,
+        block: Option<BlockId>,
+        key: BlockKey,
+        value: BlockValue,
+    ) {
+        let mut scoped = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        if !Self::is_current(&scoped, block) {
+            return;
+        }
+        let evicted = scoped.entries.insert(key, (value, Instant::now()));
+        self.counters
+            .evictions
+            .fetch_add(evicted, Ordering::Relaxed);
+    }
+
+    /// 当前缓存统计
+    pub fn stats(&self) -> CacheStats {
+        let metadata_entries = self
+            .metadata
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len();
+        let scoped = self.block.lock().unwrap_or_else(|e| e.into_inner());
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        CacheStats {
+            metadata_entries,
+            metadata_hits: load(&self.counters.metadata_hits),
+            metadata_misses: load(&self.counters.metadata_misses),
+            block_entries: scoped.entries.len(),
+            block_hits: load(&self.counters.block_hits),
+            block_misses: load(&self.counters.block_misses),
+            evictions: load(&self.counters.evictions),
+            invalidations: load(&self.counters.invalidations),
+            block_number: scoped.latest.map(|(number, _)| number),
+        }
+    }
+
+    fn is_current(scoped: &BlockScoped, block: Option<BlockId>) -> bool {
+        match block {
+            None => true,
+            Some(BlockId::Hash(hash)) => scoped.latest.map(|(_, h)| h) == Some(hash.block_hash),
+            Some(BlockId::Number(_)) => false,
+        }
+    }
+
+    fn count(hits: &AtomicU64, misses: &AtomicU64, hit: bool) {
+        let counter = if hit { hits } else { misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64) -> PinnedBlock {
+        PinnedBlock {
+            number,
+            hash: B256::with_last_byte(number as u8),
+            timestamp: number * 12,
+            base_fee_per_gas: None,
+        }
+    }
+
+    #[test]
+    fn test_metadata_is_bounded() {
+        let cache = RpcCache::new(&CacheConfig {
+            max_metadata_entries: 2,
+            ..CacheConfig::default()
+        });
+        let calldata = Bytes::from(IERC20::decimalsCall::SELECTOR.to_vec());
+        assert!(is_immutable_call(&calldata));
+
+        for i in 1..=3u8 {
+            let token = Address::with_last_byte(i);
+            cache.store_metadata(token, calldata.clone(), Bytes::from(vec![i]));
+        }
+
+        // 最早插入的条目被淘汰
+        assert_eq!(cache.metadata(Address::with_last_byte(1), &calldata), None);
+        assert_eq!(
+            cache.metadata(Address::with_last_byte(3), &calldata),
+            Some(Bytes::from(vec![3]))
+        );
+
+        let stats = cache.stats();
+        assert_eq!(stats.metadata_entries, 2);
+        assert_eq!(stats.metadata_hits, 1);
+        assert_eq!(stats.metadata_misses, 1);
+        assert_eq!(stats.evictions, 1);
+    }
+
+    #[test]
+    fn test_block_values_invalidated_by_new_block() {
+        let cache = RpcCache::new(&CacheConfig::default());
+        let key = BlockKey::EthBalance(Address::ZERO);
+        let value = BlockValue::EthBalance(U256::from(1));
+
+        let first = block(100);
+        // 尚未观察到 latest 时，指定区块的数据不缓存
+        cache.store_block_value(Some(first.id()), key.clone(), value.clone());
+        assert_eq!(cache.block_value(Some(first.id()), &key), None);
+
+        cache.observe_latest(&first);
+        cache.store_block_value(Some(first.id()), key.clone(), value.clone());
+        assert_eq!(
+            cache.block_value(Some(first.id()), &key),
+            Some(value.clone())
+        );
+
+        // 落后端点返回的旧区块不影响缓存
+        cache.observe_latest(&block(99));
+        assert_eq!(cache.block_value(Some(first.id()), &key), Some(value));
+
+        cache.observe_latest(&block(101));
+        assert_eq!(cache.block_value(Some(first.id()), &key), None);
+        assert_eq!(cache.block_value(Some(block(101).id()), &key), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(stats.block_entries, 0);
+        assert_eq!(stats.block_number, Some(101));
+    }
+
+    #[test]
+    fn test_block_values_expire() {
+        let cache = RpcCache::new(&CacheConfig {
+            block_ttl: Duration::ZERO,
+            ..CacheConfig::default()
+        });
+        cache.store_block_value(None, BlockKey::GasPrice, BlockValue::GasPrice(1));
+        assert_eq!(cache.block_value(None, &BlockKey::GasPrice), None);
+    }
+}
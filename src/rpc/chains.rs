@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::error::{EthereumError, Result};
+use crate::network::NetworkProfile;
+use crate::rpc::client::RpcClient;
+
+/// 服务器连接的各条链的 RPC 客户端
+///
+/// 每条链一个 [`RpcClient`]（各自的端点、缓存和网络配置），克隆时共享同一组客户端。
+/// 第一个客户端所在的链为默认链，工具请求未指定 `chain` 时使用。
+#[derive(Clone)]
+pub struct ChainClients {
+    clients: Arc<Vec<RpcClient>>,
+}
+
+impl ChainClients {
+    /// 由各链的客户端创建，第一个为默认链；同一条链不能出现两次
+    pub fn new(clients: Vec<RpcClient>) -> Result<Self> {
+        if clients.is_empty() {
+            return Err(EthereumError::ConfigError("至少需要配置一条链".to_string()));
+        }
+        for (i, client) in clients.iter().enumerate() {
+            let network = client.network();
+            if clients[..i]
+                .iter()
+                .any(|other| other.network().chain_id == network.chain_id)
+            {
+                return Err(EthereumError::ConfigError(format!(
+                    "{} (链 ID {}) 重复配置",
+                    network.display_name, network.chain_id
+                )));
+            }
+        }
+        Ok(ChainClients {
+            clients: Arc::new(clients),
+        })
+    }
+
+    /// 默认链的客户端
+    pub fn default_client(&self) -> &RpcClient {
+        &self.clients[0]
+    }
+
+    /// 链 `chain_id` 的客户端
+    pub fn get(&self, chain_id: u64) -> Option<&RpcClient> {
+        self.clients
+            .iter()
+            .find(|client| client.network().chain_id == chain_id)
+    }
+
+    /// 按请求中的 `chain` 参数（网络名称或链 ID）选择客户端，未指定时使用默认链
+    pub fn select(&self, chain: Option<&str>) -> Result<&RpcClient> {
+        let Some(chain) = chain else {
+            return Ok(self.default_client());
+        };
+        let network = NetworkProfile::lookup(chain).ok_or_else(|| {
+            EthereumError::NetworkError(format!(
+                "未知网络: {}（支持: {}）",
+                chain,
+                NetworkProfile::supported_chain_ids()
+            ))
+        })?;
+        self.get(network.chain_id).ok_or_else(|| {
+            EthereumError::NetworkError(format!(
+                "服务器未配置 {} (链 ID {}) 的 RPC 端点（已配置: {}）",
+                network.display_name,
+                network.chain_id,
+                self.names()
+            ))
+        })
+    }
+
+    /// 按配置顺序遍历各链的客户端
+    pub fn iter(&self) -> impl Iterator<Item = &RpcClient> {
+        self.clients.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    fn names(&self) -> String {
+        self.iter()
+            .map(|client| client.network().name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl From<RpcClient> for ChainClients {
+    fn from(client: RpcClient) -> Self {
+        ChainClients {
+            clients: Arc::new(vec![client]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcClientConfig;
+
+    fn client(chain_id: u64) -> RpcClient {
+        RpcClient::with_config(
+            "http://127.0.0.1:8545".to_string(),
+            &RpcClientConfig::default(),
+        )
+        .unwrap()
+        .with_network(NetworkProfile::by_chain_id(chain_id).unwrap())
+    }
+
+    #[test]
+    fn test_select_by_name_or_chain_id() {
+        let chains = ChainClients::new(vec![client(1), client(8453)]).unwrap();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains.select(None).unwrap().network().chain_id, 1);
+        assert_eq!(
+            chains.select(Some("base")).unwrap().network().chain_id,
+            8453
+        );
+        assert_eq!(
+            chains.select(Some("8453")).unwrap().network().chain_id,
+            8453
+        );
+        assert_eq!(
+            chains.select(Some("Mainnet")).unwrap().network().chain_id,
+            1
+        );
+
+        // 支持但未配置的网络与未知网络
+        let err = chains.select(Some("arbitrum")).map(|_| ()).unwrap_err();
+        assert!(err.to_string().contains("ethereum, base"));
+        assert!(matches!(
+            chains.select(Some("polygon")),
+            Err(EthereumError::NetworkError(_))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_chains_rejected() {
+        assert!(ChainClients::new(vec![client(10), client(10)]).is_err());
+        assert!(ChainClients::new(Vec::new()).is_err());
+    }
+}
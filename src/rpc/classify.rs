@@ -0,0 +1,283 @@
+use alloy::transports::{RpcError, TransportError, TransportErrorKind};
+use serde::Serialize;
+use std::fmt;
+
+/// JSON-RPC 中表示 revert 的错误码（EIP-1474 / geth）
+const EXECUTION_REVERTED_CODE: i64 = 3;
+
+/// JSON-RPC 规范保留给实现自定义服务器错误的错误码范围；节点落后和状态已裁剪都在此范围内，
+/// 参数错误（-32602）等标准错误码不会按消息重新分类
+const SERVER_ERROR_CODES: std::ops::RangeInclusive<i64> = -32099..=-32000;
+
+/// 节点已裁剪所请求区块状态时各客户端返回的消息
+const STATE_UNAVAILABLE_MESSAGES: [&str; 4] = [
+    // geth（hash 存储方案）、Nethermind
+    "missing trie node",
+    // geth（path 存储方案）：historical state <root> is not available
+    "historical state",
+    // reth：state at block #<n> is pruned
+    "state at block #",
+    // Nethermind：No state available for block <hash>
+    "no state available for block",
+];
+
+/// 节点还没有所请求区块时返回的消息（geth、reth、Nethermind）
+///
+/// 工具读取都固定在另一个端点解析出的区块哈希上，故障转移到稍落后的端点时同样会收到
+/// 按哈希查找失败的消息，换端点或稍后重试可以成功。
+const LAGGING_NODE_MESSAGES: [&str; 3] = [
+    "header not found",
+    "header for hash not found",
+    "unknown block",
+];
+
+/// RPC 失败的类型
+///
+/// 由 alloy 的错误变体（HTTP 状态码、reqwest 错误类型、JSON-RPC 错误码）一次性分类得到，
+/// 重试和故障转移只依据这里的变体做决定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcErrorKind {
+    /// 被限流（HTTP 429 或 JSON-RPC limit exceeded）
+    RateLimited,
+    /// 请求或连接超时
+    Timeout,
+    /// 无法建立连接或连接中断
+    Connection,
+    /// 端点返回 5xx
+    ServerError,
+    /// 节点尚未同步到请求的区块（如 "header not found"）
+    LaggingNode,
+    /// 节点已裁剪所请求区块的状态（非归档节点上的历史查询）
+    StateUnavailable,
+    /// 端点返回了空结果或无法解析的响应
+    InvalidResponse,
+    /// 端点拒绝服务（如 401/403 等非瞬时的 HTTP 错误）
+    EndpointUnavailable,
+    /// 合约执行 revert
+    ExecutionReverted,
+    /// 节点按请求内容拒绝（参数错误、方法不存在等）
+    Rejected,
+    /// 返回数据无法按 ABI 解码
+    Decode,
+    /// 本地错误（序列化失败、不支持的功能等）
+    Local,
+}
+
+impl RpcErrorKind {
+    /// 稍后重试可能成功的错误
+    pub fn is_transient(self) -> bool {
+        matches!(
+            self,
+            RpcErrorKind::RateLimited
+                | RpcErrorKind::Timeout
+                | RpcErrorKind::Connection
+                | RpcErrorKind::ServerError
+                | RpcErrorKind::LaggingNode
+                | RpcErrorKind::InvalidResponse
+        )
+    }
+
+    /// 应归咎于当前端点、换一个端点可能成功的错误
+    ///
+    /// 历史状态不可用不会因重试而改变，但其他端点（归档节点）可能可以提供。
+    pub fn is_endpoint_failure(self) -> bool {
+        self.is_transient()
+            || matches!(
+                self,
+                RpcErrorKind::EndpointUnavailable | RpcErrorKind::StateUnavailable
+            )
+    }
+}
+
+impl fmt::Display for RpcErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RpcErrorKind::RateLimited => "rate_limited",
+            RpcErrorKind::Timeout => "timeout",
+            RpcErrorKind::Connection => "connection",
+            RpcErrorKind::ServerError => "server_error",
+            RpcErrorKind::LaggingNode => "lagging_node",
+            RpcErrorKind::StateUnavailable => "state_unavailable",
+            RpcErrorKind::InvalidResponse => "invalid_response",
+            RpcErrorKind::EndpointUnavailable => "endpoint_unavailable",
+            RpcErrorKind::ExecutionReverted => "execution_reverted",
+            RpcErrorKind::Rejected => "rejected",
+            RpcErrorKind::Decode => "decode",
+            RpcErrorKind::Local => "local",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 可分类的 RPC 错误
+pub trait ClassifyError: fmt::Display {
+    fn kind(&self) -> RpcErrorKind;
+}
+
+impl ClassifyError for TransportError {
+    fn kind(&self) -> RpcErrorKind {
+        match self {
+            RpcError::Transport(kind) => classify_transport(kind),
+            RpcError::ErrorResp(payload) => {
+                let server_error = SERVER_ERROR_CODES.contains(&payload.code);
+                if payload.code == EXECUTION_REVERTED_CODE || payload.as_revert_data().is_some() {
+                    RpcErrorKind::ExecutionReverted
+                } else if server_error && matches_any(&payload.message, &STATE_UNAVAILABLE_MESSAGES)
+                {
+                    RpcErrorKind::StateUnavailable
+                } else if server_error && matches_any(&payload.message, &LAGGING_NODE_MESSAGES) {
+                    RpcErrorKind::LaggingNode
+                } else if payload.is_retry_err() {
+                    RpcErrorKind::RateLimited
+                } else {
+                    RpcErrorKind::Rejected
+                }
+            }
+            RpcError::NullResp | RpcError::DeserError { .. } => RpcErrorKind::InvalidResponse,
+            RpcError::UnsupportedFeature(_)
+            | RpcError::LocalUsageError(_)
+            | RpcError::SerError(_) => RpcErrorKind::Local,
+        }
+    }
+}
+
+impl ClassifyError for alloy::contract::Error {
+    fn kind(&self) -> RpcErrorKind {
+        match self {
+            alloy::contract::Error::TransportError(e) => e.kind(),
+            alloy::contract::Error::AbiError(_) => RpcErrorKind::Decode,
+            _ => RpcErrorKind::Local,
+        }
+    }
+}
+
+impl ClassifyError for alloy::sol_types::Error {
+    fn kind(&self) -> RpcErrorKind {
+        RpcErrorKind::Decode
+    }
+}
+
+fn classify_transport(kind: &TransportErrorKind) -> RpcErrorKind {
+    match kind {
+        TransportErrorKind::HttpError(e) => match e.status {
+            429 => RpcErrorKind::RateLimited,
+            408 | 504 => RpcErrorKind::Timeout,
+            500..=599 => RpcErrorKind::ServerError,
+            _ => RpcErrorKind::EndpointUnavailable,
+        },
+        TransportErrorKind::Custom(e) => match e.downcast_ref::<reqwest::Error>() {
+            Some(e) if e.is_timeout() => RpcErrorKind::Timeout,
+            Some(e) if e.is_decode() => RpcErrorKind::InvalidResponse,
+            _ => RpcErrorKind::Connection,
+        },
+        TransportErrorKind::MissingBatchResponse(_) => RpcErrorKind::InvalidResponse,
+        _ => RpcErrorKind::Connection,
+    }
+}
+
+/// 错误消息是否包含某个已知客户端的措辞（这些错误只有服务器错误范围内的通用错误码，只能结合消息区分）
+fn matches_any(message: &str, patterns: &[&str]) -> bool {
+    let message = message.to_lowercase();
+    patterns.iter().any(|pattern| message.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::transports::HttpError;
+
+    fn error_response(body: &str) -> TransportError {
+        RpcError::ErrorResp(serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_http_status_classification() {
+        let http = |status| {
+            TransportError::Transport(TransportErrorKind::HttpError(HttpError {
+                status,
+                body: String::new(),
+            }))
+        };
+
+        assert_eq!(http(429).kind(), RpcErrorKind::RateLimited);
+        assert_eq!(http(504).kind(), RpcErrorKind::Timeout);
+        assert_eq!(http(502).kind(), RpcErrorKind::ServerError);
+        assert_eq!(http(401).kind(), RpcErrorKind::EndpointUnavailable);
+        assert!(!http(401).kind().is_transient());
+        assert!(http(401).kind().is_endpoint_failure());
+    }
+
+    #[test]
+    fn test_error_response_classification() {
+        let revert =
+            error_response(r#"{"code":3,"message":"execution reverted","data":"0x08c379a0"}"#);
+        assert_eq!(revert.kind(), RpcErrorKind::ExecutionReverted);
+        assert!(!revert.kind().is_transient());
+
+        let limited = error_response(r#"{"code":-32005,"message":"limit exceeded"}"#);
+        assert_eq!(limited.kind(), RpcErrorKind::RateLimited);
+
+        let lagging = error_response(r#"{"code":-32000,"message":"header not found"}"#);
+        assert_eq!(lagging.kind(), RpcErrorKind::LaggingNode);
+        assert!(lagging.kind().is_transient());
+
+        let pruned = error_response(
+            r#"{"code":-32000,"message":"missing trie node 7a1d7e5e (path ) state 0x7a1d7e5e is not available"}"#,
+        );
+        assert_eq!(pruned.kind(), RpcErrorKind::StateUnavailable);
+        assert!(!pruned.kind().is_transient());
+        assert!(pruned.kind().is_endpoint_failure());
+
+        let invalid = error_response(r#"{"code":-32602,"message":"invalid argument 0"}"#);
+        assert_eq!(invalid.kind(), RpcErrorKind::Rejected);
+
+        let reth = error_response(r#"{"code":-32000,"message":"state at block #1000 is pruned"}"#);
+        assert_eq!(reth.kind(), RpcErrorKind::StateUnavailable);
+        let nethermind = error_response(
+            r#"{"code":-32002,"message":"No state available for block 0x8f6d6d1b"}"#,
+        );
+        assert_eq!(nethermind.kind(), RpcErrorKind::StateUnavailable);
+    }
+
+    #[test]
+    fn test_classification_false_positives() {
+        // 消息中提到 archive 或 pruning 的无关错误不是状态已裁剪
+        let plan = error_response(
+            r#"{"code":-32000,"message":"archive requests are not included in your plan"}"#,
+        );
+        assert_eq!(plan.kind(), RpcErrorKind::Rejected);
+        let pool = error_response(r#"{"code":-32000,"message":"txpool is pruning, try again"}"#);
+        assert_eq!(pool.kind(), RpcErrorKind::Rejected);
+
+        // 标准错误码不按消息重新分类
+        let params = error_response(
+            r#"{"code":-32602,"message":"invalid argument 1: missing trie node selector"}"#,
+        );
+        assert_eq!(params.kind(), RpcErrorKind::Rejected);
+
+        // 固定的区块哈希在落后的端点上还不存在：换端点或重试
+        for message in ["header for hash not found", "unknown block"] {
+            let unknown = error_response(&format!(r#"{{"code":-32000,"message":"{}"}}"#, message));
+            assert_eq!(unknown.kind(), RpcErrorKind::LaggingNode);
+            assert!(unknown.kind().is_endpoint_failure());
+        }
+    }
+
+    #[test]
+    fn test_transport_classification() {
+        assert_eq!(
+            TransportErrorKind::custom_str("connection refused").kind(),
+            RpcErrorKind::Connection
+        );
+        assert_eq!(
+            TransportError::NullResp.kind(),
+            RpcErrorKind::InvalidResponse
+        );
+
+        let contract_error = alloy::contract::Error::from(error_response(
+            r#"{"code":3,"message":"execution reverted"}"#,
+        ));
+        assert_eq!(contract_error.kind(), RpcErrorKind::ExecutionReverted);
+    }
+}
@@ -1,12 +1,26 @@
+use alloy::eips::BlockId;
 use alloy::network::TransactionBuilder;
-use alloy::primitives::{Address, U256};
+use alloy::primitives::{Address, Bytes, U256};
 use alloy::providers::{Provider, ProviderBuilder};
 use alloy::sol;
 use alloy::sol_types::SolCall;
-use std::sync::Arc;
-use tracing::{debug, error};
+use alloy::transports::http::Http;
+use alloy::transports::TransportError;
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info, warn};
 
+use crate::config::RpcClientConfig;
 use crate::error::{EthereumError, Result};
+use crate::network::NetworkProfile;
+use crate::rpc::block::{search_block_by_timestamp, BlockSelector, PinnedBlock};
+use crate::rpc::cache::{is_immutable_call, BlockKey, BlockValue, CacheStats, RpcCache};
+use crate::rpc::classify::{ClassifyError, RpcErrorKind};
+use crate::rpc::endpoint::{Endpoint, EndpointStatus};
+use crate::rpc::multicall::{CallResult, IMulticall3, MulticallBatch};
+use crate::rpc::retry::{RetryBudget, RetryPolicy};
 
 sol! {
     #[allow(missing_docs)]
@@ -15,6 +29,7 @@ sol! {
         function balanceOf(address account) external view returns (uint256);
         function decimals() external view returns (uint8);
         function symbol() external view returns (string);
+        function name() external view returns (string);
         function transfer(address to, uint256 amount) external returns (bool);
         function approve(address spender, uint256 amount) external returns (bool);
         function allowance(address owner, address spender) external view returns (uint256);
@@ -64,7 +79,8 @@ sol! {
     }
 }
 
-type HttpProvider = alloy::providers::fillers::FillProvider<
+/// `RpcClient` 持有的 HTTP provider 类型
+pub type HttpProvider = alloy::providers::fillers::FillProvider<
     alloy::providers::fillers::JoinFill<
         alloy::providers::Identity,
         alloy::providers::fillers::JoinFill<
@@ -84,115 +100,441 @@ type HttpProvider = alloy::providers::fillers::FillProvider<
 >;
 
 /// 以太坊 RPC 客户端
+///
+/// 按优先级持有一个或多个端点。端点发生传输故障或超时时自动切换到下一个端点，
+/// 连续失败的端点被标记为不健康，由后台任务探测恢复后才重新使用。
+/// 可选地持有一组归档端点，历史查询通过 [`RpcClient::state_reader`] 使用它们。
+/// 克隆的客户端（包括归档读取客户端）共享同一个 [`RpcCache`]。
+/// Multicall3 和 Uniswap V2 Router 等合约地址取自所连链的 [`NetworkProfile`]（默认以太坊主网）。
 #[derive(Clone)]
 pub struct RpcClient {
     inner: Arc<RpcClientInner>,
+    archive: Option<Arc<RpcClientInner>>,
+    cache: Arc<RpcCache>,
+    network: &'static NetworkProfile,
 }
 
 struct RpcClientInner {
-    provider_url: String,
+    /// 按优先级排列的端点，每个端点长期持有自己的 provider 和连接池
+    endpoints: Vec<Endpoint>,
+    unhealthy_after: u32,
+    retry: RetryPolicy,
+    retry_budget: RetryBudget,
 }
 
 impl RpcClient {
-    /// 创建一个新的 RPC 客户端
+    /// 使用默认连接池配置创建 RPC 客户端
     pub async fn new(rpc_url: String) -> Result<Self> {
-        // 验证 URL 格式
-        rpc_url
-            .parse::<url::Url>()
-            .map_err(|_| EthereumError::ConfigError("无效的 RPC URL 格式".to_string()))?;
+        Self::with_config(rpc_url, &RpcClientConfig::default())
+    }
+
+    /// 按连接池配置创建单端点 RPC 客户端
+    pub fn with_config(rpc_url: String, config: &RpcClientConfig) -> Result<Self> {
+        Self::with_endpoints(vec![rpc_url], config)
+    }
+
+    /// 按优先级顺序创建多端点 RPC 客户端
+    ///
+    /// HTTP 客户端和 provider 只在这里构建一次，之后的调用复用同一连接池。
+    /// 在 tokio 运行时中创建且端点多于一个时，会启动后台健康探测任务。
+    pub fn with_endpoints(rpc_urls: Vec<String>, config: &RpcClientConfig) -> Result<Self> {
+        if rpc_urls.is_empty() {
+            return Err(EthereumError::ConfigError(
+                "至少需要配置一个 RPC 端点".to_string(),
+            ));
+        }
+
+        let http_client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .connect_timeout(config.connect_timeout)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .tcp_keepalive(config.tcp_keepalive)
+            .build()
+            .map_err(|e| EthereumError::ConfigError(format!("无法创建 HTTP 客户端: {}", e)))?;
+
+        let endpoints = rpc_urls
+            .into_iter()
+            .map(|rpc_url| {
+                // 验证 URL 格式
+                let url = rpc_url.parse::<url::Url>().map_err(|_| {
+                    EthereumError::ConfigError(format!("无效的 RPC URL 格式: {}", rpc_url))
+                })?;
 
-        debug!("已连接到 RPC: {}", rpc_url);
+                // reqwest::Client 内部共享连接池，按主机复用连接
+                let transport = Http::with_client(http_client.clone(), url);
+                let is_local = transport.guess_local();
+                let provider = ProviderBuilder::new()
+                    .with_recommended_fillers()
+                    .on_client(alloy::rpc::client::RpcClient::new(transport, is_local));
+
+                debug!("已连接到 RPC: {}", rpc_url);
+                Ok(Endpoint::new(rpc_url, provider))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let inner = Arc::new(RpcClientInner {
+            endpoints,
+            unhealthy_after: config.unhealthy_after.max(1),
+            retry: config.retry.clone(),
+            retry_budget: RetryBudget::new(&config.retry),
+        });
+
+        if inner.endpoints.len() > 1 {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn(probe_unhealthy_endpoints(
+                        Arc::downgrade(&inner),
+                        config.probe_interval,
+                    ));
+                }
+                Err(_) => warn!("不在 tokio 运行时中，RPC 端点健康探测未启动"),
+            }
+        }
 
         Ok(RpcClient {
-            inner: Arc::new(RpcClientInner {
-                provider_url: rpc_url,
-            }),
+            inner,
+            archive: None,
+            cache: Arc::new(RpcCache::new(&config.cache)),
+            network: NetworkProfile::mainnet(),
         })
     }
 
-    /// 为每个操作获取提供程序的帮助函数
-    pub fn get_provider(&self) -> Result<HttpProvider> {
-        let url = self
-            .inner
-            .provider_url
-            .parse()
-            .map_err(|_| EthereumError::ConfigError("无效的 RPC URL".to_string()))?;
+    /// 指定端点所在链的网络配置
+    pub fn with_network(self, network: &'static NetworkProfile) -> Self {
+        RpcClient { network, ..self }
+    }
 
-        Ok(ProviderBuilder::new()
-            .with_recommended_fillers()
-            .on_http(url))
+    /// 端点所在链的网络配置
+    pub fn network(&self) -> &'static NetworkProfile {
+        self.network
     }
 
-    /// 获取地址的 ETH 余额
-    pub async fn get_eth_balance(&self, address: Address) -> Result<U256> {
-        debug!("正在获取 ETH 余额: {:?}", address);
+    /// 为历史查询附加归档节点客户端
+    pub fn with_archive(self, archive: RpcClient) -> Self {
+        RpcClient {
+            archive: Some(archive.inner),
+            ..self
+        }
+    }
 
-        let provider = self.get_provider()?;
+    /// 读取 `selector` 所选区块状态时使用的客户端
+    ///
+    /// 历史查询且配置了归档端点时返回归档客户端，否则返回自身。
+    pub fn state_reader(&self, selector: &BlockSelector) -> RpcClient {
+        match &self.archive {
+            Some(archive) if selector.is_historical() => RpcClient {
+                inner: archive.clone(),
+                archive: None,
+                cache: self.cache.clone(),
+                network: self.network,
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// 在可用端点上执行一次 RPC 操作
+    ///
+    /// 每一轮按优先级依次尝试健康端点（全部不健康时尝试所有端点），
+    /// 仅在端点故障时切换到下一个端点，节点返回的确定性错误（如合约 revert）直接返回。
+    /// 一轮全部失败且错误是瞬时的（限流、超时、节点落后等），
+    /// 在重试次数和重试预算允许时按带抖动的指数退避重试。
+    pub async fn execute<T, E, F, Fut>(&self, operation: F) -> std::result::Result<T, E>
+    where
+        F: Fn(HttpProvider) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: ClassifyError,
+    {
+        let policy = &self.inner.retry;
+        self.inner.retry_budget.deposit();
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.try_endpoints(&operation).await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            let kind = error.kind();
+            if !kind.is_transient() || attempt >= policy.max_retries {
+                return Err(error);
+            }
+            if !self.inner.retry_budget.withdraw() {
+                warn!("RPC 重试预算已耗尽，放弃重试: {}", error);
+                return Err(error);
+            }
 
-        provider.get_balance(address).await.map_err(|e| {
-            error!("获取 ETH 余额失败: {}", e);
-            EthereumError::RpcError(format!("获取余额失败: {}", e))
+            let delay = policy.backoff(attempt);
+            attempt += 1;
+            warn!(
+                "RPC 调用失败 ({})，{:?} 后进行第 {} 次重试: {}",
+                kind, delay, attempt, error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 按优先级尝试一轮端点，返回第一个成功结果或最后一个错误
+    async fn try_endpoints<T, E, F, Fut>(&self, operation: &F) -> std::result::Result<T, E>
+    where
+        F: Fn(HttpProvider) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: ClassifyError,
+    {
+        let endpoints = &self.inner.endpoints;
+        let healthy: Vec<&Endpoint> = endpoints.iter().filter(|e| e.is_healthy()).collect();
+        // 全部不健康时仍按顺序尝试，避免在探测恢复前完全不可用
+        let candidates = if healthy.is_empty() {
+            endpoints.iter().collect()
+        } else {
+            healthy
+        };
+
+        let mut last_error = None;
+        for endpoint in candidates {
+            let started = Instant::now();
+            match operation(endpoint.provider().clone()).await {
+                Err(e) if e.kind() == RpcErrorKind::StateUnavailable => {
+                    // 端点正常响应，只是没有该区块的状态：尝试下一个端点，但不计入故障
+                    endpoint.record_success(started.elapsed());
+                    debug!("RPC 端点 {} 没有所请求区块的状态: {}", endpoint.url(), e);
+                    last_error = Some(e);
+                }
+                Err(e) if e.kind().is_endpoint_failure() => {
+                    if endpoint.record_failure(&e, self.inner.unhealthy_after) {
+                        warn!("RPC 端点 {} 被标记为不健康: {}", endpoint.url(), e);
+                    } else {
+                        warn!(
+                            "RPC 端点 {} 调用失败，尝试下一个端点: {}",
+                            endpoint.url(),
+                            e
+                        );
+                    }
+                    last_error = Some(e);
+                }
+                outcome => {
+                    endpoint.record_success(started.elapsed());
+                    return outcome;
+                }
+            }
+        }
+
+        Err(last_error.expect("RpcClient 至少包含一个端点"))
+    }
+
+    /// 各端点的健康状况（诊断用）
+    pub fn endpoint_health(&self) -> Vec<EndpointStatus> {
+        self.inner.endpoints.iter().map(Endpoint::status).collect()
+    }
+
+    /// 缓存命中率和条目数（诊断用）
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// 获取端点报告的链 ID
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        self.execute(|provider| async move { provider.get_chain_id().await })
+            .await
+            .map_err(|e| EthereumError::rpc("获取链 ID 失败", e))
+    }
+
+    /// 检查每个端点报告的链 ID 是否与网络配置一致
+    ///
+    /// 只检查自身的端点，归档客户端需在 [`RpcClient::with_archive`] 挂载前单独检查。
+    /// 链 ID 不一致时返回 [`EthereumError::ConfigError`]；无法连接的端点只记录警告，
+    /// 不阻止启动，由之后的请求和健康探测处理。
+    pub async fn verify_chain_id(&self) -> Result<()> {
+        for endpoint in self.inner.endpoints.iter() {
+            match endpoint.provider().get_chain_id().await {
+                Ok(chain_id) if chain_id == self.network.chain_id => {
+                    debug!("RPC 端点 {} 的链 ID 为 {}", endpoint.url(), chain_id);
+                }
+                Ok(chain_id) => {
+                    return Err(EthereumError::ConfigError(format!(
+                        "RPC 端点 {} 的链 ID 为 {}，与配置的 {} ({}) 不一致",
+                        endpoint.url(),
+                        chain_id,
+                        self.network.chain_id,
+                        self.network.display_name
+                    )));
+                }
+                Err(e) => warn!("无法验证 RPC 端点 {} 的链 ID: {}", endpoint.url(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// 解析区块参数并固定到具体区块（区块号、哈希和时间戳）
+    ///
+    /// 工具调用开始时调用一次，之后的读取都使用 [`PinnedBlock::id`]。
+    /// 解析 `latest` 时若发现新区块，区块级缓存随之失效。
+    pub async fn pin_block(&self, block: BlockId) -> Result<PinnedBlock> {
+        debug!("正在解析区块: {}", block);
+
+        let header = self
+            .execute(|provider| async move {
+                provider
+                    .get_block(block, alloy::rpc::types::BlockTransactionsKind::Hashes)
+                    .await
+            })
+            .await
+            .map_err(|e| {
+                error!("获取区块失败: {}", e);
+                EthereumError::rpc("获取区块失败", e)
+            })?
+            .ok_or_else(|| EthereumError::BlockNotFound(block.to_string()))?
+            .header;
+
+        let pinned = PinnedBlock {
+            number: header.number,
+            hash: header.hash,
+            timestamp: header.timestamp,
+            base_fee_per_gas: header.base_fee_per_gas,
+        };
+        if block == BlockId::latest() {
+            self.cache.observe_latest(&pinned);
+        }
+        Ok(pinned)
+    }
+
+    /// 按工具请求的区块参数或时间戳固定区块
+    pub async fn resolve_block(&self, selector: BlockSelector) -> Result<PinnedBlock> {
+        match selector {
+            BlockSelector::Block(block) => self.pin_block(block).await,
+            BlockSelector::Timestamp(timestamp) => self.find_block_by_timestamp(timestamp).await,
+        }
+    }
+
+    /// 查找时间戳不晚于 `timestamp` 的最后一个区块（对 `eth_getBlockByNumber` 二分查找）
+    pub async fn find_block_by_timestamp(&self, timestamp: u64) -> Result<PinnedBlock> {
+        debug!("正在查找时间戳对应的区块: {}", timestamp);
+
+        let latest = self.pin_block(BlockId::latest()).await?;
+        search_block_by_timestamp(timestamp, latest, |number| {
+            self.pin_block(BlockId::number(number))
         })
+        .await
+    }
+
+    /// 获取地址在指定区块的 ETH 余额
+    pub async fn get_eth_balance(&self, address: Address, block: BlockId) -> Result<U256> {
+        let key = BlockKey::EthBalance(address);
+        if let Some(BlockValue::EthBalance(balance)) = self.cache.block_value(Some(block), &key) {
+            return Ok(balance);
+        }
+        debug!("正在获取 ETH 余额: {:?} (区块 {})", address, block);
+
+        let balance = self
+            .execute(|provider| async move { provider.get_balance(address).block_id(block).await })
+            .await
+            .map_err(|e| {
+                error!("获取 ETH 余额失败: {}", e);
+                EthereumError::rpc("获取余额失败", e)
+            })?;
+        self.cache
+            .store_block_value(Some(block), key, BlockValue::EthBalance(balance));
+        Ok(balance)
     }
 
-    /// 获取地址的 ERC20 代币余额
+    /// 获取地址在指定区块的 ERC20 代币余额
     pub async fn get_token_balance(
         &self,
         token_address: Address,
         account_address: Address,
+        block: BlockId,
     ) -> Result<U256> {
+        let key = BlockKey::TokenBalance(token_address, account_address);
+        if let Some(BlockValue::TokenBalance(balance)) = self.cache.block_value(Some(block), &key) {
+            return Ok(balance);
+        }
         debug!(
             "正在获取代币余额: {:?} 在代币: {:?}",
             account_address, token_address
         );
 
-        let provider = self.get_provider()?;
-        let contract = IERC20::new(token_address, provider);
-
-        contract
-            .balanceOf(account_address)
-            .call()
+        let balance = self
+            .execute(|provider| async move {
+                IERC20::new(token_address, provider)
+                    .balanceOf(account_address)
+                    .block(block)
+                    .call()
+                    .await
+                    .map(|r| r._0)
+            })
             .await
-            .map(|r| r._0)
             .map_err(|e| {
                 error!("获取代币余额失败: {} (代币: {:?})", e, token_address);
-                EthereumError::RpcError(format!("获取代币余额失败: {}", e))
-            })
+                EthereumError::rpc("获取代币余额失败", e)
+            })?;
+        self.cache
+            .store_block_value(Some(block), key, BlockValue::TokenBalance(balance));
+        Ok(balance)
     }
 
-    /// 获取 ERC20 代币小数位数
-    pub async fn get_token_decimals(&self, token_address: Address) -> Result<u8> {
+    /// 获取 ERC20 代币小数位数（结果永久缓存）
+    pub async fn get_token_decimals(&self, token_address: Address, block: BlockId) -> Result<u8> {
         debug!("正在获取代币小数位数: {:?}", token_address);
 
-        let provider = self.get_provider()?;
-        let contract = IERC20::new(token_address, provider);
-
-        contract.decimals().call().await.map(|r| r._0).map_err(|e| {
-            error!("获取代币小数位数失败: {}", e);
-            EthereumError::RpcError(format!("获取代币小数位数失败: {}", e))
-        })
+        self.call_immutable(token_address, IERC20::decimalsCall {}, block)
+            .await
+            .map(|r| r._0)
+            .map_err(|e| {
+                error!("获取代币小数位数失败: {}", e);
+                EthereumError::rpc("获取代币小数位数失败", e)
+            })
     }
 
-    /// 获取 ERC20 代币符号
-    pub async fn get_token_symbol(&self, token_address: Address) -> Result<String> {
+    /// 获取 ERC20 代币符号（结果永久缓存）
+    pub async fn get_token_symbol(&self, token_address: Address, block: BlockId) -> Result<String> {
         debug!("正在获取代币符号: {:?}", token_address);
 
-        let provider = self.get_provider()?;
-        let contract = IERC20::new(token_address, provider);
-
-        contract.symbol().call().await.map(|r| r._0).map_err(|e| {
-            error!("获取代币符号失败: {}", e);
-            EthereumError::RpcError(format!("获取代币符号失败: {}", e))
-        })
+        self.call_immutable(token_address, IERC20::symbolCall {}, block)
+            .await
+            .map(|r| r._0)
+            .map_err(|e| {
+                error!("获取代币符号失败: {}", e);
+                EthereumError::rpc("获取代币符号失败", e)
+            })
     }
 
-    /// 估算交易的 Gas
-    pub async fn estimate_gas(&self, tx: alloy::rpc::types::TransactionRequest) -> Result<u64> {
-        debug!("正在估算交易的 Gas");
+    /// 读取不可变的合约元数据，优先使用缓存，成功解码的返回数据写入缓存
+    async fn call_immutable<C: SolCall>(
+        &self,
+        target: Address,
+        call: C,
+        block: BlockId,
+    ) -> std::result::Result<C::Return, alloy::contract::Error> {
+        let call_data = Bytes::from(call.abi_encode());
+        if let Some(data) = self.cache.metadata(target, &call_data) {
+            if let Ok(decoded) = C::abi_decode_returns(&data, true) {
+                return Ok(decoded);
+            }
+        }
 
-        let provider = self.get_provider()?;
+        let tx = alloy::rpc::types::TransactionRequest::default()
+            .with_to(target)
+            .with_input(call_data.clone());
+        let data = self.eth_call(&tx, block).await?;
+        let decoded = C::abi_decode_returns(&data, true)?;
+        self.cache.store_metadata(target, call_data, data);
+        Ok(decoded)
+    }
 
-        provider.estimate_gas(&tx).await.map_err(|e| {
+    /// 在指定区块的状态上估算交易的 Gas
+    pub async fn estimate_gas(
+        &self,
+        tx: alloy::rpc::types::TransactionRequest,
+        block: BlockId,
+    ) -> Result<u64> {
+        debug!("正在估算交易的 Gas (区块 {})", block);
+
+        self.execute(|provider| {
+            let tx = tx.clone();
+            async move { provider.estimate_gas(&tx).block(block).await }
+        })
+        .await
+        .map_err(|e| {
             error!("Gas 估算失败: {}", e);
             EthereumError::GasEstimationFailed(format!("Gas 估算失败: {}", e))
         })
@@ -200,55 +542,199 @@ impl RpcClient {
 
     /// 获取当前 Gas 价格
     pub async fn get_gas_price(&self) -> Result<u128> {
+        if let Some(BlockValue::GasPrice(price)) = self.cache.block_value(None, &BlockKey::GasPrice)
+        {
+            return Ok(price);
+        }
         debug!("正在获取当前 Gas 价格");
 
-        let provider = self.get_provider()?;
-
-        provider.get_gas_price().await.map_err(|e| {
-            error!("获取 Gas 价格失败: {}", e);
-            EthereumError::RpcError(format!("获取 Gas 价格失败: {}", e))
-        })
+        let price = self
+            .execute(|provider| async move { provider.get_gas_price().await })
+            .await
+            .map_err(|e| {
+                error!("获取 Gas 价格失败: {}", e);
+                EthereumError::rpc("获取 Gas 价格失败", e)
+            })?;
+        self.cache
+            .store_block_value(None, BlockKey::GasPrice, BlockValue::GasPrice(price));
+        Ok(price)
     }
 
-    /// 调用合约函数（只读）
+    /// 在指定区块调用合约函数（只读）
     pub async fn call_contract(
         &self,
         tx: alloy::rpc::types::TransactionRequest,
+        block: BlockId,
     ) -> Result<alloy::primitives::Bytes> {
         debug!("正在调用合约函数");
 
-        let provider = self.get_provider()?;
-
-        provider.call(&tx).await.map_err(|e| {
+        self.eth_call(&tx, block).await.map_err(|e| {
             error!("调用合约失败: {}", e);
-            EthereumError::RpcError(format!("调用合约失败: {}", e))
+            EthereumError::rpc("调用合约失败", e)
         })
     }
 
-    /// 从 Uniswap V2 Router 获取交换输出金额
-    pub async fn get_amounts_out(&self, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
+    /// 通过 Multicall3 `aggregate3` 在一次 `eth_call` 中执行一批只读调用
+    ///
+    /// 返回值与 `batch` 中的调用一一对应；单个子调用失败只体现在对应的 [`CallResult`] 中，
+    /// 只有整个 `eth_call` 失败时才返回错误。
+    /// 命中缓存的子调用（代币元数据、当前区块的状态）不会再发送给节点。
+    pub async fn multicall(
+        &self,
+        batch: &MulticallBatch,
+        block: BlockId,
+    ) -> Result<Vec<CallResult>> {
+        let mut results: Vec<Option<CallResult>> = (0..batch.len())
+            .map(|i| self.cached_call(batch, i, block))
+            .collect();
+        let missing: Vec<usize> = (0..batch.len()).filter(|&i| results[i].is_none()).collect();
+        if missing.is_empty() {
+            return Ok(results.into_iter().flatten().collect());
+        }
+
+        let fetched = self.aggregate3(&batch.subset(&missing), block).await?;
+        for (i, result) in missing.into_iter().zip(fetched) {
+            self.store_call(batch, i, block, &result);
+            results[i] = Some(result);
+        }
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    fn cached_call(
+        &self,
+        batch: &MulticallBatch,
+        index: usize,
+        block: BlockId,
+    ) -> Option<CallResult> {
+        let (target, call_data) = (batch.target(index), batch.call_data(index));
+        if is_immutable_call(call_data) {
+            return self
+                .cache
+                .metadata(target, call_data)
+                .map(|data| CallResult::new(target, true, data));
+        }
+
+        match self
+            .cache
+            .block_value(Some(block), &BlockKey::Call(target, call_data.clone()))?
+        {
+            BlockValue::Call { success, data } => Some(CallResult::new(target, success, data)),
+            _ => None,
+        }
+    }
+
+    fn store_call(
+        &self,
+        batch: &MulticallBatch,
+        index: usize,
+        block: BlockId,
+        result: &CallResult,
+    ) {
+        let (target, call_data) = (batch.target(index), batch.call_data(index).clone());
+        if is_immutable_call(&call_data) {
+            if result.success() {
+                self.cache
+                    .store_metadata(target, call_data, result.return_data().clone());
+            }
+        } else {
+            self.cache.store_block_value(
+                Some(block),
+                BlockKey::Call(target, call_data),
+                BlockValue::Call {
+                    success: result.success(),
+                    data: result.return_data().clone(),
+                },
+            );
+        }
+    }
+
+    /// 把一批子调用编码为一次 `aggregate3` 的 `eth_call`
+    async fn aggregate3(&self, batch: &MulticallBatch, block: BlockId) -> Result<Vec<CallResult>> {
+        debug!(
+            "正在通过 Multicall3 批量调用: {} 个子调用 (区块 {})",
+            batch.len(),
+            block
+        );
+
+        let tx = alloy::rpc::types::TransactionRequest::default()
+            .with_to(self.network.multicall3)
+            .with_input(batch.encode());
+        let output = self.eth_call(&tx, block).await.map_err(|e| {
+            error!("Multicall 调用失败: {}", e);
+            EthereumError::rpc("Multicall 调用失败", e)
+        })?;
+        // 对没有代码的地址调用会成功但返回空数据
+        if output.is_empty() {
+            return Err(EthereumError::InvalidBlock(format!(
+                "Multicall3 ({}) 在区块 {} 尚未部署",
+                self.network.multicall3, block
+            )));
+        }
+
+        let results = IMulticall3::aggregate3Call::abi_decode_returns(&output, true)
+            .map_err(|e| EthereumError::rpc("解码 Multicall 结果失败", e))?
+            .returnData;
+        if results.len() != batch.len() {
+            return Err(EthereumError::RpcError {
+                kind: RpcErrorKind::InvalidResponse,
+                message: format!(
+                    "Multicall 返回了 {} 个结果，预期 {} 个",
+                    results.len(),
+                    batch.len()
+                ),
+            });
+        }
+
+        Ok(results
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| CallResult::new(batch.target(i), r.success, r.returnData))
+            .collect())
+    }
+
+    async fn eth_call(
+        &self,
+        tx: &alloy::rpc::types::TransactionRequest,
+        block: BlockId,
+    ) -> std::result::Result<alloy::primitives::Bytes, TransportError> {
+        self.execute(|provider| {
+            let tx = tx.clone();
+            async move { provider.call(&tx).block(block).await }
+        })
+        .await
+    }
+
+    /// 在指定区块从 Uniswap V2 Router 获取交换输出金额
+    pub async fn get_amounts_out(
+        &self,
+        amount_in: U256,
+        path: Vec<Address>,
+        block: BlockId,
+    ) -> Result<Vec<U256>> {
         debug!(
             "正在获取 Uniswap 交换输出金额: amount_in={}, path_len={}",
             amount_in,
             path.len()
         );
 
-        let router_address = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
-            .parse::<Address>()
-            .map_err(|_| EthereumError::ConfigError("无效的 Router 地址".to_string()))?;
-
-        let provider = self.get_provider()?;
-        let router = IUniswapV2Router::new(router_address, provider);
+        let router_address = self.network.uniswap_v2.router;
 
-        router
-            .getAmountsOut(amount_in, path)
-            .call()
-            .await
-            .map(|r| r.amounts)
-            .map_err(|e| {
-                error!("获取交换输出金额失败: {}", e);
-                EthereumError::RpcError(format!("获取交换输出金额失败: {}", e))
-            })
+        self.execute(|provider| {
+            let path = path.clone();
+            async move {
+                IUniswapV2Router::new(router_address, provider)
+                    .getAmountsOut(amount_in, path)
+                    .block(block)
+                    .call()
+                    .await
+                    .map(|r| r.amounts)
+            }
+        })
+        .await
+        .map_err(|e| {
+            error!("获取交换输出金额失败: {}", e);
+            EthereumError::rpc("获取交换输出金额失败", e)
+        })
     }
 
     /// 模拟 Uniswap V2 交换交易（使用 eth_call 只读模拟）
@@ -258,6 +744,7 @@ impl RpcClient {
     /// - ✅ 无交易广播：不会将交易提交到区块链
     /// - ✅ 安全模拟：可以获得真实的返回值而不承担任何风险
     /// - ✅ 免费执行：不需要支付 Gas 费用
+    #[allow(clippy::too_many_arguments)]
     pub async fn simulate_swap_exact_tokens_for_tokens(
         &self,
         amount_in: U256,
@@ -266,6 +753,7 @@ impl RpcClient {
         to: Address,
         deadline: U256,
         from: Address,
+        block: BlockId,
     ) -> Result<(Vec<U256>, u64)> {
         debug!(
             "正在模拟 Uniswap 交换: amount_in={}, path_len={}",
@@ -273,27 +761,25 @@ impl RpcClient {
             path.len()
         );
 
-        let router_address = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
-            .parse::<Address>()
-            .map_err(|_| EthereumError::ConfigError("无效的 Router 地址".to_string()))?;
-
-        let provider = self.get_provider()?;
-        let router = IUniswapV2Router::new(router_address, provider.clone());
+        let router_address = self.network.uniswap_v2.router;
 
         // 构建交易请求用于 eth_call 模拟
         // 注意：这个交易请求不会被发送到网络，只是用于 eth_call 的参数
+        let calldata = IUniswapV2Router::swapExactTokensForTokensCall {
+            amountIn: amount_in,
+            amountOutMin: amount_out_min,
+            path,
+            to,
+            deadline,
+        }
+        .abi_encode();
         let tx = alloy::rpc::types::TransactionRequest::default()
             .with_from(from)
             .with_to(router_address)
-            .with_input(
-                router
-                    .swapExactTokensForTokens(amount_in, amount_out_min, path.clone(), to, deadline)
-                    .calldata()
-                    .clone(),
-            );
+            .with_input(calldata);
 
         // 估算 Gas（使用 eth_estimateGas，也是只读操作）
-        let gas_estimate = self.estimate_gas(tx.clone()).await?;
+        let gas_estimate = self.estimate_gas(tx.clone(), block).await?;
 
         // 执行 eth_call 模拟（只读）
         // 这是关键步骤：使用以太坊的 eth_call JSON-RPC 方法
@@ -302,7 +788,7 @@ impl RpcClient {
         // 2. 不会修改区块链状态
         // 3. 不会广播任何交易到网络
         // 4. 返回函数的返回值供我们解析
-        let result = provider.call(&tx).await.map_err(|e| {
+        let result = self.eth_call(&tx, block).await.map_err(|e| {
             error!("交换模拟失败: {}", e);
             EthereumError::SwapSimulationFailed(format!("交换模拟失败: {}", e))
         })?;
@@ -322,14 +808,44 @@ impl RpcClient {
         Ok((amounts, gas_estimate))
     }
 
-    /// 获取 RPC URL
+    /// 获取主 RPC URL（优先级最高的端点）
     pub fn rpc_url(&self) -> &str {
-        &self.inner.provider_url
+        self.inner.endpoints[0].url()
+    }
+}
+
+/// 定期探测不健康的端点，恢复后重新参与请求；客户端全部释放后退出
+async fn probe_unhealthy_endpoints(inner: Weak<RpcClientInner>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let Some(inner) = inner.upgrade() else {
+            debug!("RPC 客户端已释放，停止端点健康探测");
+            return;
+        };
+
+        for endpoint in inner.endpoints.iter().filter(|e| !e.is_healthy()) {
+            let started = Instant::now();
+            match endpoint.provider().get_block_number().await {
+                Ok(block) => {
+                    endpoint.record_success(started.elapsed());
+                    info!("RPC 端点 {} 已恢复 (区块 {})", endpoint.url(), block);
+                }
+                Err(e) => {
+                    endpoint.record_failure(&e, inner.unhealthy_after);
+                    debug!("RPC 端点 {} 仍不可用: {}", endpoint.url(), e);
+                }
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
 
     #[test]
     fn test_rpc_client_creation() {
@@ -338,4 +854,410 @@ mod tests {
         let url = "https://eth.llamarpc.com";
         assert!(!url.is_empty());
     }
+
+    #[test]
+    fn test_invalid_url_rejected() {
+        let result = RpcClient::with_config("not a url".to_string(), &RpcClientConfig::default());
+        assert!(matches!(result, Err(EthereumError::ConfigError(_))));
+
+        let result = RpcClient::with_endpoints(Vec::new(), &RpcClientConfig::default());
+        assert!(matches!(result, Err(EthereumError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_next_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x4a817c800"}"#)
+            .create_async()
+            .await;
+
+        let config = RpcClientConfig {
+            unhealthy_after: 1,
+            ..RpcClientConfig::default()
+        };
+        // 第一个端点拒绝连接
+        let client = RpcClient::with_endpoints(
+            vec!["http://127.0.0.1:1".to_string(), server.url()],
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(client.get_gas_price().await.unwrap(), 20_000_000_000);
+        mock.assert_async().await;
+
+        let health = client.endpoint_health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].failures, 1);
+        assert!(health[1].healthy);
+        assert_eq!(health[1].successes, 1);
+
+        // 不健康的端点在探测恢复前不再被尝试
+        client.get_gas_price().await.unwrap();
+        assert_eq!(client.endpoint_health()[0].failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_error_response_does_not_fail_over() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":0,"error":{"code":3,"message":"execution reverted"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = RpcClient::with_endpoints(
+            vec![server.url(), "http://127.0.0.1:1".to_string()],
+            &RpcClientConfig::default(),
+        )
+        .unwrap();
+
+        assert!(client.get_gas_price().await.is_err());
+        let health = client.endpoint_health();
+        assert_eq!(health[0].failures, 0);
+        assert_eq!(health[1].successes + health[1].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoint_recovers_after_probe() {
+        let mut primary = mockito::Server::new_async().await;
+        let outage = primary
+            .mock("POST", "/")
+            .with_status(503)
+            .create_async()
+            .await;
+        let mut backup = mockito::Server::new_async().await;
+        backup
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x1"}"#)
+            .create_async()
+            .await;
+
+        let config = RpcClientConfig {
+            unhealthy_after: 1,
+            probe_interval: Duration::from_millis(50),
+            ..RpcClientConfig::default()
+        };
+        let client = RpcClient::with_endpoints(vec![primary.url(), backup.url()], &config).unwrap();
+
+        client.get_gas_price().await.unwrap();
+        assert!(!client.endpoint_health()[0].healthy);
+
+        outage.remove_async().await;
+        primary
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x10"}"#)
+            .create_async()
+            .await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert!(client.endpoint_health()[0].healthy);
+    }
+
+    fn fast_retry(max_retries: u32) -> RpcClientConfig {
+        RpcClientConfig {
+            retry: RetryPolicy {
+                max_retries,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(5),
+                ..RetryPolicy::default()
+            },
+            ..RpcClientConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_call_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let limited = server
+            .mock("POST", "/")
+            .with_status(429)
+            .expect(2)
+            .create_async()
+            .await;
+        let success = server
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = RpcClient::with_config(server.url(), &fast_retry(3)).unwrap();
+
+        assert_eq!(client.get_gas_price().await.unwrap(), 42);
+        limited.assert_async().await;
+        success.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_retries_exhausted_keep_error_kind() {
+        let mut server = mockito::Server::new_async().await;
+        let limited = server
+            .mock("POST", "/")
+            .with_status(429)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = RpcClient::with_config(server.url(), &fast_retry(2)).unwrap();
+
+        let err = client.get_gas_price().await.unwrap_err();
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::RateLimited));
+        limited.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_revert_is_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let revert = server
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":0,"error":{"code":3,"message":"execution reverted"}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = RpcClient::with_config(server.url(), &fast_retry(3)).unwrap();
+
+        let err = client.get_gas_price().await.unwrap_err();
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::ExecutionReverted));
+        revert.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_pruned_state_is_reported_clearly() {
+        let mut server = mockito::Server::new_async().await;
+        let pruned = server
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32000,"message":"missing trie node 5ab4ff (path ) state 0x5ab4ff is not available"}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = RpcClient::with_config(server.url(), &fast_retry(3)).unwrap();
+
+        let err = client
+            .get_eth_balance(Address::ZERO, BlockId::number(1_000_000))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::StateUnavailable(_)));
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::StateUnavailable));
+        pruned.assert_async().await;
+
+        // 节点本身是健康的
+        let health = client.endpoint_health();
+        assert!(health[0].healthy);
+        assert_eq!(health[0].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_hash_missing_on_fallback_is_retried() {
+        use crate::rpc::block::mock_block_response;
+        use alloy::primitives::B256;
+        use mockito::Matcher;
+
+        let balance_request =
+            || Matcher::PartialJsonString(r#"{"method":"eth_getBalance"}"#.to_string());
+        let mut primary = mockito::Server::new_async().await;
+        primary
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(100, B256::with_last_byte(100), 1200))
+            .create_async()
+            .await;
+        let unavailable = primary
+            .mock("POST", "/")
+            .match_body(balance_request())
+            .with_status(502)
+            .expect(1)
+            .create_async()
+            .await;
+        let balance = primary
+            .mock("POST", "/")
+            .match_body(balance_request())
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // 备用端点落后几个区块，还没有主端点固定的区块
+        let mut fallback = mockito::Server::new_async().await;
+        let behind = fallback
+            .mock("POST", "/")
+            .match_body(balance_request())
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32000,"message":"header for hash not found"}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client =
+            RpcClient::with_endpoints(vec![primary.url(), fallback.url()], &fast_retry(3)).unwrap();
+        let block = client.pin_block(BlockId::latest()).await.unwrap();
+        let value = client.get_eth_balance(Address::ZERO, block.id()).await;
+        assert_eq!(value.unwrap(), U256::from(42));
+
+        unavailable.assert_async().await;
+        behind.assert_async().await;
+        balance.assert_async().await;
+        assert_eq!(client.endpoint_health()[1].failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_cache_invalidated_by_new_block() {
+        use crate::rpc::block::mock_block_response;
+        use alloy::primitives::B256;
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new_async().await;
+        let latest = |number: u64| {
+            mock_block_response(number, B256::with_last_byte(number as u8), number * 12)
+        };
+        let first = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber","params":["latest",false]}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(latest(100))
+            .create_async()
+            .await;
+        let balance = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBalance"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = RpcClient::new(server.url()).await.unwrap();
+        let block = client.pin_block(BlockId::latest()).await.unwrap();
+        assert_eq!(block.base_fee_per_gas, Some(1_000_000_000));
+        for _ in 0..2 {
+            let value = client.get_eth_balance(Address::ZERO, block.id()).await;
+            assert_eq!(value.unwrap(), U256::from(42));
+        }
+        let stats = client.cache_stats();
+        assert_eq!((stats.block_hits, stats.block_misses), (1, 1));
+
+        // 新区块到达后重新读取
+        first.remove_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber","params":["latest",false]}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(latest(101))
+            .create_async()
+            .await;
+        let block = client.pin_block(BlockId::latest()).await.unwrap();
+        client
+            .get_eth_balance(Address::ZERO, block.id())
+            .await
+            .unwrap();
+
+        balance.assert_async().await;
+        let stats = client.cache_stats();
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(stats.block_number, Some(101));
+    }
+
+    #[tokio::test]
+    async fn test_token_balance_cached_per_block() {
+        use crate::rpc::block::mock_block_response;
+        use alloy::primitives::B256;
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(100, B256::with_last_byte(100), 1200))
+            .create_async()
+            .await;
+        let balance = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_call"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"0x{:064x}"}}"#,
+                42
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = RpcClient::new(server.url()).await.unwrap();
+        let block = client.pin_block(BlockId::latest()).await.unwrap();
+        let token = Address::with_last_byte(1);
+        for _ in 0..2 {
+            let value = client
+                .get_token_balance(token, Address::ZERO, block.id())
+                .await;
+            assert_eq!(value.unwrap(), U256::from(42));
+        }
+        let stats = client.cache_stats();
+        assert_eq!((stats.block_hits, stats.block_misses), (1, 1));
+
+        // 同一区块上其他代币的余额单独缓存
+        client
+            .get_token_balance(Address::with_last_byte(2), Address::ZERO, block.id())
+            .await
+            .unwrap();
+        balance.assert_async().await;
+        assert_eq!(client.cache_stats().block_misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_historical_reads_use_archive_endpoint() {
+        let config = RpcClientConfig::default();
+        let client = RpcClient::with_config("http://full.node".to_string(), &config)
+            .unwrap()
+            .with_archive(
+                RpcClient::with_config("http://archive.node".to_string(), &config).unwrap(),
+            );
+
+        let latest = BlockSelector::Block(BlockId::latest());
+        assert_eq!(client.state_reader(&latest).rpc_url(), "http://full.node");
+
+        let historical = BlockSelector::Block(BlockId::number(15_000_000));
+        assert_eq!(
+            client.state_reader(&historical).rpc_url(),
+            "http://archive.node"
+        );
+        assert_eq!(
+            client
+                .state_reader(&BlockSelector::Timestamp(1_719_705_600))
+                .rpc_url(),
+            "http://archive.node"
+        );
+    }
 }
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::rpc::client::HttpProvider;
+
+/// 延迟指数移动平均的平滑系数
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// 单个 RPC 端点及其健康统计
+pub(crate) struct Endpoint {
+    url: String,
+    provider: HttpProvider,
+    health: Mutex<EndpointHealth>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    unhealthy: bool,
+    latency_ms: Option<f64>,
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_failure: Option<SystemTime>,
+}
+
+impl Endpoint {
+    pub(crate) fn new(url: String, provider: HttpProvider) -> Self {
+        Endpoint {
+            url,
+            provider,
+            health: Mutex::new(EndpointHealth::default()),
+        }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn provider(&self) -> &HttpProvider {
+        &self.provider
+    }
+
+    pub(crate) fn is_healthy(&self) -> bool {
+        !self.health().unhealthy
+    }
+
+    /// 记录一次成功调用（包括节点返回了业务错误的调用）
+    pub(crate) fn record_success(&self, latency: Duration) {
+        let mut health = self.health();
+        let sample = latency.as_secs_f64() * 1000.0;
+        health.latency_ms = Some(match health.latency_ms {
+            Some(average) => average + LATENCY_EWMA_ALPHA * (sample - average),
+            None => sample,
+        });
+        health.successes += 1;
+        health.consecutive_failures = 0;
+        health.unhealthy = false;
+    }
+
+    /// 记录一次端点故障，连续失败达到 `unhealthy_after` 次后标记为不健康
+    ///
+    /// 返回本次是否刚被标记为不健康。
+    pub(crate) fn record_failure(&self, error: &dyn fmt::Display, unhealthy_after: u32) -> bool {
+        let mut health = self.health();
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+        health.last_failure = Some(SystemTime::now());
+
+        let became_unhealthy = !health.unhealthy && health.consecutive_failures >= unhealthy_after;
+        if became_unhealthy {
+            health.unhealthy = true;
+        }
+        became_unhealthy
+    }
+
+    /// 当前健康状况快照
+    pub(crate) fn status(&self) -> EndpointStatus {
+        let health = self.health();
+        let total = health.successes + health.failures;
+
+        EndpointStatus {
+            url: self.url.clone(),
+            healthy: !health.unhealthy,
+            latency_ms: health.latency_ms.map(|ms| (ms * 10.0).round() / 10.0),
+            error_rate: if total == 0 {
+                0.0
+            } else {
+                health.failures as f64 / total as f64
+            },
+            successes: health.successes,
+            failures: health.failures,
+            consecutive_failures: health.consecutive_failures,
+            last_error: health.last_error.clone(),
+            last_failure_at: health
+                .last_failure
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        }
+    }
+
+    fn health(&self) -> std::sync::MutexGuard<'_, EndpointHealth> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 端点健康状况（诊断信息）
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// 不健康的端点只在后台探测恢复后才重新参与请求
+    pub healthy: bool,
+    /// 成功调用延迟的指数移动平均（毫秒）
+    pub latency_ms: Option<f64>,
+    /// 端点故障占全部调用的比例
+    pub error_rate: f64,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// 最近一次故障的 Unix 时间戳（秒）
+    pub last_failure_at: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::providers::ProviderBuilder;
+
+    fn endpoint() -> Endpoint {
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .on_http("http://127.0.0.1:1".parse().unwrap());
+        Endpoint::new("http://127.0.0.1:1".to_string(), provider)
+    }
+
+    #[test]
+    fn test_unhealthy_after_consecutive_failures() {
+        let endpoint = endpoint();
+
+        assert!(!endpoint.record_failure(&"timeout", 2));
+        assert!(endpoint.is_healthy());
+        assert!(endpoint.record_failure(&"timeout", 2));
+        assert!(!endpoint.is_healthy());
+
+        endpoint.record_success(Duration::from_millis(50));
+        let status = endpoint.status();
+        assert!(status.healthy);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.latency_ms, Some(50.0));
+        assert!((status.error_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(status.last_error.as_deref(), Some("timeout"));
+    }
+}
@@ -1,3 +1,17 @@
+pub mod block;
+pub mod cache;
+pub mod chains;
+pub mod classify;
 pub mod client;
+pub mod endpoint;
+pub mod multicall;
+pub mod retry;
 
+pub use block::{parse_block_id, parse_timestamp, BlockSelector, PinnedBlock};
+pub use cache::{CacheConfig, CacheStats, RpcCache};
+pub use chains::ChainClients;
+pub use classify::{ClassifyError, RpcErrorKind};
 pub use client::RpcClient;
+pub use endpoint::EndpointStatus;
+pub use multicall::{CallResult, MulticallBatch};
+pub use retry::RetryPolicy;
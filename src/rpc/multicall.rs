@@ -0,0 +1,172 @@
+use alloy::primitives::{address, Address, Bytes};
+use alloy::sol;
+use alloy::sol_types::SolCall;
+
+use crate::error::{EthereumError, Result};
+use crate::rpc::classify::RpcErrorKind;
+
+/// Multicall3 合约地址（在以太坊主网和大多数 EVM 链上相同）
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+sol! {
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    contract IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Call3Result[] memory returnData);
+    }
+}
+
+/// 打包到一次 `aggregate3` 调用中的只读调用
+///
+/// 每个子调用都允许失败，单个子调用 revert 不会影响其他结果。
+#[derive(Debug, Clone, Default)]
+pub struct MulticallBatch {
+    calls: Vec<IMulticall3::Call3>,
+}
+
+impl MulticallBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个子调用，返回其结果在 [`RpcClient::multicall`](crate::rpc::RpcClient::multicall) 返回值中的下标
+    pub fn add<C: SolCall>(&mut self, target: Address, call: C) -> usize {
+        self.calls.push(IMulticall3::Call3 {
+            target,
+            allowFailure: true,
+            callData: call.abi_encode().into(),
+        });
+        self.calls.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub(crate) fn target(&self, index: usize) -> Address {
+        self.calls[index].target
+    }
+
+    pub(crate) fn call_data(&self, index: usize) -> &Bytes {
+        &self.calls[index].callData
+    }
+
+    /// 只包含 `indices` 所指子调用的新批次（按给定顺序）
+    pub(crate) fn subset(&self, indices: &[usize]) -> MulticallBatch {
+        MulticallBatch {
+            calls: indices.iter().map(|&i| self.calls[i].clone()).collect(),
+        }
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        IMulticall3::aggregate3Call {
+            calls: self.calls.clone(),
+        }
+        .abi_encode()
+    }
+}
+
+/// 单个子调用的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallResult {
+    target: Address,
+    success: bool,
+    return_data: Bytes,
+}
+
+impl CallResult {
+    pub(crate) fn new(target: Address, success: bool, return_data: Bytes) -> Self {
+        CallResult {
+            target,
+            success,
+            return_data,
+        }
+    }
+
+    pub fn target(&self) -> Address {
+        self.target
+    }
+
+    /// 子调用是否执行成功（未 revert）
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// 原始返回数据；调用没有代码的地址时成功但为空
+    pub fn return_data(&self) -> &Bytes {
+        &self.return_data
+    }
+
+    /// 按调用类型解码返回值
+    pub fn decode<C: SolCall>(&self) -> Result<C::Return> {
+        if !self.success {
+            return Err(EthereumError::RpcError {
+                kind: RpcErrorKind::ExecutionReverted,
+                message: format!("调用 {} 失败: execution reverted", self.target),
+            });
+        }
+        if self.return_data.is_empty() {
+            return Err(EthereumError::RpcError {
+                kind: RpcErrorKind::Decode,
+                message: format!("调用 {} 没有返回数据（合约可能不存在）", self.target),
+            });
+        }
+
+        C::abi_decode_returns(&self.return_data, true)
+            .map_err(|e| EthereumError::rpc(&format!("解码 {} 的返回值失败", self.target), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::client::IERC20;
+    use alloy::primitives::U256;
+    use alloy::sol_types::SolValue;
+
+    #[test]
+    fn test_call_result_decode() {
+        let token = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+
+        let ok = CallResult::new(token, true, U256::from(6).abi_encode().into());
+        assert_eq!(ok.decode::<IERC20::decimalsCall>().unwrap()._0, 6);
+
+        let reverted = CallResult::new(token, false, Bytes::new());
+        let err = reverted
+            .decode::<IERC20::decimalsCall>()
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::ExecutionReverted));
+
+        let no_code = CallResult::new(token, true, Bytes::new());
+        let err = no_code
+            .decode::<IERC20::decimalsCall>()
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::Decode));
+    }
+
+    #[test]
+    fn test_batch_indices() {
+        let mut batch = MulticallBatch::new();
+        assert!(batch.is_empty());
+        assert_eq!(batch.add(MULTICALL3_ADDRESS, IERC20::decimalsCall {}), 0);
+        assert_eq!(batch.add(MULTICALL3_ADDRESS, IERC20::symbolCall {}), 1);
+        assert_eq!(batch.len(), 2);
+    }
+}
@@ -0,0 +1,131 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 重试策略：带抖动的指数退避
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 单次调用的最大重试次数（不含首次尝试）
+    pub max_retries: u32,
+    /// 第一次重试前的退避上限
+    pub base_delay: Duration,
+    /// 退避上限的最大值
+    pub max_delay: Duration,
+    /// 重试预算：最多可累积的重试次数
+    pub budget_capacity: f64,
+    /// 重试预算：每次调用补充的重试次数（0.2 表示长期重试量不超过调用量的 20%）
+    pub budget_refill_per_call: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            budget_capacity: 20.0,
+            budget_refill_per_call: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试
+    pub fn disabled() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间
+    ///
+    /// 使用 "full jitter"：在 `[0, min(max_delay, base_delay * 2^attempt)]` 内均匀取值，
+    /// 避免多个客户端在同一时刻集中重试。
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// 重试预算（令牌桶）
+///
+/// 每次调用补充少量令牌，每次重试消耗一个令牌。端点长时间故障时预算耗尽，
+/// 重试自动停止，避免重试流量放大故障。
+#[derive(Debug)]
+pub struct RetryBudget {
+    tokens: Mutex<f64>,
+    capacity: f64,
+    refill_per_call: f64,
+}
+
+impl RetryBudget {
+    pub fn new(policy: &RetryPolicy) -> Self {
+        let capacity = policy.budget_capacity.max(0.0);
+        RetryBudget {
+            tokens: Mutex::new(capacity),
+            capacity,
+            refill_per_call: policy.budget_refill_per_call.max(0.0),
+        }
+    }
+
+    /// 记录一次调用
+    pub fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        *tokens = (*tokens + self.refill_per_call).min(self.capacity);
+    }
+
+    /// 尝试消耗一次重试，预算不足时返回 false
+    pub fn withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 当前剩余的重试次数
+    pub fn remaining(&self) -> f64 {
+        *self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..10 {
+            let ceiling = (policy.base_delay * 2u32.pow(attempt)).min(policy.max_delay);
+            assert!(policy.backoff(attempt) <= ceiling);
+        }
+        assert!(policy.backoff(40) <= policy.max_delay);
+    }
+
+    #[test]
+    fn test_budget_limits_retries() {
+        let budget = RetryBudget::new(&RetryPolicy {
+            budget_capacity: 2.0,
+            budget_refill_per_call: 0.5,
+            ..RetryPolicy::default()
+        });
+
+        assert!(budget.withdraw());
+        assert!(budget.withdraw());
+        assert!(!budget.withdraw());
+
+        budget.deposit();
+        assert!(!budget.withdraw());
+        budget.deposit();
+        assert!(budget.withdraw());
+    }
+}
@@ -0,0 +1,697 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{debug, info, warn};
+
+use crate::server::mcp::{JsonRpcRequest, McpServer, SUPPORTED_PROTOCOL_VERSIONS};
+use crate::server::session::McpSession;
+use crate::server::transport::SERVER_OVERLOADED;
+
+/// Streamable HTTP 端点路径
+pub const MCP_ENDPOINT: &str = "/mcp";
+
+/// 会话 ID 请求/响应头
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// 协议版本请求头
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// 会话默认的空闲过期时间
+pub const DEFAULT_SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// 默认允许同时存在的会话数
+pub const DEFAULT_MAX_SESSIONS: usize = 1024;
+
+/// Streamable HTTP 传输配置：会话的数量上限、空闲过期时间和允许的浏览器 Origin
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// 超过该时间未收到请求且没有打开 SSE 流的会话被删除，之后的请求返回 404
+    pub idle_timeout: Duration,
+    /// 同时存在的会话数上限，达到上限后新的 `initialize` 返回 503
+    pub max_sessions: usize,
+    /// 除本机外允许的 Origin（如 `https://agent.example.com`），`*` 表示允许任意 Origin
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            idle_timeout: DEFAULT_SESSION_IDLE_TIMEOUT,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct HttpSession {
+    session: Arc<McpSession>,
+    last_seen: Arc<Mutex<Instant>>,
+}
+
+impl HttpSession {
+    fn new(session: Arc<McpSession>) -> Self {
+        HttpSession {
+            session,
+            last_seen: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// 打开 SSE 流的会话仍在使用中，不会过期
+    fn is_expired(&self, idle_timeout: Duration) -> bool {
+        !self.session.has_subscribers()
+            && self
+                .last_seen
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .elapsed()
+                >= idle_timeout
+    }
+
+    fn touch(&self) {
+        touch(&self.last_seen);
+    }
+}
+
+fn touch(last_seen: &Mutex<Instant>) {
+    *last_seen.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+}
+
+/// SSE 流结束时刷新会话的活动时间，空闲计时从流关闭时开始
+///
+/// 只持有活动时间而不持有会话，会话被删除后 SSE 流仍能随发送端释放而结束。
+struct TouchOnDrop(Arc<Mutex<Instant>>);
+
+impl Drop for TouchOnDrop {
+    fn drop(&mut self) {
+        touch(&self.0);
+    }
+}
+
+#[derive(Clone)]
+struct HttpState {
+    server: Arc<McpServer>,
+    sessions: Arc<RwLock<HashMap<String, HttpSession>>>,
+    config: Arc<HttpConfig>,
+}
+
+/// 构建 Streamable HTTP 路由
+///
+/// - `POST /mcp`: 发送一条 JSON-RPC 消息，请求返回 JSON 响应，通知返回 202
+/// - `GET /mcp`: 打开 SSE 流，接收服务器推送给该会话的通知
+/// - `DELETE /mcp`: 结束会话
+pub fn router(mcp_server: Arc<McpServer>, config: HttpConfig) -> Router {
+    let state = HttpState {
+        server: mcp_server,
+        sessions: Arc::new(RwLock::new(HashMap::new())),
+        config: Arc::new(config),
+    };
+
+    Router::new()
+        .route(
+            MCP_ENDPOINT,
+            post(handle_post).get(handle_get).delete(handle_delete),
+        )
+        .with_state(state)
+}
+
+/// 在指定地址上提供 Streamable HTTP 服务
+pub async fn serve_http(
+    addr: SocketAddr,
+    mcp_server: Arc<McpServer>,
+    config: HttpConfig,
+) -> eyre::Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+
+    info!("MCP 服务器监听 http://{}{}", addr, MCP_ENDPOINT);
+
+    axum::serve(listener, router(mcp_server, config)).await?;
+    Ok(())
+}
+
+async fn handle_post(State(state): State<HttpState>, headers: HeaderMap, body: Bytes) -> Response {
+    if let Some(response) = reject_foreign_origin(&headers, &state.config.allowed_origins) {
+        return response;
+    }
+
+    let message = match serde_json::from_slice::<Value>(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("无法解析 JSON-RPC 请求: {}", e);
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                -32700,
+                &format!("Parse error: {}", e),
+            );
+        }
+    };
+
+    // initialize 总是创建新会话（不允许出现在批量请求中），成功后通过响应头下发会话 ID
+    if message.get("method").and_then(|m| m.as_str()) == Some("initialize") {
+        return initialize_session(&state, message).await;
+    }
+
+    if let Some(response) = reject_unsupported_version(&headers) {
+        return response;
+    }
+
+    let session = match lookup_session(&state, &headers).await {
+        Ok(entry) => entry.session,
+        Err(response) => return response,
+    };
+
+    match state.server.handle_value(&session, message).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn initialize_session(state: &HttpState, message: Value) -> Response {
+    let request = match serde_json::from_value::<JsonRpcRequest>(message) {
+        Ok(request) => request,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                -32600,
+                &format!("Invalid Request: {}", e),
+            )
+        }
+    };
+
+    let session = Arc::new(McpSession::new());
+    let Some(response) = state.server.handle_request(&session, request).await else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    if response.error.is_some() {
+        return Json(response).into_response();
+    }
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    {
+        let mut sessions = state.sessions.write().await;
+        // 新会话是会话表唯一的增长来源，在此清理过期会话即可限制内存占用
+        sessions.retain(|id, entry| {
+            let expired = entry.is_expired(state.config.idle_timeout);
+            if expired {
+                debug!("HTTP 会话空闲过期: {}", id);
+            }
+            !expired
+        });
+        if sessions.len() >= state.config.max_sessions {
+            warn!("HTTP 会话数已达上限 {}", state.config.max_sessions);
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                SERVER_OVERLOADED,
+                &format!("Too many sessions (limit {})", state.config.max_sessions),
+            );
+        }
+        sessions.insert(session_id.clone(), HttpSession::new(session));
+    }
+    debug!("创建 HTTP 会话: {}", session_id);
+
+    let mut http_response = Json(response).into_response();
+    if let Ok(value) = HeaderValue::from_str(&session_id) {
+        http_response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    http_response
+}
+
+async fn handle_get(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    if let Some(response) = reject_foreign_origin(&headers, &state.config.allowed_origins) {
+        return response;
+    }
+
+    let accepts_sse = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+    if !accepts_sse {
+        return error_response(
+            StatusCode::NOT_ACCEPTABLE,
+            -32600,
+            "GET requires Accept: text/event-stream",
+        );
+    }
+
+    let entry = match lookup_session(&state, &headers).await {
+        Ok(entry) => entry,
+        Err(response) => return response,
+    };
+
+    // 流只持有接收端，会话被删除后发送端释放，SSE 流随之结束
+    let guard = TouchOnDrop(entry.last_seen);
+    let stream = notification_stream(&entry.session).map(move |event| {
+        let _guard = &guard;
+        event
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn handle_delete(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    if let Some(response) = reject_foreign_origin(&headers, &state.config.allowed_origins) {
+        return response;
+    }
+
+    let Some(session_id) = session_id(&headers) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "Missing Mcp-Session-Id header",
+        );
+    };
+
+    match state.sessions.write().await.remove(&session_id) {
+        Some(_) => {
+            debug!("结束 HTTP 会话: {}", session_id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        None => error_response(StatusCode::NOT_FOUND, -32001, "Session not found"),
+    }
+}
+
+fn notification_stream(session: &McpSession) -> impl Stream<Item = Result<Event, Infallible>> {
+    BroadcastStream::new(session.subscribe()).filter_map(|message| match message {
+        Ok(message) => Some(Ok(Event::default()
+            .event("message")
+            .data(message.to_string()))),
+        Err(e) => {
+            // 消费过慢导致丢弃的消息只记录日志，不中断流
+            warn!("SSE 订阅者落后: {}", e);
+            None
+        }
+    })
+}
+
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+async fn lookup_session(state: &HttpState, headers: &HeaderMap) -> Result<HttpSession, Response> {
+    let session_id = session_id(headers).ok_or_else(|| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "Missing Mcp-Session-Id header",
+        )
+    })?;
+
+    let not_found = || error_response(StatusCode::NOT_FOUND, -32001, "Session not found");
+
+    {
+        let sessions = state.sessions.read().await;
+        let entry = sessions.get(&session_id).ok_or_else(not_found)?;
+        if !entry.is_expired(state.config.idle_timeout) {
+            entry.touch();
+            return Ok(entry.clone());
+        }
+    }
+
+    // 过期会话与不存在的会话一样返回 404，客户端应重新 initialize
+    let mut sessions = state.sessions.write().await;
+    if sessions
+        .get(&session_id)
+        .is_some_and(|entry| entry.is_expired(state.config.idle_timeout))
+    {
+        sessions.remove(&session_id);
+        debug!("HTTP 会话空闲过期: {}", session_id);
+    }
+    Err(not_found())
+}
+
+/// 拒绝来自本机和 `allowed_origins` 以外 Origin 的浏览器请求，防止 DNS 重绑定攻击
+fn reject_foreign_origin(headers: &HeaderMap, allowed_origins: &[String]) -> Option<Response> {
+    let origin = headers.get(header::ORIGIN)?;
+
+    let is_allowed = origin
+        .to_str()
+        .ok()
+        .and_then(|o| o.parse::<url::Url>().ok())
+        .is_some_and(|u| {
+            let is_local = u
+                .host_str()
+                .is_some_and(|h| h == "localhost" || h == "127.0.0.1" || h == "[::1]");
+            let serialized = u.origin().ascii_serialization();
+            is_local
+                || allowed_origins
+                    .iter()
+                    .any(|allowed| allowed == "*" || allowed.trim_end_matches('/') == serialized)
+        });
+
+    if is_allowed {
+        None
+    } else {
+        warn!("拒绝来自 Origin {:?} 的请求", origin);
+        Some(error_response(
+            StatusCode::FORBIDDEN,
+            -32600,
+            "Origin not allowed",
+        ))
+    }
+}
+
+/// 校验 `Mcp-Protocol-Version` 请求头（缺省时视为兼容）
+fn reject_unsupported_version(headers: &HeaderMap) -> Option<Response> {
+    let version = headers
+        .get(PROTOCOL_VERSION_HEADER)
+        .and_then(|v| v.to_str().ok())?;
+
+    if SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+        None
+    } else {
+        Some(error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            &format!("Unsupported protocol version: {}", version),
+        ))
+    }
+}
+
+fn error_response(status: StatusCode, code: i32, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "jsonrpc": "2.0",
+            "error": {
+                "code": code,
+                "message": message
+            },
+            "id": null
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    async fn spawn_server() -> String {
+        spawn_server_with_config(HttpConfig::default()).await
+    }
+
+    async fn spawn_server_with_config(config: HttpConfig) -> String {
+        let server = Arc::new(McpServer::new(Config::from_url(
+            "https://eth.llamarpc.com".to_string(),
+        )));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(server, config)).await.unwrap();
+        });
+        format!("http://{}{}", addr, MCP_ENDPOINT)
+    }
+
+    #[tokio::test]
+    async fn test_http_session_lifecycle() {
+        let url = spawn_server().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "method": "initialize",
+                "params": {"protocolVersion": "2025-06-18"},
+                "id": 1
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let session_id = response
+            .headers()
+            .get(SESSION_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "ping", "id": 2}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["id"], 2);
+
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session_id)
+            .json(&json!([
+                {"jsonrpc": "2.0", "method": "ping", "id": 10},
+                {"jsonrpc": "2.0", "method": "ping", "id": 11}
+            ]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body.as_array().unwrap().len(), 2);
+
+        let response = client
+            .delete(&url)
+            .header(SESSION_HEADER, &session_id)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "ping", "id": 3}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    async fn initialize(client: &reqwest::Client, url: &str) -> reqwest::Response {
+        client
+            .post(url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "method": "initialize",
+                "params": {"protocolVersion": "2025-06-18"},
+                "id": 1
+            }))
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn ping(client: &reqwest::Client, url: &str, session_id: &str) -> reqwest::Response {
+        client
+            .post(url)
+            .header(SESSION_HEADER, session_id)
+            .json(&json!({"jsonrpc": "2.0", "method": "ping", "id": 2}))
+            .send()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_session_idle_expiry() {
+        let url = spawn_server_with_config(HttpConfig {
+            idle_timeout: Duration::from_millis(200),
+            ..HttpConfig::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let response = initialize(&client, &url).await;
+        let session_id = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // 每次请求都会刷新空闲计时
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(ping(&client, &url, &session_id).await.status(), 200);
+        }
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(ping(&client, &url, &session_id).await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_http_session_with_open_stream_does_not_expire() {
+        let url = spawn_server_with_config(HttpConfig {
+            idle_timeout: Duration::from_millis(200),
+            ..HttpConfig::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let response = initialize(&client, &url).await;
+        let session_id = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // 只通过 SSE 流接收通知的客户端长时间不发请求
+        let stream = client
+            .get(&url)
+            .header(SESSION_HEADER, &session_id)
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stream.status(), 200);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(ping(&client, &url, &session_id).await.status(), 200);
+
+        // 流关闭后从关闭时开始计时
+        drop(stream);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(ping(&client, &url, &session_id).await.status(), 200);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(ping(&client, &url, &session_id).await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_http_max_sessions() {
+        let url = spawn_server_with_config(HttpConfig {
+            idle_timeout: Duration::from_millis(200),
+            max_sessions: 1,
+            ..HttpConfig::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let response = initialize(&client, &url).await;
+        assert_eq!(response.status(), 200);
+        let session_id = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = initialize(&client, &url).await;
+        assert_eq!(response.status(), 503);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], SERVER_OVERLOADED);
+
+        // 过期会话在创建新会话时被清理，不再占用名额
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let response = initialize(&client, &url).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(ping(&client, &url, &session_id).await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_notification_stream_forwards_session_messages() {
+        let session = McpSession::new();
+        let mut stream = Box::pin(notification_stream(&session));
+
+        session.notify("notifications/message", json!({"level": "info"}));
+
+        assert!(stream.next().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_http_requires_session_header() {
+        let url = spawn_server().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .json(&json!({"jsonrpc": "2.0", "method": "tools/list", "id": 1}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_http_rejects_foreign_origin() {
+        let url = spawn_server().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .header("Origin", "https://evil.example")
+            .json(&json!({"jsonrpc": "2.0", "method": "ping", "id": 1}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+    }
+
+    #[tokio::test]
+    async fn test_http_allows_configured_origin() {
+        let url = spawn_server_with_config(HttpConfig {
+            allowed_origins: vec!["https://agent.example/".to_string()],
+            ..HttpConfig::default()
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let initialize_from = |origin: &'static str| {
+            client
+                .post(&url)
+                .header("Origin", origin)
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "method": "initialize",
+                    "params": {"protocolVersion": "2025-06-18"},
+                    "id": 1
+                }))
+                .send()
+        };
+        assert_eq!(
+            initialize_from("https://agent.example")
+                .await
+                .unwrap()
+                .status(),
+            200
+        );
+        assert_eq!(
+            initialize_from("http://localhost:3000")
+                .await
+                .unwrap()
+                .status(),
+            200
+        );
+        assert_eq!(
+            initialize_from("https://agent.example:8443")
+                .await
+                .unwrap()
+                .status(),
+            403
+        );
+    }
+}
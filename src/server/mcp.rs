@@ -2,21 +2,43 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
 use tracing::{debug, info};
 
-use crate::config::Config;
-use crate::rpc::RpcClient;
-use crate::tools::balance::{BalanceRequest, BalanceTool};
-use crate::tools::price::{PriceRequest, PriceTool};
-use crate::tools::swap::{SwapRequest, SwapTool};
+use crate::config::{ChainConfig, Config, RpcClientConfig};
+use crate::rpc::{ChainClients, RpcClient};
+use crate::server::session::McpSession;
+use crate::tokens::{TokenList, TokenRegistry, TokenStore};
+use crate::tools::registry::{Tool, ToolContext, ToolDefinition, ToolError, ToolRegistry};
+use crate::tools::{BalanceTool, NetworksTool, PriceTool, SwapTool};
+
+/// 服务器支持的 MCP 协议版本（按从新到旧排列）
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// 服务器首选的 MCP 协议版本
+pub const LATEST_PROTOCOL_VERSION: &str = SUPPORTED_PROTOCOL_VERSIONS[0];
+
+/// 在 `initialize` 中上报的服务器名称
+pub const SERVER_NAME: &str = "ethereum-trading-mcp-server";
 
 /// JSON-RPC 2.0 请求格式
+///
+/// 没有 `id` 的消息是通知（notification），服务器不得对其回复。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
     pub method: String,
+    #[serde(default)]
     pub params: Value,
-    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// 是否为通知消息（无 `id`，不需要响应）
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
 }
 
 /// JSON-RPC 2.0 响应格式
@@ -30,6 +52,26 @@ pub struct JsonRpcResponse {
     pub id: Value,
 }
 
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(result),
+            error: None,
+            id,
+        }
+    }
+
+    pub fn error(id: Value, error: JsonRpcError) -> Self {
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: None,
+            error: Some(error),
+            id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcError {
     pub code: i32,
@@ -38,129 +80,158 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
-/// MCP 工具定义
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolDefinition {
-    pub name: String,
-    pub description: String,
-    pub input_schema: Value,
+/// 创建一条链的 RPC 客户端，并校验普通端点和归档端点的链 ID
+///
+/// 端点连到了其他链时拒绝启动，避免用错误链的合约地址读取状态；归档端点在挂载前单独校验，
+/// 否则历史查询会读到其他链的状态。
+async fn connect_chain(
+    chain: &ChainConfig,
+    config: &RpcClientConfig,
+) -> crate::error::Result<RpcClient> {
+    let network = chain.network()?;
+    let rpc = RpcClient::with_endpoints(chain.rpc_endpoints(), config)?.with_network(network);
+    rpc.verify_chain_id().await?;
+    if chain.archive_rpc_urls.is_empty() {
+        return Ok(rpc);
+    }
+
+    let archive =
+        RpcClient::with_endpoints(chain.archive_rpc_urls.clone(), config)?.with_network(network);
+    archive.verify_chain_id().await?;
+    Ok(rpc.with_archive(archive))
 }
 
 /// 以太坊交易工具的 MCP 服务器
 pub struct McpServer {
     config: Config,
-    rpc_client: Arc<RwLock<Option<RpcClient>>>,
-    balance_tool: Arc<RwLock<Option<BalanceTool>>>,
-    price_tool: Arc<RwLock<Option<PriceTool>>>,
-    swap_tool: Arc<RwLock<Option<SwapTool>>>,
+    chains: Arc<RwLock<Option<ChainClients>>>,
+    tools: Arc<RwLock<ToolRegistry>>,
 }
 
 impl McpServer {
     pub fn new(config: Config) -> Self {
         McpServer {
             config,
-            rpc_client: Arc::new(RwLock::new(None)),
-            balance_tool: Arc::new(RwLock::new(None)),
-            price_tool: Arc::new(RwLock::new(None)),
-            swap_tool: Arc::new(RwLock::new(None)),
+            chains: Arc::new(RwLock::new(None)),
+            tools: Arc::new(RwLock::new(ToolRegistry::new())),
         }
     }
 
-    /// 初始化服务器并连接到 RPC
+    /// 初始化服务器并连接到各条链的 RPC，注册内置工具
     pub async fn initialize(&self) -> crate::error::Result<()> {
         info!("使用 RPC URL 初始化 MCP 服务器: {}", self.config.rpc_url);
 
-        let rpc = RpcClient::new(self.config.rpc_url.clone()).await?;
+        let chains = self.config.chains();
+        let clients = futures::future::try_join_all(
+            chains
+                .iter()
+                .map(|chain| connect_chain(chain, &self.config.rpc)),
+        )
+        .await?;
+        let chains = ChainClients::new(clients)?;
+        for rpc in chains.iter() {
+            let network = rpc.network();
+            info!(
+                "网络: {} (链 ID {}): {}",
+                network.display_name,
+                network.chain_id,
+                rpc.rpc_url()
+            );
+        }
+
+        *self.chains.write().await = Some(chains.clone());
 
-        *self.rpc_client.write().await = Some(rpc.clone());
-        *self.balance_tool.write().await = Some(BalanceTool::new(rpc.clone()));
-        *self.price_tool.write().await = Some(PriceTool::new(rpc.clone()));
-        *self.swap_tool.write().await = Some(SwapTool::new(rpc));
+        // 所有工具共享同一个代币注册表，运行时解析到的代币对所有工具可见
+        let token_lists = TokenList::load_all(&self.config.token_lists).await;
+        let token_registry = TokenRegistry::from_token_lists(&token_lists);
+        let token_registry = Arc::new(match &self.config.data_dir {
+            Some(data_dir) => token_registry.with_store(TokenStore::open(data_dir)?)?,
+            None => token_registry,
+        });
+
+        let mut tools = self.tools.write().await;
+        tools.register(Arc::new(BalanceTool::with_chains(
+            chains.clone(),
+            token_registry.clone(),
+        )));
+        tools.register(Arc::new(PriceTool::with_chains(
+            chains.clone(),
+            token_registry.clone(),
+        )));
+        tools.register(Arc::new(SwapTool::with_chains(
+            chains.clone(),
+            token_registry,
+        )));
+        tools.register(Arc::new(NetworksTool::new(chains)));
 
         info!("MCP 服务器初始化成功");
         Ok(())
     }
 
+    /// 注册一个工具（同名工具会被替换）
+    pub async fn register_tool(&self, tool: Arc<dyn Tool>) {
+        info!("注册工具: {}", tool.name());
+        if self.tools.write().await.register(tool).is_some() {
+            debug!("替换了同名工具");
+        }
+    }
+
+    /// 已初始化的默认链 RPC 客户端，供下游工具复用
+    pub async fn rpc_client(&self) -> Option<RpcClient> {
+        self.chains
+            .read()
+            .await
+            .as_ref()
+            .map(|chains| chains.default_client().clone())
+    }
+
+    /// 已初始化的各链 RPC 客户端
+    pub async fn chain_clients(&self) -> Option<ChainClients> {
+        self.chains.read().await.clone()
+    }
+
     /// 获取工具定义（MCP 规范）
     pub async fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
-        vec![
-            ToolDefinition {
-                name: "get_balance".to_string(),
-                description: "Get ETH or ERC20 token balance for a wallet address".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "address": {
-                            "type": "string",
-                            "description": "Ethereum wallet address (0x...)"
-                        },
-                        "token_address": {
-                            "type": "string",
-                            "description": "ERC20 contract address (optional, omit for ETH balance)"
-                        }
-                    },
-                    "required": ["address"]
-                }),
-            },
-            ToolDefinition {
-                name: "get_token_price".to_string(),
-                description: "Get current price of a token in USD and ETH".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "token_identifier": {
-                            "type": "string",
-                            "description": "Token symbol (e.g., ETH, USDC) or contract address"
-                        }
-                    },
-                    "required": ["token_identifier"]
-                }),
-            },
-            ToolDefinition {
-                name: "swap_tokens".to_string(),
-                description: "Simulate a token swap on Uniswap (no actual transaction executed)"
-                    .to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "from_token": {
-                            "type": "string",
-                            "description": "Source token symbol or address"
-                        },
-                        "to_token": {
-                            "type": "string",
-                            "description": "Destination token symbol or address"
-                        },
-                        "amount": {
-                            "type": "string",
-                            "description": "Amount to swap (in human-readable format)"
-                        },
-                        "slippage": {
-                            "type": "number",
-                            "description": "Slippage tolerance in percentage (e.g., 0.5 for 0.5%)"
-                        },
-                        "wallet_address": {
-                            "type": "string",
-                            "description": "Wallet address initiating the swap"
-                        }
-                    },
-                    "required": ["from_token", "to_token", "amount", "slippage", "wallet_address"]
-                }),
-            },
-        ]
+        self.tools.read().await.definitions()
     }
 
-    /// 处理一个 JSON-RPC 请求
-    pub async fn handle_request(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+    /// 处理一个 JSON-RPC 消息
+    ///
+    /// 对于通知消息返回 `None`，调用方不应写回任何内容。
+    pub async fn handle_request(
+        &self,
+        session: &McpSession,
+        request: JsonRpcRequest,
+    ) -> Option<JsonRpcResponse> {
+        info!("收到请求: {} (id: {:?})", request.method, request.id);
         debug!(
             "处理 MCP 请求: {} 带参数: {:?}",
             request.method, request.params
         );
 
+        let id = match request.id {
+            Some(id) => id,
+            None => {
+                self.handle_notification(session, &request.method, &request.params);
+                return None;
+            }
+        };
+
         let response = match request.method.as_str() {
+            "initialize" => self.handle_initialize(session, &request.params),
+            "ping" => Ok(json!({})),
+            _ if !session.is_initialized() => Err(JsonRpcError {
+                code: -32002,
+                message: format!("Server not initialized: {}", request.method),
+                data: None,
+            }),
             "tools/list" => self.handle_tools_list().await,
-            "tools/call" => self.handle_tool_call(&request.params).await,
-            "ping" => Ok(json!({"status": "ok"})),
+            "tools/call" => match self.handle_tool_call(session, &id, &request.params).await {
+                Ok(Some(result)) => Ok(result),
+                // 被 notifications/cancelled 取消的请求不再响应
+                Ok(None) => return None,
+                Err(err) => Err(err),
+            },
             _ => Err(JsonRpcError {
                 code: -32601,
                 message: format!("Method not found: {}", request.method),
@@ -168,32 +239,161 @@ impl McpServer {
             }),
         };
 
-        match response {
-            Ok(result) => JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: Some(result),
-                error: None,
-                id: request.id,
+        Some(match response {
+            Ok(result) => JsonRpcResponse::success(id, result),
+            Err(err) => JsonRpcResponse::error(id, err),
+        })
+    }
+
+    /// 处理一条已解析为 JSON 的消息：单个请求或批量请求
+    ///
+    /// 没有需要回复的内容（通知、全部为通知的批量请求）时返回 `None`。
+    pub async fn handle_value(&self, session: &McpSession, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(items) => self.handle_batch(session, items).await,
+            message => self.handle_batch_entry(session, message).await,
+        }
+    }
+
+    /// 并发处理 JSON-RPC 2.0 批量请求，响应按请求顺序返回，通知不产生响应
+    pub async fn handle_batch(&self, session: &McpSession, items: Vec<Value>) -> Option<Value> {
+        if items.is_empty() {
+            return to_response_value(JsonRpcResponse::error(
+                Value::Null,
+                JsonRpcError {
+                    code: -32600,
+                    message: "Invalid Request: empty batch".to_string(),
+                    data: None,
+                },
+            ));
+        }
+
+        debug!("处理批量请求: {} 条", items.len());
+
+        let responses: Vec<Value> = futures::future::join_all(
+            items
+                .into_iter()
+                .map(|item| self.handle_batch_entry(session, item)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if responses.is_empty() {
+            None
+        } else {
+            Some(Value::Array(responses))
+        }
+    }
+
+    async fn handle_batch_entry(&self, session: &McpSession, item: Value) -> Option<Value> {
+        match serde_json::from_value::<JsonRpcRequest>(item) {
+            Ok(request) => to_response_value(self.handle_request(session, request).await?),
+            Err(e) => to_response_value(JsonRpcResponse::error(
+                Value::Null,
+                JsonRpcError {
+                    code: -32600,
+                    message: "Invalid Request".to_string(),
+                    data: Some(Value::String(e.to_string())),
+                },
+            )),
+        }
+    }
+
+    /// 处理通知消息（不产生响应）
+    fn handle_notification(&self, session: &McpSession, method: &str, params: &Value) {
+        match method {
+            "notifications/initialized" => {
+                session.mark_ready();
+                info!("MCP 会话握手完成");
+            }
+            "notifications/cancelled" => {
+                let request_id = params.get("requestId").unwrap_or(&Value::Null);
+                let reason = params.get("reason").and_then(|v| v.as_str());
+                // 请求可能已经完成，此时忽略取消通知
+                if session.cancel(request_id) {
+                    info!("已取消请求 {} (原因: {:?})", request_id, reason);
+                } else {
+                    debug!("取消通知对应的请求不存在或已完成: {}", request_id);
+                }
+            }
+            _ => debug!("忽略通知: {}", method),
+        }
+    }
+
+    /// 处理 `initialize` 请求：协商协议版本并返回服务器能力
+    fn handle_initialize(
+        &self,
+        session: &McpSession,
+        params: &Value,
+    ) -> Result<Value, JsonRpcError> {
+        if session.is_initialized() {
+            return Err(JsonRpcError {
+                code: -32600,
+                message: "Session already initialized".to_string(),
+                data: None,
+            });
+        }
+
+        let requested = params
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| JsonRpcError {
+                code: -32602,
+                message: "Missing or invalid 'protocolVersion' parameter".to_string(),
+                data: None,
+            })?;
+
+        // 客户端请求的版本受支持时直接采用，否则回退到服务器首选版本，由客户端决定是否断开
+        let protocol_version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+            requested
+        } else {
+            LATEST_PROTOCOL_VERSION
+        };
+
+        let client_info = params.get("clientInfo").cloned();
+        info!(
+            "MCP 初始化: 客户端 {:?} 请求协议 {}，协商结果 {}",
+            client_info, requested, protocol_version
+        );
+
+        session.begin(protocol_version.to_string(), client_info);
+
+        Ok(json!({
+            "protocolVersion": protocol_version,
+            "capabilities": {
+                "tools": { "listChanged": false }
             },
-            Err(err) => JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: None,
-                error: Some(err),
-                id: request.id,
+            "serverInfo": {
+                "name": SERVER_NAME,
+                "version": env!("CARGO_PKG_VERSION")
             },
-        }
+            "instructions": "Query Ethereum balances and token prices, and simulate Uniswap swaps. No transaction is ever broadcast."
+        }))
     }
 
     async fn handle_tools_list(&self) -> Result<Value, JsonRpcError> {
         let tools = self.get_tool_definitions().await;
-        serde_json::to_value(&tools).map_err(|e| JsonRpcError {
-            code: -32603,
-            message: format!("Internal error: {}", e),
-            data: None,
-        })
+        serde_json::to_value(&tools)
+            .map(|tools| json!({ "tools": tools }))
+            .map_err(|e| JsonRpcError {
+                code: -32603,
+                message: format!("Internal error: {}", e),
+                data: None,
+            })
     }
 
-    async fn handle_tool_call(&self, params: &Value) -> Result<Value, JsonRpcError> {
+    /// 执行 `tools/call`
+    ///
+    /// 工具在独立任务中运行并登记到会话，以便 `notifications/cancelled` 中止；
+    /// 请求被取消时返回 `Ok(None)`。
+    async fn handle_tool_call(
+        &self,
+        session: &McpSession,
+        id: &Value,
+        params: &Value,
+    ) -> Result<Option<Value>, JsonRpcError> {
         let tool_name =
             params
                 .get("name")
@@ -212,91 +412,120 @@ impl McpServer {
 
         info!("处理工具调用: {} 带参数: {:?}", tool_name, arguments);
 
-        match tool_name {
-            "get_balance" => {
-                let request: BalanceRequest =
-                    serde_json::from_value(arguments.clone()).map_err(|e| JsonRpcError {
-                        code: -32602,
-                        message: format!("Invalid arguments: {}", e),
-                        data: None,
-                    })?;
-
-                let balance_tool = self.balance_tool.read().await;
-                let tool = balance_tool.as_ref().ok_or_else(|| JsonRpcError {
-                    code: -32603,
-                    message: "Balance tool not initialized".to_string(),
-                    data: None,
-                })?;
+        let tool = self
+            .tools
+            .read()
+            .await
+            .get(tool_name)
+            .ok_or_else(|| JsonRpcError {
+                code: -32602,
+                message: format!("Unknown tool: {}", tool_name),
+                data: None,
+            })?;
 
-                match tool.get_balance(request).await {
-                    Ok(response) => Ok(serde_json::to_value(&response).unwrap()),
-                    Err(e) => Err(JsonRpcError {
-                        code: -32603,
-                        message: format!("Balance query failed: {}", e),
-                        data: None,
-                    }),
-                }
-            }
-            "get_token_price" => {
-                let request: PriceRequest =
-                    serde_json::from_value(arguments.clone()).map_err(|e| JsonRpcError {
-                        code: -32602,
-                        message: format!("Invalid arguments: {}", e),
-                        data: None,
-                    })?;
-
-                let price_tool = self.price_tool.read().await;
-                let tool = price_tool.as_ref().ok_or_else(|| JsonRpcError {
-                    code: -32603,
-                    message: "Price tool not initialized".to_string(),
-                    data: None,
-                })?;
+        let context = progress_context(session, params);
+        let arguments = arguments.clone();
+        let task = session
+            .track(id, || {
+                tokio::spawn(async move { tool.call(arguments, context).await })
+            })
+            .ok_or_else(|| JsonRpcError {
+                code: -32600,
+                message: format!("Invalid Request: request id {} is already in flight", id),
+                data: None,
+            })?;
+        let guard = InFlightGuard {
+            session,
+            id,
+            handle: task.abort_handle(),
+        };
 
-                match tool.get_price(request).await {
-                    Ok(response) => Ok(serde_json::to_value(&response).unwrap()),
-                    Err(e) => Err(JsonRpcError {
-                        code: -32603,
-                        message: format!("Price query failed: {}", e),
-                        data: None,
-                    }),
-                }
-            }
-            "swap_tokens" => {
-                let request: SwapRequest =
-                    serde_json::from_value(arguments.clone()).map_err(|e| JsonRpcError {
-                        code: -32602,
-                        message: format!("Invalid arguments: {}", e),
-                        data: None,
-                    })?;
-
-                let swap_tool = self.swap_tool.read().await;
-                let tool = swap_tool.as_ref().ok_or_else(|| JsonRpcError {
+        let outcome = task.await;
+        drop(guard);
+
+        let result = match outcome {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => return Ok(None),
+            Err(e) => {
+                return Err(JsonRpcError {
                     code: -32603,
-                    message: "Swap tool not initialized".to_string(),
+                    message: format!("Internal error: tool panicked: {}", e),
                     data: None,
-                })?;
-
-                match tool.simulate_swap(request).await {
-                    Ok(response) => Ok(serde_json::to_value(&response).unwrap()),
-                    Err(e) => Err(JsonRpcError {
-                        code: -32603,
-                        message: format!("Swap simulation failed: {}", e),
-                        data: None,
-                    }),
-                }
+                })
             }
-            _ => Err(JsonRpcError {
-                code: -32601,
-                message: format!("Tool not found: {}", tool_name),
+        };
+
+        let result = result.map_err(|e| match e {
+            ToolError::InvalidArguments(_) => JsonRpcError {
+                code: -32602,
+                message: e.to_string(),
                 data: None,
-            }),
+            },
+            ToolError::Internal(_) => JsonRpcError {
+                code: -32603,
+                message: e.to_string(),
+                data: None,
+            },
+        })?;
+
+        serde_json::to_value(&result)
+            .map(Some)
+            .map_err(|e| JsonRpcError {
+                code: -32603,
+                message: format!("Internal error: {}", e),
+                data: None,
+            })
+    }
+}
+
+/// 根据 `params._meta.progressToken` 构造工具上下文，进度以 `notifications/progress` 推送给会话
+fn progress_context(session: &McpSession, params: &Value) -> ToolContext {
+    let Some(token) = params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .cloned()
+    else {
+        return ToolContext::default();
+    };
+
+    let notifier = session.notifier();
+    ToolContext::with_progress(move |progress, total, message| {
+        let mut params = json!({
+            "progressToken": token,
+            "progress": progress,
+            "message": message
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
         }
+        notifier.notify("notifications/progress", params);
+    })
+}
+
+/// 正在执行的工具任务登记（由 [`McpSession::track`] 创建）；离开作用域时（包括请求 future 被丢弃）中止任务并移除登记
+struct InFlightGuard<'a> {
+    session: &'a McpSession,
+    id: &'a Value,
+    handle: AbortHandle,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.handle.abort();
+        self.session.untrack(self.id);
     }
 }
 
+fn to_response_value(response: JsonRpcResponse) -> Option<Value> {
+    serde_json::to_value(response).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ChainConfig;
+    use crate::error::EthereumError;
+    use crate::server::session::SessionPhase;
 
     #[test]
     fn test_jsonrpc_request_serialization() {
@@ -304,7 +533,7 @@ mod tests {
             jsonrpc: "2.0".to_string(),
             method: "tools/call".to_string(),
             params: json!({}),
-            id: json!(1),
+            id: Some(json!(1)),
         };
 
         let json_str = serde_json::to_string(&request).unwrap();
@@ -321,4 +550,476 @@ mod tests {
         // Just verify the server can be created
         assert_eq!(server.config.rpc_url, "https://eth.llamarpc.com");
     }
+
+    fn request(method: &str, params: Value, id: Option<Value>) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id,
+        }
+    }
+
+    fn test_server() -> McpServer {
+        McpServer::new(Config::from_url("https://eth.llamarpc.com".to_string()))
+    }
+
+    #[test]
+    fn test_notification_deserialization() {
+        let request: JsonRpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .unwrap();
+        assert!(request.is_notification());
+        assert!(request.params.is_null());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_handshake() {
+        let server = test_server();
+        let session = McpSession::new();
+
+        let response = server
+            .handle_request(
+                &session,
+                request(
+                    "initialize",
+                    json!({
+                        "protocolVersion": "2025-03-26",
+                        "capabilities": {},
+                        "clientInfo": {"name": "test", "version": "1.0"}
+                    }),
+                    Some(json!(1)),
+                ),
+            )
+            .await
+            .unwrap();
+
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], "2025-03-26");
+        assert_eq!(result["serverInfo"]["name"], SERVER_NAME);
+        assert!(result["capabilities"]["tools"].is_object());
+        assert_eq!(session.phase(), SessionPhase::Initializing);
+
+        let ack = server
+            .handle_request(
+                &session,
+                request("notifications/initialized", Value::Null, None),
+            )
+            .await;
+        assert!(ack.is_none());
+        assert_eq!(session.phase(), SessionPhase::Ready);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_unsupported_version_falls_back() {
+        let server = test_server();
+        let session = McpSession::new();
+
+        let response = server
+            .handle_request(
+                &session,
+                request(
+                    "initialize",
+                    json!({"protocolVersion": "1999-01-01"}),
+                    Some(json!(1)),
+                ),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.result.unwrap()["protocolVersion"],
+            LATEST_PROTOCOL_VERSION
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_rejected_before_initialize() {
+        let server = test_server();
+        let session = McpSession::new();
+
+        let response = server
+            .handle_request(&session, request("tools/list", json!({}), Some(json!(1))))
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, -32002);
+
+        // ping 在握手前也允许
+        let response = server
+            .handle_request(&session, request("ping", json!({}), Some(json!(2))))
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+    }
+
+    /// 只响应 `eth_chainId` 的模拟 RPC 端点
+    async fn mock_rpc(chain_id: u64) -> mockito::ServerGuard {
+        let mut rpc = mockito::Server::new_async().await;
+        rpc.mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"method":"eth_chainId"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"0x{:x}"}}"#,
+                chain_id
+            ))
+            .create_async()
+            .await;
+        rpc
+    }
+
+    #[tokio::test]
+    async fn test_initialize_checks_chain_id() {
+        // RPC 端点连的是 Base，配置却是主网
+        let rpc = mock_rpc(8453).await;
+        let server = McpServer::new(Config::from_url(rpc.url()));
+        let err = server.initialize().await.unwrap_err();
+        assert!(matches!(err, EthereumError::ConfigError(_)));
+        assert!(err.to_string().contains("8453"));
+
+        let mut config = Config::from_url(rpc.url());
+        config.chain_id = 8453;
+        McpServer::new(config).initialize().await.unwrap();
+
+        let mut config = Config::from_url(rpc.url());
+        config.chain_id = 56;
+        assert!(McpServer::new(config).initialize().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_serves_additional_chains() {
+        let mainnet = mock_rpc(1).await;
+        let base = mock_rpc(8453).await;
+        let mut config = Config::from_url(mainnet.url());
+        config.additional_chains.push(ChainConfig {
+            chain_id: 8453,
+            rpc_url: base.url(),
+            fallback_rpc_urls: Vec::new(),
+            archive_rpc_urls: Vec::new(),
+        });
+        let server = McpServer::new(config.clone());
+        server.initialize().await.unwrap();
+
+        let chains = server.chain_clients().await.unwrap();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(server.rpc_client().await.unwrap().network().chain_id, 1);
+        assert_eq!(chains.get(8453).unwrap().rpc_url(), base.url());
+
+        // 额外链的端点同样校验链 ID
+        config.additional_chains[0].chain_id = 10;
+        let err = McpServer::new(config).initialize().await.unwrap_err();
+        assert!(err.to_string().contains("8453"));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_checks_archive_chain_id() {
+        let mainnet = mock_rpc(1).await;
+        let base = mock_rpc(8453).await;
+        // 归档端点连的是 Base，历史查询会读到其他链的状态
+        let mut config = Config::from_url(mainnet.url());
+        config.archive_rpc_urls = vec![base.url()];
+        let err = McpServer::new(config.clone())
+            .initialize()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::ConfigError(_)));
+        assert!(err.to_string().contains(&base.url()));
+
+        config.archive_rpc_urls = vec![mainnet.url()];
+        McpServer::new(config).initialize().await.unwrap();
+    }
+
+    async fn initialized_server() -> (McpServer, McpSession, mockito::ServerGuard) {
+        let rpc = mock_rpc(1).await;
+        let server = McpServer::new(Config::from_url(rpc.url()));
+        server.initialize().await.unwrap();
+        let session = McpSession::new();
+        server
+            .handle_request(
+                &session,
+                request(
+                    "initialize",
+                    json!({"protocolVersion": LATEST_PROTOCOL_VERSION}),
+                    Some(json!(0)),
+                ),
+            )
+            .await;
+        (server, session, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_tool_failure_reported_as_is_error() {
+        let (server, session, _rpc) = initialized_server().await;
+
+        let response = server
+            .handle_request(
+                &session,
+                request(
+                    "tools/call",
+                    json!({"name": "get_balance", "arguments": {"address": "invalid"}}),
+                    Some(json!(1)),
+                ),
+            )
+            .await
+            .unwrap();
+
+        assert!(response.error.is_none());
+        let result = response.result.unwrap();
+        assert_eq!(result["isError"], true);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("invalid"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_is_protocol_error() {
+        let (server, session, _rpc) = initialized_server().await;
+
+        let response = server
+            .handle_request(
+                &session,
+                request(
+                    "tools/call",
+                    json!({"name": "nope", "arguments": {}}),
+                    Some(json!(1)),
+                ),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.error.unwrap().code, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_register_custom_tool() {
+        struct PingTool;
+
+        #[async_trait::async_trait]
+        impl Tool for PingTool {
+            fn name(&self) -> &str {
+                "custom_ping"
+            }
+
+            fn description(&self) -> &str {
+                "Replies with pong"
+            }
+
+            fn input_schema(&self) -> Value {
+                json!({"type": "object"})
+            }
+
+            async fn call(
+                &self,
+                _arguments: Value,
+                _context: ToolContext,
+            ) -> std::result::Result<crate::tools::CallToolResult, ToolError> {
+                crate::tools::CallToolResult::json(&json!({"reply": "pong"}), false)
+            }
+        }
+
+        let (server, session, _rpc) = initialized_server().await;
+        server.register_tool(Arc::new(PingTool)).await;
+
+        let names: Vec<String> = server
+            .get_tool_definitions()
+            .await
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "get_balance",
+                "get_token_price",
+                "swap_tokens",
+                "list_networks",
+                "custom_ping"
+            ]
+        );
+
+        let response = server
+            .handle_request(
+                &session,
+                request(
+                    "tools/call",
+                    json!({"name": "custom_ping", "arguments": {}}),
+                    Some(json!(1)),
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.result.unwrap()["structuredContent"]["reply"],
+            "pong"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_request() {
+        let (server, session, _rpc) = initialized_server().await;
+
+        let response = server
+            .handle_value(
+                &session,
+                json!([
+                    {"jsonrpc": "2.0", "method": "ping", "id": 1},
+                    {"jsonrpc": "2.0", "method": "notifications/initialized"},
+                    {"jsonrpc": "2.0", "method": "tools/list", "id": 2},
+                    {"foo": "bar"}
+                ]),
+            )
+            .await
+            .unwrap();
+
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[2]["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_batch_of_notifications_has_no_response() {
+        let (server, session, _rpc) = initialized_server().await;
+
+        let response = server
+            .handle_value(
+                &session,
+                json!([{"jsonrpc": "2.0", "method": "notifications/initialized"}]),
+            )
+            .await;
+        assert!(response.is_none());
+
+        let response = server.handle_value(&session, json!([])).await.unwrap();
+        assert_eq!(response["error"]["code"], -32600);
+    }
+
+    /// 上报一次进度后一直等待，直到被取消
+    struct SlowTool;
+
+    #[async_trait::async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Never finishes"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(
+            &self,
+            _arguments: Value,
+            context: ToolContext,
+        ) -> std::result::Result<crate::tools::CallToolResult, ToolError> {
+            context.report_progress(1.0, Some(2.0), "waiting");
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_in_flight_tool_call() {
+        let (server, session, _rpc) = initialized_server().await;
+        server.register_tool(Arc::new(SlowTool)).await;
+        let mut notifications = session.subscribe();
+
+        let call = server.handle_request(
+            &session,
+            request(
+                "tools/call",
+                json!({
+                    "name": "slow",
+                    "arguments": {},
+                    "_meta": {"progressToken": "p1"}
+                }),
+                Some(json!(7)),
+            ),
+        );
+        let cancel = async {
+            let progress = notifications.recv().await.unwrap();
+            assert_eq!(progress["method"], "notifications/progress");
+            assert_eq!(progress["params"]["progressToken"], "p1");
+            assert_eq!(progress["params"]["total"], 2.0);
+            assert_eq!(session.in_flight_count(), 1);
+
+            server
+                .handle_request(
+                    &session,
+                    request(
+                        "notifications/cancelled",
+                        json!({"requestId": 7, "reason": "user aborted"}),
+                        None,
+                    ),
+                )
+                .await
+        };
+
+        let (response, cancel_response) = tokio::join!(call, cancel);
+        assert!(response.is_none());
+        assert!(cancel_response.is_none());
+        assert_eq!(session.in_flight_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_in_flight_id_rejected() {
+        let (server, session, _rpc) = initialized_server().await;
+        server.register_tool(Arc::new(SlowTool)).await;
+        let mut notifications = session.subscribe();
+
+        let slow_call = |id| {
+            request(
+                "tools/call",
+                json!({
+                    "name": "slow",
+                    "arguments": {},
+                    "_meta": {"progressToken": "p1"}
+                }),
+                Some(id),
+            )
+        };
+
+        let first = server.handle_request(&session, slow_call(json!(7)));
+        let duplicate_then_cancel = async {
+            notifications.recv().await.unwrap();
+
+            let duplicate = server
+                .handle_request(&session, slow_call(json!(7)))
+                .await
+                .unwrap();
+            assert_eq!(duplicate.error.unwrap().code, -32600);
+            // 被拒绝的请求不影响第一个请求的登记
+            assert_eq!(session.in_flight_count(), 1);
+
+            server
+                .handle_request(
+                    &session,
+                    request("notifications/cancelled", json!({"requestId": 7}), None),
+                )
+                .await
+        };
+
+        let (response, _) = tokio::join!(first, duplicate_then_cancel);
+        assert!(response.is_none());
+        assert_eq!(session.in_flight_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_request_is_ignored() {
+        let (server, session, _rpc) = initialized_server().await;
+
+        let response = server
+            .handle_request(
+                &session,
+                request("notifications/cancelled", json!({"requestId": 99}), None),
+            )
+            .await;
+        assert!(response.is_none());
+        assert!(!session.cancel(&json!(99)));
+    }
 }
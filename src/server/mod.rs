@@ -1,3 +1,8 @@
+pub mod http;
 pub mod mcp;
+pub mod session;
+pub mod transport;
 
 pub use mcp::{JsonRpcRequest, JsonRpcResponse, McpServer};
+pub use session::{McpSession, SessionPhase};
+pub use transport::Transport;
@@ -0,0 +1,199 @@
+use serde_json::{json, Value};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use tokio::sync::broadcast;
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// 服务器主动推送消息的缓冲区大小
+const OUTBOUND_CAPACITY: usize = 64;
+
+/// MCP 会话生命周期阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionPhase {
+    /// 尚未收到 `initialize` 请求
+    Uninitialized,
+    /// 已响应 `initialize`，等待客户端发送 `notifications/initialized`
+    Initializing,
+    /// 握手完成，可以正常通信
+    Ready,
+}
+
+#[derive(Debug)]
+struct SessionState {
+    phase: SessionPhase,
+    protocol_version: Option<String>,
+    client_info: Option<Value>,
+}
+
+/// 向会话推送通知的句柄，可在派生的任务中持有
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    outbound: broadcast::Sender<Value>,
+}
+
+impl Notifier {
+    /// 推送一条 JSON-RPC 通知，没有订阅者时直接丢弃
+    pub fn notify(&self, method: &str, params: Value) {
+        let _ = self.outbound.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }));
+    }
+}
+
+/// 单个 MCP 连接的会话状态
+///
+/// 每个传输连接（TCP 连接、stdio 进程、HTTP 会话等）持有一个独立的会话，
+/// 用于记录握手阶段、协商出的协议版本，并承载服务器主动推送的通知。
+#[derive(Debug)]
+pub struct McpSession {
+    state: RwLock<SessionState>,
+    outbound: broadcast::Sender<Value>,
+    /// 正在执行的请求（按请求 id 索引），用于响应 `notifications/cancelled`
+    in_flight: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl McpSession {
+    pub fn new() -> Self {
+        let (outbound, _) = broadcast::channel(OUTBOUND_CAPACITY);
+        McpSession {
+            state: RwLock::new(SessionState {
+                phase: SessionPhase::Uninitialized,
+                protocol_version: None,
+                client_info: None,
+            }),
+            outbound,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 订阅服务器推送给该会话的消息
+    pub fn subscribe(&self) -> broadcast::Receiver<Value> {
+        self.outbound.subscribe()
+    }
+
+    /// 向该会话推送一条 JSON-RPC 通知，没有订阅者时直接丢弃
+    pub fn notify(&self, method: &str, params: Value) {
+        self.notifier().notify(method, params);
+    }
+
+    /// 是否有订阅者（如打开的 SSE 流）
+    pub fn has_subscribers(&self) -> bool {
+        self.outbound.receiver_count() > 0
+    }
+
+    /// 获取可跨任务持有的通知句柄
+    pub fn notifier(&self) -> Notifier {
+        Notifier {
+            outbound: self.outbound.clone(),
+        }
+    }
+
+    /// 启动并登记一个请求的任务
+    ///
+    /// 同一 id 的请求仍在执行时不启动任务并返回 `None`，避免覆盖前一个请求的登记
+    /// （否则取消通知只能中止后一个请求，前一个请求结束时还会移除后一个请求的登记）。
+    pub(crate) fn track<T>(
+        &self,
+        request_id: &Value,
+        spawn: impl FnOnce() -> JoinHandle<T>,
+    ) -> Option<JoinHandle<T>> {
+        match self
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(request_id.to_string())
+        {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                let task = spawn();
+                entry.insert(task.abort_handle());
+                Some(task)
+            }
+        }
+    }
+
+    /// 请求结束后移除登记
+    pub(crate) fn untrack(&self, request_id: &Value) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request_id.to_string());
+    }
+
+    /// 取消一个正在执行的请求，请求不存在（已完成或 id 未知）时返回 false
+    pub fn cancel(&self, request_id: &Value) -> bool {
+        match self
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request_id.to_string())
+        {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 正在执行的请求数量
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// 当前生命周期阶段
+    pub fn phase(&self) -> SessionPhase {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).phase
+    }
+
+    /// 是否已完成 `initialize` 请求（工具调用的前置条件）
+    pub fn is_initialized(&self) -> bool {
+        self.phase() != SessionPhase::Uninitialized
+    }
+
+    /// 协商出的协议版本
+    pub fn protocol_version(&self) -> Option<String> {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .protocol_version
+            .clone()
+    }
+
+    /// 客户端在 `initialize` 中上报的 clientInfo
+    pub fn client_info(&self) -> Option<Value> {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .client_info
+            .clone()
+    }
+
+    /// 记录 `initialize` 的协商结果
+    pub(crate) fn begin(&self, protocol_version: String, client_info: Option<Value>) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.phase = SessionPhase::Initializing;
+        state.protocol_version = Some(protocol_version);
+        state.client_info = client_info;
+    }
+
+    /// 收到 `notifications/initialized` 后进入就绪状态
+    pub(crate) fn mark_ready(&self) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if state.phase == SessionPhase::Initializing {
+            state.phase = SessionPhase::Ready;
+        }
+    }
+}
+
+impl Default for McpSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
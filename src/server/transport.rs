@@ -0,0 +1,551 @@
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, Semaphore};
+use tracing::{debug, error, info, warn};
+
+use crate::error::EthereumError;
+use crate::server::mcp::McpServer;
+use crate::server::session::McpSession;
+
+/// 默认 TCP 监听地址
+pub const DEFAULT_TCP_ADDR: &str = "127.0.0.1:8080";
+
+/// 每个连接默认允许同时执行的请求数
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+
+/// 连接上进行中的请求已达上限时返回的 JSON-RPC 错误码
+pub const SERVER_OVERLOADED: i32 = -32000;
+
+/// MCP 传输方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// 以换行分隔的 JSON-RPC over TCP
+    Tcp,
+    /// 以换行分隔的 JSON-RPC over stdin/stdout（作为子进程运行）
+    Stdio,
+    /// MCP Streamable HTTP（POST + SSE）
+    Http,
+}
+
+impl FromStr for Transport {
+    type Err = EthereumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tcp" => Ok(Transport::Tcp),
+            "stdio" => Ok(Transport::Stdio),
+            "http" => Ok(Transport::Http),
+            other => Err(EthereumError::ConfigError(format!(
+                "不支持的传输方式: {}（可选: tcp, stdio, http）",
+                other
+            ))),
+        }
+    }
+}
+
+/// 在 TCP 端口上监听，每个连接使用独立的会话
+pub async fn serve_tcp(
+    addr: SocketAddr,
+    mcp_server: Arc<McpServer>,
+    max_in_flight: usize,
+) -> eyre::Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+
+    info!("MCP 服务器监听 tcp://{}", addr);
+
+    loop {
+        let (socket, peer_addr) = listener.accept().await?;
+        let mcp_server = Arc::clone(&mcp_server);
+
+        tokio::spawn(async move {
+            let (reader, writer) = socket.into_split();
+            if let Err(e) = serve_lines(reader, writer, mcp_server, max_in_flight).await {
+                error!("Error handling connection from {}: {}", peer_addr, e);
+            }
+        });
+    }
+}
+
+/// 通过 stdin/stdout 提供服务，读到 EOF 时返回
+///
+/// stdout 专用于协议消息，日志必须输出到 stderr。
+pub async fn serve_stdio(mcp_server: Arc<McpServer>, max_in_flight: usize) -> eyre::Result<()> {
+    info!("MCP 服务器通过 stdio 提供服务");
+
+    serve_lines(
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+        mcp_server,
+        max_in_flight,
+    )
+    .await?;
+
+    info!("stdin 已关闭，MCP 服务器退出");
+    Ok(())
+}
+
+/// 处理一条以换行分隔的 JSON-RPC 消息流，直到读端 EOF
+///
+/// 同一连接上的请求并发执行，最多 `max_in_flight` 个，超出时返回 `-32000` 错误。
+/// 响应和服务器推送的通知统一由写端按完成顺序写出（客户端按 id 匹配响应）。
+pub async fn serve_lines<R, W>(
+    reader: R,
+    writer: W,
+    mcp_server: Arc<McpServer>,
+    max_in_flight: usize,
+) -> eyre::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    // 每个连接拥有独立的 MCP 会话（握手状态、协议版本、进行中的请求）
+    let session = Arc::new(McpSession::new());
+    let notifications = session.subscribe();
+    let (outgoing, incoming) = mpsc::unbounded_channel();
+
+    tokio::try_join!(
+        read_messages(reader, mcp_server, session, outgoing, max_in_flight),
+        write_messages(writer, incoming, notifications),
+    )?;
+
+    Ok(())
+}
+
+/// 读取并分发消息；返回时释放发送端，写端在所有进行中的请求完成后结束
+async fn read_messages<R>(
+    reader: R,
+    mcp_server: Arc<McpServer>,
+    session: Arc<McpSession>,
+    outgoing: mpsc::UnboundedSender<String>,
+    max_in_flight: usize,
+) -> eyre::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf_reader = BufReader::new(reader);
+    let mut line = String::new();
+    let max_in_flight = max_in_flight.max(1);
+    let permits = Arc::new(Semaphore::new(max_in_flight));
+
+    while buf_reader.read_line(&mut line).await? > 0 {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            line.clear();
+            continue;
+        }
+
+        // 解析 JSON-RPC 消息（单个请求或批量请求）
+        let message = match serde_json::from_str::<Value>(trimmed) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("无法解析 JSON-RPC 请求: {}", e);
+
+                let _ = outgoing.send(serde_json::to_string(&json!({
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": -32700,
+                        "message": "Parse error",
+                        "data": e.to_string()
+                    },
+                    "id": null
+                }))?);
+                line.clear();
+                continue;
+            }
+        };
+        debug!("收到消息: {}", trimmed);
+        line.clear();
+
+        // 通知和 initialize 会修改会话状态，按到达顺序就地处理，不占用并发配额
+        let needed = permits_needed(&message);
+        if needed == 0 {
+            if let Some(response) = mcp_server.handle_value(&session, message).await {
+                let _ = outgoing.send(response.to_string());
+            }
+            continue;
+        }
+
+        // 超出并发上限的请求直接拒绝，读端不阻塞，取消通知仍能及时处理；
+        // 批量请求中的各项并发执行，必须一次取得全部配额
+        let permit = u32::try_from(needed)
+            .ok()
+            .and_then(|needed| Arc::clone(&permits).try_acquire_many_owned(needed).ok());
+        let Some(permit) = permit else {
+            warn!("连接上进行中的请求已达上限 {}，拒绝新请求", max_in_flight);
+            let _ = outgoing.send(overloaded_response(&message, max_in_flight).to_string());
+            continue;
+        };
+        let mcp_server = Arc::clone(&mcp_server);
+        let session = Arc::clone(&session);
+        let outgoing = outgoing.clone();
+        tokio::spawn(async move {
+            // 通知消息和被取消的请求不需要响应
+            if let Some(response) = mcp_server.handle_value(&session, message).await {
+                let _ = outgoing.send(response.to_string());
+            }
+            drop(permit);
+        });
+    }
+
+    Ok(())
+}
+
+/// 消息需要占用的并发配额：每个带 id 的请求一个，批量请求按其中的请求数计算；
+/// 通知、`initialize` 和只含通知的批量请求为 0，就地处理
+fn permits_needed(message: &Value) -> usize {
+    let is_request = |message: &Value| message.get("id").is_some();
+    match message {
+        Value::Array(items) => items.iter().filter(|item| is_request(item)).count(),
+        message if message.get("method").and_then(|m| m.as_str()) == Some("initialize") => 0,
+        message => usize::from(is_request(message)),
+    }
+}
+
+/// 并发已满时的错误响应；批量请求中的每个请求各返回一条错误
+fn overloaded_response(message: &Value, max_in_flight: usize) -> Value {
+    let error = |id: &Value| {
+        json!({
+            "jsonrpc": "2.0",
+            "error": {
+                "code": SERVER_OVERLOADED,
+                "message": format!("Too many requests in flight (limit {})", max_in_flight)
+            },
+            "id": id
+        })
+    };
+
+    match message {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .filter_map(|item| item.get("id"))
+                .map(error)
+                .collect(),
+        ),
+        message => error(message.get("id").unwrap_or(&Value::Null)),
+    }
+}
+
+/// 唯一的写端：写出响应和会话通知，直到所有发送端释放
+async fn write_messages<W>(
+    mut writer: W,
+    mut incoming: mpsc::UnboundedReceiver<String>,
+    mut notifications: broadcast::Receiver<Value>,
+) -> eyre::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut notifications_open = true;
+
+    loop {
+        tokio::select! {
+            // 优先写出通知，保证进度通知先于对应请求的响应
+            biased;
+            notification = notifications.recv(), if notifications_open => match notification {
+                Ok(notification) => write_line(&mut writer, &notification.to_string()).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("连接消费通知过慢，丢弃 {} 条通知", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => notifications_open = false,
+            },
+            message = incoming.recv() => match message {
+                Some(message) => write_line(&mut writer, &message).await?,
+                None => break,
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// 写出一条消息并换行
+async fn write_line<W: AsyncWrite + Unpin>(writer: &mut W, message: &str) -> std::io::Result<()> {
+    writer.write_all(message.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_transport_from_str() {
+        assert_eq!("stdio".parse::<Transport>().unwrap(), Transport::Stdio);
+        assert_eq!("TCP".parse::<Transport>().unwrap(), Transport::Tcp);
+        assert_eq!("http".parse::<Transport>().unwrap(), Transport::Http);
+        assert!("websocket".parse::<Transport>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_serve_lines_until_eof() {
+        let server = Arc::new(McpServer::new(Config::from_url(
+            "https://eth.llamarpc.com".to_string(),
+        )));
+
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","method":"initialize","params":{"protocolVersion":"2025-06-18"},"id":1}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            "not json\n",
+            r#"{"jsonrpc":"2.0","method":"ping","id":2}"#,
+            "\n",
+            r#"[{"jsonrpc":"2.0","method":"ping","id":3},{"jsonrpc":"2.0","method":"ping","id":4}]"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+
+        serve_lines(input.as_bytes(), &mut output, server, DEFAULT_MAX_IN_FLIGHT)
+            .await
+            .unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        // 通知不产生响应：initialize、解析错误、ping、批量请求各一条
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["error"]["code"], -32700);
+        // 并发执行的请求按完成顺序写出
+        assert!(responses[2..].iter().any(|r| r["id"] == 2));
+        assert!(responses[2..]
+            .iter()
+            .any(|r| r.as_array().is_some_and(|batch| batch.len() == 2)));
+    }
+
+    struct PendingTool;
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for PendingTool {
+        fn name(&self) -> &str {
+            "pending"
+        }
+
+        fn description(&self) -> &str {
+            "Never finishes"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn call(
+            &self,
+            _arguments: Value,
+            _context: crate::tools::ToolContext,
+        ) -> Result<crate::tools::CallToolResult, crate::tools::ToolError> {
+            std::future::pending().await
+        }
+    }
+
+    /// 在内存管道上启动一个连接，返回客户端读写端
+    async fn spawn_connection(
+        max_in_flight: usize,
+    ) -> (
+        tokio::io::Lines<BufReader<tokio::io::ReadHalf<tokio::io::DuplexStream>>>,
+        tokio::io::WriteHalf<tokio::io::DuplexStream>,
+        tokio::task::JoinHandle<eyre::Result<()>>,
+    ) {
+        let server = Arc::new(McpServer::new(Config::from_url(
+            "https://eth.llamarpc.com".to_string(),
+        )));
+        server.register_tool(Arc::new(PendingTool)).await;
+
+        let (client, server_end) = tokio::io::duplex(4096);
+        let (server_reader, server_writer) = tokio::io::split(server_end);
+        let handle = tokio::spawn(serve_lines(
+            server_reader,
+            server_writer,
+            server,
+            max_in_flight,
+        ));
+
+        let (client_reader, mut client_writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_reader).lines();
+        send(
+            &mut client_writer,
+            json!({"jsonrpc": "2.0", "method": "initialize", "params": {"protocolVersion": "2025-06-18"}, "id": 1}),
+        )
+        .await;
+        send(
+            &mut client_writer,
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        )
+        .await;
+        let response = next_message(&mut lines).await;
+        assert_eq!(response["id"], 1);
+
+        (lines, client_writer, handle)
+    }
+
+    async fn send<W: AsyncWrite + Unpin>(writer: &mut W, message: Value) {
+        write_line(writer, &message.to_string()).await.unwrap();
+    }
+
+    async fn next_message<R: tokio::io::AsyncBufRead + Unpin>(
+        lines: &mut tokio::io::Lines<R>,
+    ) -> Value {
+        let line = lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn call_pending(id: u64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {"name": "pending", "arguments": {}},
+            "id": id
+        })
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_does_not_block_connection() {
+        let (mut lines, mut writer, handle) = spawn_connection(DEFAULT_MAX_IN_FLIGHT).await;
+
+        send(&mut writer, call_pending(2)).await;
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "ping", "id": 3}),
+        )
+        .await;
+        assert_eq!(next_message(&mut lines).await["id"], 3);
+
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 2}}),
+        )
+        .await;
+        writer.shutdown().await.unwrap();
+
+        // 被取消的请求不产生响应，连接在 EOF 后结束
+        handle.await.unwrap().unwrap();
+        assert!(lines.next_line().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_batch_does_not_block_connection() {
+        let (mut lines, mut writer, handle) = spawn_connection(DEFAULT_MAX_IN_FLIGHT).await;
+
+        send(
+            &mut writer,
+            json!([call_pending(2), {"jsonrpc": "2.0", "method": "ping", "id": 3}]),
+        )
+        .await;
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "ping", "id": 4}),
+        )
+        .await;
+        assert_eq!(next_message(&mut lines).await["id"], 4);
+
+        // 批量请求中较慢的调用可以被取消，批量响应只包含其余请求
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 2}}),
+        )
+        .await;
+        let batch = next_message(&mut lines).await;
+        assert_eq!(batch.as_array().unwrap().len(), 1);
+        assert_eq!(batch[0]["id"], 3);
+
+        writer.shutdown().await.unwrap();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_permits_needed() {
+        let ping = json!({"jsonrpc": "2.0", "method": "ping", "id": 1});
+        let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert_eq!(permits_needed(&initialized), 0);
+        assert_eq!(permits_needed(&ping), 1);
+        assert_eq!(permits_needed(&json!([initialized.clone()])), 0);
+        assert_eq!(
+            permits_needed(&json!([initialized, ping.clone(), ping.clone()])),
+            2
+        );
+        assert_eq!(
+            overloaded_response(&json!([ping]), 1)[0]["error"]["code"],
+            SERVER_OVERLOADED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_limit_rejects_oversized_batch() {
+        let (mut lines, mut writer, handle) = spawn_connection(2).await;
+
+        send(
+            &mut writer,
+            json!([call_pending(2), call_pending(3), call_pending(4)]),
+        )
+        .await;
+        let batch = next_message(&mut lines).await;
+        let ids: Vec<&Value> = batch.as_array().unwrap().iter().map(|r| &r["id"]).collect();
+        assert_eq!(ids, [2, 3, 4]);
+        assert!(batch
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|r| r["error"]["code"] == SERVER_OVERLOADED));
+
+        // 被拒绝的批量请求不占用配额
+        send(
+            &mut writer,
+            json!([
+                {"jsonrpc": "2.0", "method": "ping", "id": 5},
+                {"jsonrpc": "2.0", "method": "ping", "id": 6}
+            ]),
+        )
+        .await;
+        let batch = next_message(&mut lines).await;
+        assert_eq!(batch.as_array().unwrap().len(), 2);
+        assert!(batch[0].get("error").is_none());
+
+        writer.shutdown().await.unwrap();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_limit_rejects_excess_requests() {
+        let (mut lines, mut writer, handle) = spawn_connection(1).await;
+
+        send(&mut writer, call_pending(2)).await;
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "ping", "id": 3}),
+        )
+        .await;
+
+        let response = next_message(&mut lines).await;
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], SERVER_OVERLOADED);
+
+        // 取消后配额释放，新的请求可以执行
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 2}}),
+        )
+        .await;
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        send(
+            &mut writer,
+            json!({"jsonrpc": "2.0", "method": "ping", "id": 4}),
+        )
+        .await;
+        let response = next_message(&mut lines).await;
+        assert_eq!(response["id"], 4);
+        assert!(response.get("error").is_none());
+
+        writer.shutdown().await.unwrap();
+        handle.await.unwrap().unwrap();
+    }
+}
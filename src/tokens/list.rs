@@ -0,0 +1,275 @@
+use alloy::primitives::Address;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::error::{EthereumError, Result};
+
+/// 内置代币列表（常见主网代币，以及各支持网络的原生代币、WETH 和稳定币），始终以最低优先级合并
+const BUILTIN_TOKEN_LIST: &str = include_str!("default.tokenlist.json");
+
+/// 下载远程代币列表的超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Uniswap 代币列表格式（<https://tokenlists.org>）中的一个代币
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenListEntry {
+    pub chain_id: u64,
+    pub address: Address,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub logo_uri: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// 经过校验的代币列表
+///
+/// 列表本身不符合格式时整体拒绝；单个代币不合格（地址校验和错误、字段越界等）时跳过该代币。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenList {
+    pub name: String,
+    /// 列表版本（`major.minor.patch`）
+    pub version: String,
+    /// 列表发布时间（ISO 8601）
+    pub timestamp: String,
+    pub tokens: Vec<TokenListEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawTokenList {
+    name: String,
+    timestamp: String,
+    version: RawVersion,
+    tokens: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct RawVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawToken {
+    chain_id: u64,
+    address: String,
+    symbol: String,
+    name: String,
+    decimals: u8,
+    #[serde(rename = "logoURI")]
+    logo_uri: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl TokenList {
+    /// 解析并校验代币列表 JSON
+    pub fn parse(json: &str) -> Result<Self> {
+        let raw: RawTokenList = serde_json::from_str(json)
+            .map_err(|e| EthereumError::TokenListError(format!("不是有效的代币列表: {}", e)))?;
+        if raw.name.trim().is_empty() {
+            return Err(EthereumError::TokenListError(
+                "代币列表缺少名称".to_string(),
+            ));
+        }
+
+        let mut tokens = Vec::with_capacity(raw.tokens.len());
+        for (index, value) in raw.tokens.into_iter().enumerate() {
+            match serde_json::from_value::<RawToken>(value)
+                .map_err(|e| e.to_string())
+                .and_then(validate_token)
+            {
+                Ok(token) => tokens.push(token),
+                Err(e) => warn!("代币列表 {} 跳过第 {} 个代币: {}", raw.name, index, e),
+            }
+        }
+
+        Ok(TokenList {
+            name: raw.name,
+            version: format!(
+                "{}.{}.{}",
+                raw.version.major, raw.version.minor, raw.version.patch
+            ),
+            timestamp: raw.timestamp,
+            tokens,
+        })
+    }
+
+    /// 内置代币列表
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_TOKEN_LIST).expect("内置代币列表格式正确")
+    }
+
+    /// 从本地文件或 `http(s)://` URL 加载代币列表
+    pub async fn load(source: &str) -> Result<Self> {
+        let json = if source.starts_with("http://") || source.starts_with("https://") {
+            debug!("正在下载代币列表: {}", source);
+            let fetch = async {
+                reqwest::Client::builder()
+                    .timeout(FETCH_TIMEOUT)
+                    .build()?
+                    .get(source)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            };
+            fetch.await.map_err(|e| {
+                EthereumError::TokenListError(format!("无法下载代币列表 {}: {}", source, e))
+            })?
+        } else {
+            tokio::fs::read_to_string(source).await.map_err(|e| {
+                EthereumError::TokenListError(format!("无法读取代币列表 {}: {}", source, e))
+            })?
+        };
+
+        let list = Self::parse(&json)?;
+        info!(
+            "已加载代币列表 {} v{} ({} 个代币): {}",
+            list.name,
+            list.version,
+            list.tokens.len(),
+            source
+        );
+        Ok(list)
+    }
+
+    /// 按顺序加载多个代币列表，加载失败的列表记录警告后跳过
+    pub async fn load_all(sources: &[String]) -> Vec<Self> {
+        let mut lists = Vec::with_capacity(sources.len());
+        for source in sources {
+            match Self::load(source).await {
+                Ok(list) => lists.push(list),
+                Err(e) => warn!("跳过代币列表 {}: {}", source, e),
+            }
+        }
+        lists
+    }
+}
+
+/// 按代币列表 JSON Schema 的约束校验单个代币
+fn validate_token(raw: RawToken) -> std::result::Result<TokenListEntry, String> {
+    if raw.chain_id == 0 {
+        return Err("chainId 必须为正整数".to_string());
+    }
+    // 代币列表要求 EIP-55 校验和格式的地址
+    let address = Address::parse_checksummed(&raw.address, None)
+        .map_err(|_| format!("地址 {} 不是有效的 EIP-55 校验和地址", raw.address))?;
+    if raw.symbol.is_empty()
+        || raw.symbol.chars().count() > 20
+        || raw.symbol.chars().any(char::is_whitespace)
+    {
+        return Err(format!("无效的符号: {:?}", raw.symbol));
+    }
+    if raw.name.trim().is_empty() || raw.name.chars().count() > 40 {
+        return Err(format!("无效的名称: {:?}", raw.name));
+    }
+    if raw.tags.len() > 10 {
+        return Err(format!("{} 的标签超过 10 个", raw.symbol));
+    }
+
+    Ok(TokenListEntry {
+        chain_id: raw.chain_id,
+        address,
+        symbol: raw.symbol,
+        name: raw.name,
+        decimals: raw.decimals,
+        logo_uri: raw.logo_uri,
+        tags: raw.tags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_json(tokens: Value) -> String {
+        serde_json::json!({
+            "name": "Test List",
+            "timestamp": "2025-01-01T00:00:00.000Z",
+            "version": {"major": 2, "minor": 1, "patch": 0},
+            "tokens": tokens
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_builtin_list_is_valid() {
+        let list = TokenList::builtin();
+        assert_eq!(list.tokens.len(), 26);
+        assert_eq!(list.tokens.iter().filter(|t| t.chain_id == 1).count(), 9);
+    }
+
+    #[test]
+    fn test_invalid_tokens_are_skipped() {
+        let json = list_json(serde_json::json!([
+            {"chainId": 1, "address": "0x6982508145454Ce325dDbE47a25d4ec3d2311933", "symbol": "PEPE", "name": "Pepe", "decimals": 18, "logoURI": "https://example.com/pepe.png", "tags": ["meme"]},
+            // 校验和错误（全小写）
+            {"chainId": 1, "address": "0x6b175474e89094c44da98b954eedeac495271d0f", "symbol": "DAI", "name": "Dai", "decimals": 18},
+            // 小数位数越界
+            {"chainId": 1, "address": "0xdAC17F958D2ee523a2206206994597C13D831ec7", "symbol": "USDT", "name": "Tether", "decimals": 256},
+            // 符号包含空格
+            {"chainId": 1, "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "symbol": "US DC", "name": "USD Coin", "decimals": 6},
+            // 缺少字段
+            {"chainId": 1, "address": "0x514910771AF9Ca656af840dff83E8264EcF986CA", "decimals": 18}
+        ]));
+
+        let list = TokenList::parse(&json).unwrap();
+        assert_eq!(list.name, "Test List");
+        assert_eq!(list.version, "2.1.0");
+        assert_eq!(list.tokens.len(), 1);
+        assert_eq!(list.tokens[0].symbol, "PEPE");
+        assert_eq!(
+            list.tokens[0].logo_uri.as_deref(),
+            Some("https://example.com/pepe.png")
+        );
+        assert_eq!(list.tokens[0].tags, vec!["meme".to_string()]);
+    }
+
+    #[test]
+    fn test_malformed_list_is_rejected() {
+        assert!(matches!(
+            TokenList::parse(r#"{"name": "No tokens"}"#),
+            Err(EthereumError::TokenListError(_))
+        ));
+        assert!(TokenList::parse("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_from_url_and_file() {
+        let json = list_json(serde_json::json!([
+            {"chainId": 1, "address": "0x6982508145454Ce325dDbE47a25d4ec3d2311933", "symbol": "PEPE", "name": "Pepe", "decimals": 18}
+        ]));
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/tokens.json")
+            .with_header("content-type", "application/json")
+            .with_body(&json)
+            .create_async()
+            .await;
+        let from_url = TokenList::load(&format!("{}/tokens.json", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(from_url.tokens.len(), 1);
+
+        let path = std::env::temp_dir().join(format!("tokenlist-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &json).unwrap();
+        let from_file = TokenList::load(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(from_file, from_url);
+        std::fs::remove_file(path).unwrap();
+
+        // 无法加载的列表被跳过
+        let lists = TokenList::load_all(&[
+            "/nonexistent/tokens.json".to_string(),
+            format!("{}/tokens.json", server.url()),
+        ])
+        .await;
+        assert_eq!(lists.len(), 1);
+    }
+}
@@ -0,0 +1,353 @@
+pub mod list;
+pub mod store;
+
+use alloy::primitives::Address;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::error::Result;
+
+pub use list::{TokenList, TokenListEntry};
+pub use store::{TokenMetadata, TokenSource, TokenStore};
+
+/// 各链的代币符号 ↔ 地址映射和代币元数据
+///
+/// 由一个或多个代币列表按优先级合并而成（内置列表优先级最低），按链 ID 分开存放。
+/// 运行时从链上解析到的代币元数据也记录在这里；配置了 [`TokenStore`] 时写入磁盘，
+/// 重启后直接加载，不必重新查询。注册表在工具之间通过 `Arc` 共享。
+pub struct TokenRegistry {
+    chains: RwLock<HashMap<u64, Tokens>>,
+    store: Option<TokenStore>,
+}
+
+#[derive(Default)]
+struct Tokens {
+    symbol_to_address: HashMap<String, Address>,
+    address_to_symbol: HashMap<Address, String>,
+    metadata: HashMap<Address, TokenMetadata>,
+}
+
+impl TokenRegistry {
+    /// 创建一个只包含内置代币列表的代币注册表
+    pub fn new() -> Self {
+        Self::from_token_lists(&[])
+    }
+
+    /// 由代币列表构建代币注册表，列表中每条链的代币分别登记
+    ///
+    /// `lists` 按优先级从高到低排列，内置列表追加在最后。同一条链上的同一地址或
+    /// 同一符号出现在多个列表中时以优先级高的列表为准。
+    pub fn from_token_lists(lists: &[TokenList]) -> Self {
+        let builtin = TokenList::builtin();
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let mut chains: HashMap<u64, Tokens> = HashMap::new();
+
+        for list in lists.iter().chain(std::iter::once(&builtin)) {
+            let mut added = 0;
+            for entry in &list.tokens {
+                let tokens = chains.entry(entry.chain_id).or_default();
+                if tokens.metadata.contains_key(&entry.address) {
+                    continue;
+                }
+                tokens
+                    .symbol_to_address
+                    .entry(entry.symbol.to_uppercase())
+                    .or_insert(entry.address);
+                tokens
+                    .address_to_symbol
+                    .insert(entry.address, entry.symbol.clone());
+                tokens.metadata.insert(
+                    entry.address,
+                    TokenMetadata {
+                        chain_id: entry.chain_id,
+                        address: entry.address,
+                        symbol: entry.symbol.clone(),
+                        name: Some(entry.name.clone()),
+                        decimals: entry.decimals,
+                        logo_uri: entry.logo_uri.clone(),
+                        tags: entry.tags.clone(),
+                        first_seen: now,
+                        source: TokenSource::TokenList(list.name.clone()),
+                    },
+                );
+                added += 1;
+            }
+            debug!("代币列表 {} 贡献了 {} 个代币", list.name, added);
+        }
+
+        TokenRegistry {
+            chains: RwLock::new(chains),
+            store: None,
+        }
+    }
+
+    /// 加载 `store` 中已解析过的代币，之后新解析的代币写入 `store`
+    ///
+    /// 代币列表中已有的代币以列表为准。
+    pub fn with_store(self, store: TokenStore) -> Result<Self> {
+        let mut count = 0;
+        for token in store.load()? {
+            if self.insert_metadata(token) {
+                count += 1;
+            }
+        }
+
+        info!(
+            "从 {} 加载了 {} 个代币的元数据",
+            store.path().display(),
+            count
+        );
+        Ok(TokenRegistry {
+            store: Some(store),
+            ..self
+        })
+    }
+
+    /// 从符号获取链 `chain_id` 上的地址
+    pub fn symbol_to_address(&self, chain_id: u64, symbol: &str) -> Option<Address> {
+        self.read()
+            .get(&chain_id)?
+            .symbol_to_address
+            .get(&symbol.to_uppercase())
+            .copied()
+    }
+
+    /// 从地址获取符号
+    pub fn address_to_symbol(&self, chain_id: u64, address: Address) -> Option<String> {
+        self.read()
+            .get(&chain_id)?
+            .address_to_symbol
+            .get(&address)
+            .cloned()
+    }
+
+    /// 代币元数据（符号、名称、小数位数等），来自代币列表或运行时解析
+    pub fn metadata(&self, chain_id: u64, address: Address) -> Option<TokenMetadata> {
+        self.read().get(&chain_id)?.metadata.get(&address).cloned()
+    }
+
+    /// 在链 `chain_id` 上注册一个新代币
+    pub fn register(&self, chain_id: u64, symbol: String, address: Address) {
+        let mut chains = self.write();
+        let tokens = chains.entry(chain_id).or_default();
+        tokens.symbol_to_address.insert(symbol.clone(), address);
+        tokens.address_to_symbol.insert(address, symbol);
+    }
+
+    /// 记录运行时从代币合约读取到的元数据
+    ///
+    /// 首次出现的代币写入持久化存储（写入失败只记录警告）。
+    /// 链上读取的符号未经验证，不会用于按符号查找地址，只用于按地址显示符号。
+    pub fn record_resolved(
+        &self,
+        chain_id: u64,
+        address: Address,
+        symbol: String,
+        name: Option<String>,
+        decimals: u8,
+    ) {
+        if self.metadata(chain_id, address).is_some() {
+            return;
+        }
+
+        let token = TokenMetadata {
+            chain_id,
+            address,
+            symbol,
+            name,
+            decimals,
+            logo_uri: None,
+            tags: Vec::new(),
+            first_seen: chrono::Utc::now().timestamp().max(0) as u64,
+            source: TokenSource::OnChain,
+        };
+        // 并发解析同一代币时只有第一次插入会写入存储
+        if !self.insert_metadata(token.clone()) {
+            return;
+        }
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(&token) {
+                warn!("保存代币 {} 的元数据失败: {}", address, e);
+            }
+        }
+    }
+
+    /// 获取链 `chain_id` 上所有已注册的符号
+    pub fn symbols(&self, chain_id: u64) -> Vec<String> {
+        self.read()
+            .get(&chain_id)
+            .map(|tokens| tokens.symbol_to_address.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 插入元数据，已存在时返回 `false`
+    fn insert_metadata(&self, token: TokenMetadata) -> bool {
+        let mut chains = self.write();
+        let tokens = chains.entry(token.chain_id).or_default();
+        if tokens.metadata.contains_key(&token.address) {
+            return false;
+        }
+        tokens
+            .address_to_symbol
+            .entry(token.address)
+            .or_insert_with(|| token.symbol.clone());
+        tokens.metadata.insert(token.address, token);
+        true
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<u64, Tokens>> {
+        self.chains.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<u64, Tokens>> {
+        self.chains.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for TokenRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_registry_eth() {
+        let registry = TokenRegistry::new();
+        let eth_addr = registry.symbol_to_address(1, "ETH");
+        assert!(eth_addr.is_some());
+    }
+
+    #[test]
+    fn test_token_registry_usdt() {
+        let registry = TokenRegistry::new();
+        let usdt_addr = registry.symbol_to_address(1, "USDT");
+        assert!(usdt_addr.is_some());
+    }
+
+    #[test]
+    fn test_resolved_tokens_survive_restart() {
+        let dir = store::tests::temp_data_dir("registry");
+        let pepe: Address = "0x6982508145454Ce325dDbE47a25d4ec3d2311933"
+            .parse()
+            .unwrap();
+
+        let registry = TokenRegistry::new()
+            .with_store(TokenStore::open(&dir).unwrap())
+            .unwrap();
+        assert!(registry.metadata(1, pepe).is_none());
+        registry.record_resolved(1, pepe, "PEPE".to_string(), Some("Pepe".to_string()), 18);
+        registry.record_resolved(1, pepe, "PEPE".to_string(), Some("Pepe".to_string()), 18);
+        drop(registry);
+
+        let registry = TokenRegistry::new()
+            .with_store(TokenStore::open(&dir).unwrap())
+            .unwrap();
+        let metadata = registry.metadata(1, pepe).unwrap();
+        assert_eq!(metadata.symbol, "PEPE");
+        assert_eq!(metadata.decimals, 18);
+        assert_eq!(metadata.source, TokenSource::OnChain);
+        assert_eq!(
+            registry.address_to_symbol(1, pepe),
+            Some("PEPE".to_string())
+        );
+        // 链上读取的符号不参与按符号查找
+        assert_eq!(registry.symbol_to_address(1, "PEPE"), None);
+
+        // 记录只属于解析时所在的链
+        assert!(registry.metadata(10, pepe).is_none());
+
+        let lines = std::fs::read_to_string(dir.join(store::TOKEN_STORE_FILE)).unwrap();
+        assert_eq!(lines.lines().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_token_lists_merge_by_priority() {
+        let list = |name: &str, tokens: serde_json::Value| {
+            TokenList::parse(
+                &serde_json::json!({
+                    "name": name,
+                    "timestamp": "2025-01-01T00:00:00.000Z",
+                    "version": {"major": 1, "minor": 0, "patch": 0},
+                    "tokens": tokens
+                })
+                .to_string(),
+            )
+            .unwrap()
+        };
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let pepe = "0x6982508145454Ce325dDbE47a25d4ec3d2311933";
+        let primary = list(
+            "Primary",
+            serde_json::json!([
+                {"chainId": 1, "address": usdc, "symbol": "USDC", "name": "USDC (primary)", "decimals": 6, "logoURI": "https://example.com/usdc.png"},
+                {"chainId": 10, "address": pepe, "symbol": "PEPE", "name": "Pepe on Optimism", "decimals": 18}
+            ]),
+        );
+        let secondary = list(
+            "Secondary",
+            serde_json::json!([
+                {"chainId": 1, "address": usdc, "symbol": "USDC", "name": "USDC (secondary)", "decimals": 6},
+                // 低优先级列表不能抢占已有的符号
+                {"chainId": 1, "address": pepe, "symbol": "usdc", "name": "Fake USDC", "decimals": 18, "tags": ["meme"]}
+            ]),
+        );
+
+        let registry = TokenRegistry::from_token_lists(&[primary, secondary]);
+        let usdc: Address = usdc.parse().unwrap();
+        let pepe: Address = pepe.parse().unwrap();
+
+        let metadata = registry.metadata(1, usdc).unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("USDC (primary)"));
+        assert_eq!(
+            metadata.logo_uri.as_deref(),
+            Some("https://example.com/usdc.png")
+        );
+        assert_eq!(
+            metadata.source,
+            TokenSource::TokenList("Primary".to_string())
+        );
+        assert_eq!(registry.symbol_to_address(1, "USDC"), Some(usdc));
+
+        // PEPE 仍以地址可查，但符号 usdc 已被占用
+        let fake = registry.metadata(1, pepe).unwrap();
+        assert_eq!(fake.chain_id, 1);
+        assert_eq!(fake.tags, vec!["meme".to_string()]);
+        assert_eq!(
+            registry.address_to_symbol(1, pepe),
+            Some("usdc".to_string())
+        );
+
+        // 同一地址在其他链上是另一个代币
+        assert_eq!(registry.symbol_to_address(10, "PEPE"), Some(pepe));
+        assert_eq!(registry.metadata(10, pepe).unwrap().decimals, 18);
+        assert_eq!(
+            registry.symbol_to_address(10, "DAI"),
+            registry.symbol_to_address(42161, "DAI")
+        );
+        assert_ne!(registry.symbol_to_address(10, "USDC"), Some(usdc));
+
+        // 内置列表以最低优先级补充
+        assert_eq!(
+            registry
+                .metadata(1, registry.symbol_to_address(1, "DAI").unwrap())
+                .unwrap()
+                .decimals,
+            18
+        );
+    }
+
+    #[test]
+    fn test_reverse_lookup() {
+        let registry = TokenRegistry::new();
+        if let Some(usdt_addr) = registry.symbol_to_address(1, "USDT") {
+            let symbol = registry.address_to_symbol(1, usdt_addr);
+            assert_eq!(symbol, Some("USDT".to_string()));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::error::{EthereumError, Result};
+
+/// 数据目录下代币元数据文件的名称
+pub const TOKEN_STORE_FILE: &str = "tokens.jsonl";
+
+/// 代币元数据的来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSource {
+    /// 来自指定名称的代币列表
+    TokenList(String),
+    /// 运行时从代币合约的 `symbol()` / `name()` / `decimals()` 读取
+    OnChain,
+}
+
+/// 一个代币的元数据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub chain_id: u64,
+    pub address: Address,
+    pub symbol: String,
+    /// 部分代币没有实现 `name()`
+    pub name: Option<String>,
+    pub decimals: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// 代币列表中的标签
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 首次解析到该代币的时间（Unix 秒）
+    pub first_seen: u64,
+    pub source: TokenSource,
+}
+
+/// 持久化的代币元数据存储（JSON Lines，每行一个 [`TokenMetadata`]）
+///
+/// 只追加写入；同一链上的同一地址以最早的记录为准，
+/// 无法解析的行（如进程在写入中途退出留下的半行）在加载时跳过。
+#[derive(Debug)]
+pub struct TokenStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl TokenStore {
+    /// 打开 `data_dir` 下的代币存储，目录或文件不存在时创建
+    pub fn open(data_dir: impl AsRef<Path>) -> Result<Self> {
+        let data_dir = data_dir.as_ref();
+        fs::create_dir_all(data_dir).map_err(|e| {
+            EthereumError::StorageError(format!("无法创建数据目录 {}: {}", data_dir.display(), e))
+        })?;
+
+        let path = data_dir.join(TOKEN_STORE_FILE);
+        let io_error = |e: std::io::Error| {
+            EthereumError::StorageError(format!("无法打开 {}: {}", path.display(), e))
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(io_error)?;
+
+        // 上次写入中断留下的半行单独成行，不影响之后追加的记录
+        let content = fs::read(&path).map_err(io_error)?;
+        if content.last().is_some_and(|&byte| byte != b'\n') {
+            file.write_all(b"\n").map_err(io_error)?;
+        }
+
+        debug!("已打开代币存储: {}", path.display());
+        Ok(TokenStore {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取全部记录，按 (链 ID, 地址) 去重并保留最早的一条
+    pub fn load(&self) -> Result<Vec<TokenMetadata>> {
+        let content = fs::read_to_string(&self.path).map_err(|e| {
+            EthereumError::StorageError(format!("无法读取 {}: {}", self.path.display(), e))
+        })?;
+
+        let mut seen = HashSet::new();
+        let mut tokens = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TokenMetadata>(line) {
+                Ok(token) => {
+                    if seen.insert((token.chain_id, token.address)) {
+                        tokens.push(token);
+                    }
+                }
+                Err(e) => warn!(
+                    "跳过代币存储 {} 第 {} 行: {}",
+                    self.path.display(),
+                    index + 1,
+                    e
+                ),
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, token: &TokenMetadata) -> Result<()> {
+        let mut line = serde_json::to_string(token)
+            .map_err(|e| EthereumError::StorageError(format!("无法序列化代币元数据: {}", e)))?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        // 整行一次写入，避免并发追加时行内容交错
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| {
+                EthereumError::StorageError(format!("无法写入 {}: {}", self.path.display(), e))
+            })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 测试用的临时数据目录
+    pub(crate) fn temp_data_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ethereum-mcp-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    pub(crate) fn token(chain_id: u64, address: Address, symbol: &str) -> TokenMetadata {
+        TokenMetadata {
+            chain_id,
+            address,
+            symbol: symbol.to_string(),
+            name: Some(format!("{} Token", symbol)),
+            decimals: 18,
+            logo_uri: None,
+            tags: Vec::new(),
+            first_seen: 1_735_689_600,
+            source: TokenSource::OnChain,
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = temp_data_dir("store");
+        let store = TokenStore::open(&dir).unwrap();
+        assert!(store.load().unwrap().is_empty());
+
+        let first = token(1, Address::with_last_byte(1), "AAA");
+        store.append(&first).unwrap();
+        // 同一地址的后续记录被忽略
+        store
+            .append(&token(1, Address::with_last_byte(1), "BBB"))
+            .unwrap();
+        // 其他链上的同一地址是另一个代币
+        store
+            .append(&token(10, Address::with_last_byte(1), "CCC"))
+            .unwrap();
+        drop(store);
+
+        let reopened = TokenStore::open(&dir).unwrap();
+        let tokens = reopened.load().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0], first);
+        assert_eq!(tokens[1].chain_id, 10);
+
+        let line = fs::read_to_string(reopened.path()).unwrap();
+        assert!(line.contains(r#""source":"on_chain""#));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_store_skips_truncated_lines() {
+        let dir = temp_data_dir("truncated");
+        let store = TokenStore::open(&dir).unwrap();
+        store
+            .append(&token(1, Address::with_last_byte(1), "AAA"))
+            .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(store.path())
+            .unwrap()
+            .write_all(br#"{"chain_id":1,"address":"0x00"#)
+            .unwrap();
+        drop(store);
+
+        let store = TokenStore::open(&dir).unwrap();
+        store
+            .append(&token(1, Address::with_last_byte(2), "BBB"))
+            .unwrap();
+        assert_eq!(store.load().unwrap().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
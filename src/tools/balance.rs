@@ -1,60 +1,120 @@
 use alloy::primitives::Address;
+use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
-use crate::error::Result;
+use crate::error::{EthereumError, Result};
 use crate::precision;
-use crate::rpc::RpcClient;
-use crate::tokens::TokenRegistry;
+use crate::rpc::client::IERC20;
+use crate::rpc::{BlockSelector, ChainClients, MulticallBatch, PinnedBlock, RpcClient};
+use crate::tokens::{TokenMetadata, TokenRegistry};
+use crate::tools::registry::{parse_arguments, CallToolResult, Tool, ToolContext, ToolError};
+use crate::tools::schema::schema_for;
 
 // ETH 地址的特殊标识符（通常用于区分 ETH 和 ERC20）
 const ETH_IDENTIFIER: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// ERC20 代币元数据的来源：注册表中已有，或与余额在同一次 Multicall 中查询
+enum Erc20Metadata {
+    Known(TokenMetadata),
+    /// 各查询在批量调用中的下标
+    Queried {
+        decimals_call: usize,
+        symbol_call: usize,
+        name_call: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BalanceRequest {
     /// 钱包地址
+    #[schemars(description = "Ethereum wallet address (0x...)")]
     pub address: String,
     /// 代币地址或符号（可选）
     /// - 如果不提供，默认查询 ETH
     /// - 如果提供为 ETH 特殊地址或符号，查询 ETH
     /// - 如果提供为合约地址，查询对应的 ERC20 代币
+    #[schemars(
+        description = "ERC20 contract address or token symbol (optional, omit for ETH balance)"
+    )]
     pub token_address: Option<String>,
+    /// 读取的区块（区块号、哈希或标签），默认 latest
+    #[schemars(
+        description = "Block to read at: number, hash, or tag (latest, safe, finalized). Defaults to latest"
+    )]
+    pub block: Option<String>,
+    /// 历史查询：取该时间之前的最后一个区块，与 block 互斥
+    #[schemars(
+        description = "Historical balance at a point in time: Unix seconds, RFC 3339 (e.g. 2026-06-30T00:00:00Z) or YYYY-MM-DD (UTC midnight). Uses the last block at or before it; mutually exclusive with block"
+    )]
+    pub timestamp: Option<String>,
+    /// 查询的网络（名称或链 ID），默认为服务器的默认网络
+    #[schemars(
+        description = "Network to query: name (ethereum, arbitrum, base, optimism, sepolia) or chain ID. Defaults to the server's default network"
+    )]
+    pub chain: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BalanceResponse {
+    /// 余额所在链的 ID
+    #[schemars(description = "Chain ID of the network the balance was read on")]
+    pub chain_id: u64,
     /// 查询的钱包地址
+    #[schemars(description = "Queried wallet address")]
     pub address: String,
     /// 余额（人类可读格式）
+    #[schemars(description = "Balance in human-readable units")]
     pub balance: String,
     /// 代币小数位数
+    #[schemars(description = "Token decimals")]
     pub decimals: u8,
     /// 原始余额（最小单位）
+    #[schemars(description = "Raw balance in the smallest unit (wei for ETH)")]
     pub raw: String,
     /// 代币符号或 "ETH"
+    #[schemars(description = "Token symbol, or ETH")]
     pub token_type: String,
     /// 代币合约地址（ETH 时为 ETH 特殊地址）
+    #[schemars(description = "Token contract address (0xEeee...EEeE for ETH)")]
     pub token_address: String,
+    /// 读取余额的区块号
+    #[schemars(description = "Number of the block the balance was read at")]
+    pub block_number: u64,
+    /// 读取余额的区块哈希
+    #[schemars(description = "Hash of the block the balance was read at")]
+    pub block_hash: String,
+    /// 读取余额的区块时间戳
+    #[schemars(description = "Unix timestamp (seconds) of the block the balance was read at")]
+    pub block_timestamp: u64,
 }
 
 pub struct BalanceTool {
-    rpc: RpcClient,
-    token_registry: TokenRegistry,
+    chains: ChainClients,
+    token_registry: Arc<TokenRegistry>,
 }
 
 impl BalanceTool {
     pub fn new(rpc: RpcClient) -> Self {
+        Self::with_chains(rpc.into(), Arc::new(TokenRegistry::new()))
+    }
+
+    /// 使用各链的 RPC 客户端和共享的代币注册表创建
+    pub fn with_chains(chains: ChainClients, token_registry: Arc<TokenRegistry>) -> Self {
         BalanceTool {
-            rpc,
-            token_registry: TokenRegistry::new(),
+            chains,
+            token_registry,
         }
     }
 
     /// 验证以太坊地址格式
     fn validate_address(addr_str: &str) -> Result<Address> {
-        addr_str.parse::<Address>().map_err(|_| {
-            crate::error::EthereumError::InvalidAddress(format!("无效的以太坊地址: {}", addr_str))
-        })
+        addr_str
+            .parse::<Address>()
+            .map_err(|_| EthereumError::InvalidAddress(format!("无效的以太坊地址: {}", addr_str)))
     }
 
     /// 智能识别代币类型并获取余额
@@ -72,30 +132,76 @@ impl BalanceTool {
 
         // 验证钱包地址
         let wallet_address = Self::validate_address(&request.address)?;
+        let chain = self.chains.select(request.chain.as_deref())?;
 
         // 智能识别代币类型
-        let token_info = self.resolve_token_info(request.token_address).await?;
+        let token_info =
+            self.resolve_token_info(chain.network().chain_id, request.token_address)?;
 
         debug!("解析代币信息: {:?}", token_info);
 
-        // 根据解析结果调用相应的方法
+        // 历史查询优先在归档节点上读取状态
+        let selector =
+            BlockSelector::from_request(request.block.as_deref(), request.timestamp.as_deref())?;
+        let block = chain.resolve_block(selector).await?;
+        let rpc = chain.state_reader(&selector);
+        self.balance_at(&rpc, wallet_address, &token_info, &block)
+            .await
+    }
+
+    /// 在已固定的区块上查询余额，供其他工具与自身的读取保持在同一区块
+    ///
+    /// `rpc` 为读取该区块状态所用的客户端（见 [`RpcClient::state_reader`]）。
+    pub(crate) async fn get_balance_at(
+        &self,
+        rpc: &RpcClient,
+        wallet_address: Address,
+        token_address: Option<String>,
+        block: &PinnedBlock,
+    ) -> Result<BalanceResponse> {
+        let token_info = self.resolve_token_info(rpc.network().chain_id, token_address)?;
+        self.balance_at(rpc, wallet_address, &token_info, block)
+            .await
+    }
+
+    /// 根据解析结果调用相应的方法
+    async fn balance_at(
+        &self,
+        rpc: &RpcClient,
+        wallet_address: Address,
+        token_info: &TokenInfo,
+        block: &PinnedBlock,
+    ) -> Result<BalanceResponse> {
         match token_info.is_eth {
-            true => self.get_eth_balance(wallet_address).await,
+            true => Self::get_eth_balance(rpc, wallet_address, block).await,
             false => {
-                self.get_erc20_balance(wallet_address, &token_info.address, &token_info.symbol)
-                    .await
+                self.get_erc20_balance(
+                    rpc,
+                    wallet_address,
+                    &token_info.address,
+                    token_info.symbol.as_deref(),
+                    block,
+                )
+                .await
             }
         }
     }
 
     /// 智能解析代币信息
-    async fn resolve_token_info(&self, token_address: Option<String>) -> Result<TokenInfo> {
+    ///
+    /// 以合约地址给出的代币没有符号，符号与余额在同一次 Multicall 中读取。
+    /// 代币符号在链 `chain_id` 的代币列表中查找。
+    fn resolve_token_info(
+        &self,
+        chain_id: u64,
+        token_address: Option<String>,
+    ) -> Result<TokenInfo> {
         match token_address {
             None => {
                 // 未提供 token_address，默认查询 ETH
                 Ok(TokenInfo {
                     address: ETH_IDENTIFIER.to_string(),
-                    symbol: "ETH".to_string(),
+                    symbol: Some("ETH".to_string()),
                     is_eth: true,
                 })
             }
@@ -106,7 +212,7 @@ impl BalanceTool {
                 if token_id_upper == ETH_IDENTIFIER.to_uppercase() || token_id_upper == "ETH" {
                     return Ok(TokenInfo {
                         address: ETH_IDENTIFIER.to_string(),
-                        symbol: "ETH".to_string(),
+                        symbol: Some("ETH".to_string()),
                         is_eth: true,
                     });
                 }
@@ -117,34 +223,31 @@ impl BalanceTool {
                     if address.to_string().to_uppercase() == ETH_IDENTIFIER.to_uppercase() {
                         Ok(TokenInfo {
                             address: ETH_IDENTIFIER.to_string(),
-                            symbol: "ETH".to_string(),
+                            symbol: Some("ETH".to_string()),
                             is_eth: true,
                         })
                     } else {
-                        // 是合约地址，需要获取符号
-                        let symbol = self
-                            .rpc
-                            .get_token_symbol(address)
-                            .await
-                            .unwrap_or_else(|_| "UNKNOWN".to_string());
-
+                        // 是合约地址，符号稍后与余额一起查询
                         Ok(TokenInfo {
                             address: address.to_string(),
-                            symbol,
+                            symbol: None,
                             is_eth: false,
                         })
                     }
                 } else {
                     // 不是地址，尝试作为符号查找
-                    if let Some(address) = self.token_registry.symbol_to_address(&token_id_upper) {
+                    if let Some(address) = self
+                        .token_registry
+                        .symbol_to_address(chain_id, &token_id_upper)
+                    {
                         Ok(TokenInfo {
                             address: address.to_string(),
-                            symbol: token_id_upper,
+                            symbol: Some(token_id_upper),
                             is_eth: false,
                         })
                     } else {
                         warn!("未找到代币符号: {}", token_id);
-                        Err(crate::error::EthereumError::TokenNotFound(format!(
+                        Err(EthereumError::TokenNotFound(format!(
                             "未找到代币: {}",
                             token_id
                         )))
@@ -155,61 +258,163 @@ impl BalanceTool {
     }
 
     /// 获取 ETH 余额
-    async fn get_eth_balance(&self, address: Address) -> Result<BalanceResponse> {
-        info!("正在获取 ETH 余额: {:?}", address);
+    async fn get_eth_balance(
+        rpc: &RpcClient,
+        address: Address,
+        block: &PinnedBlock,
+    ) -> Result<BalanceResponse> {
+        info!("正在获取 ETH 余额: {:?} (区块 {})", address, block.number);
 
-        let raw_balance = self.rpc.get_eth_balance(address).await?;
+        let raw_balance = rpc.get_eth_balance(address, block.id()).await?;
         let balance = precision::to_decimal(raw_balance, 18)?;
 
         Ok(BalanceResponse {
+            chain_id: rpc.network().chain_id,
             address: address.to_string(),
             balance: balance.normalize().to_string(),
             decimals: 18,
             raw: raw_balance.to_string(),
             token_type: "ETH".to_string(),
             token_address: ETH_IDENTIFIER.to_string(),
+            block_number: block.number,
+            block_hash: block.hash.to_string(),
+            block_timestamp: block.timestamp,
         })
     }
 
     /// 获取 ERC20 代币余额
+    ///
+    /// 已解析过的代币只读取余额；首次遇到的代币在同一次 Multicall 中读取小数位数、
+    /// 符号和名称，并记录到代币注册表。
     async fn get_erc20_balance(
         &self,
+        rpc: &RpcClient,
         wallet_address: Address,
         token_addr_str: &str,
-        token_symbol: &str,
+        token_symbol: Option<&str>,
+        block: &PinnedBlock,
     ) -> Result<BalanceResponse> {
         info!(
-            "正在获取 ERC20 余额: {:?} 在代币: {} ({})",
-            wallet_address, token_addr_str, token_symbol
+            "正在获取 ERC20 余额: {:?} 在代币: {} ({}) (区块 {})",
+            wallet_address,
+            token_addr_str,
+            token_symbol.unwrap_or("?"),
+            block.number
         );
 
         let token_address = Self::validate_address(token_addr_str)?;
 
-        // 并行获取代币小数位数和余额
-        let decimals = self.rpc.get_token_decimals(token_address).await?;
-        let raw_balance = self
-            .rpc
-            .get_token_balance(token_address, wallet_address)
-            .await?;
+        let chain_id = rpc.network().chain_id;
+        let known = self.token_registry.metadata(chain_id, token_address);
+
+        let mut batch = MulticallBatch::new();
+        let balance_call = batch.add(
+            token_address,
+            IERC20::balanceOfCall {
+                account: wallet_address,
+            },
+        );
+        let metadata = match known {
+            Some(metadata) => Erc20Metadata::Known(metadata),
+            None => Erc20Metadata::Queried {
+                decimals_call: batch.add(token_address, IERC20::decimalsCall {}),
+                symbol_call: batch.add(token_address, IERC20::symbolCall {}),
+                name_call: batch.add(token_address, IERC20::nameCall {}),
+            },
+        };
+
+        let results = rpc.multicall(&batch, block.id()).await?;
+        let raw_balance = results[balance_call]
+            .decode::<IERC20::balanceOfCall>()
+            .map_err(|e| EthereumError::InvalidERC20(format!("{}: {}", token_address, e)))?
+            ._0;
+        let (decimals, resolved_symbol) = match metadata {
+            Erc20Metadata::Known(metadata) => (metadata.decimals, Some(metadata.symbol)),
+            Erc20Metadata::Queried {
+                decimals_call,
+                symbol_call,
+                name_call,
+            } => {
+                let decimals = results[decimals_call]
+                    .decode::<IERC20::decimalsCall>()
+                    .map_err(|e| EthereumError::InvalidERC20(format!("{}: {}", token_address, e)))?
+                    ._0;
+                let symbol = results[symbol_call]
+                    .decode::<IERC20::symbolCall>()
+                    .map(|r| r._0)
+                    .ok();
+                if let Some(symbol) = &symbol {
+                    let name = results[name_call]
+                        .decode::<IERC20::nameCall>()
+                        .map(|r| r._0)
+                        .ok();
+                    self.token_registry.record_resolved(
+                        chain_id,
+                        token_address,
+                        symbol.clone(),
+                        name,
+                        decimals,
+                    );
+                }
+                (decimals, symbol)
+            }
+        };
+        let token_symbol = token_symbol
+            .map(str::to_string)
+            .or(resolved_symbol)
+            .unwrap_or_else(|| "UNKNOWN".to_string());
 
         // 转换为人类可读的格式
         let balance = precision::to_decimal(raw_balance, decimals)?;
 
         Ok(BalanceResponse {
+            chain_id,
             address: wallet_address.to_string(),
             balance: balance.normalize().to_string(),
             decimals,
             raw: raw_balance.to_string(),
-            token_type: token_symbol.to_string(),
+            token_type: token_symbol,
             token_address: token_address.to_string(),
+            block_number: block.number,
+            block_hash: block.hash.to_string(),
+            block_timestamp: block.timestamp,
         })
     }
 }
 
+#[async_trait]
+impl Tool for BalanceTool {
+    fn name(&self) -> &str {
+        "get_balance"
+    }
+
+    fn description(&self) -> &str {
+        "Get ETH or ERC20 token balance for a wallet address"
+    }
+
+    fn input_schema(&self) -> Value {
+        schema_for::<BalanceRequest>()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(schema_for::<BalanceResponse>())
+    }
+
+    async fn call(
+        &self,
+        arguments: Value,
+        _context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError> {
+        let request: BalanceRequest = parse_arguments(arguments)?;
+        CallToolResult::from_result(self.get_balance(request).await, "Balance query failed")
+    }
+}
+
 #[derive(Debug)]
 struct TokenInfo {
     address: String,
-    symbol: String,
+    /// 以合约地址给出时为 `None`，需要从链上查询
+    symbol: Option<String>,
     is_eth: bool,
 }
 
@@ -241,6 +446,9 @@ mod tests {
         let request = BalanceRequest {
             address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
             token_address: None,
+            block: None,
+            timestamp: None,
+            chain: None,
         };
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"));
@@ -249,6 +457,9 @@ mod tests {
         let request_with_token = BalanceRequest {
             address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
             token_address: Some("USDT".to_string()),
+            block: None,
+            timestamp: None,
+            chain: None,
         };
         let json = serde_json::to_string(&request_with_token).unwrap();
         assert!(json.contains("USDT"));
@@ -257,12 +468,17 @@ mod tests {
     #[test]
     fn test_balance_response_with_token_address() {
         let response = BalanceResponse {
+            chain_id: 1,
             address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
             balance: "100.5".to_string(),
             decimals: 6,
             raw: "100500000".to_string(),
             token_type: "USDT".to_string(),
             token_address: "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+            block_number: 21_525_000,
+            block_hash: "0x0000000000000000000000000000000000000000000000000000000000000000"
+                .to_string(),
+            block_timestamp: 1735689600,
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -285,9 +501,9 @@ mod tests {
             .unwrap();
         let tool = BalanceTool::new(rpc);
 
-        let token_info = tool.resolve_token_info(None).await.unwrap();
+        let token_info = tool.resolve_token_info(1, None).unwrap();
         assert!(token_info.is_eth);
-        assert_eq!(token_info.symbol, "ETH");
+        assert_eq!(token_info.symbol.as_deref(), Some("ETH"));
     }
 
     #[tokio::test]
@@ -297,12 +513,9 @@ mod tests {
             .unwrap();
         let tool = BalanceTool::new(rpc);
 
-        let token_info = tool
-            .resolve_token_info(Some("ETH".to_string()))
-            .await
-            .unwrap();
+        let token_info = tool.resolve_token_info(1, Some("ETH".to_string())).unwrap();
         assert!(token_info.is_eth);
-        assert_eq!(token_info.symbol, "ETH");
+        assert_eq!(token_info.symbol.as_deref(), Some("ETH"));
     }
 
     #[tokio::test]
@@ -313,11 +526,10 @@ mod tests {
         let tool = BalanceTool::new(rpc);
 
         let token_info = tool
-            .resolve_token_info(Some(ETH_IDENTIFIER.to_string()))
-            .await
+            .resolve_token_info(1, Some(ETH_IDENTIFIER.to_string()))
             .unwrap();
         assert!(token_info.is_eth);
-        assert_eq!(token_info.symbol, "ETH");
+        assert_eq!(token_info.symbol.as_deref(), Some("ETH"));
     }
 
     #[tokio::test]
@@ -328,14 +540,165 @@ mod tests {
         let tool = BalanceTool::new(rpc);
 
         let token_info = tool
-            .resolve_token_info(Some("USDT".to_string()))
-            .await
+            .resolve_token_info(1, Some("USDT".to_string()))
             .unwrap();
         assert!(!token_info.is_eth);
-        assert_eq!(token_info.symbol, "USDT");
+        assert_eq!(token_info.symbol.as_deref(), Some("USDT"));
         assert_eq!(
             token_info.address.to_uppercase(),
             "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_uppercase()
         );
     }
+
+    #[tokio::test]
+    async fn test_erc20_balance_at_block() {
+        use crate::rpc::block::mock_block_response;
+        use crate::rpc::multicall::IMulticall3;
+        use alloy::primitives::{Bytes, B256, U256};
+        use alloy::sol_types::SolCall;
+        use mockito::Matcher;
+
+        let ok = |data: Vec<u8>| IMulticall3::Call3Result {
+            success: true,
+            returnData: data.into(),
+        };
+        // 不在代币列表中的代币（PYUSD），调用顺序：balanceOf、decimals、symbol、name
+        let output = IMulticall3::aggregate3Call::abi_encode_returns(&(vec![
+            ok(IERC20::balanceOfCall::abi_encode_returns(&(U256::from(
+                100_500_000u64,
+            ),))),
+            ok(IERC20::decimalsCall::abi_encode_returns(&(6u8,))),
+            ok(IERC20::symbolCall::abi_encode_returns(&(
+                "PYUSD".to_string(),
+            ))),
+            ok(IERC20::nameCall::abi_encode_returns(&(
+                "PayPal USD".to_string(),
+            ))),
+        ],));
+
+        let block_hash = B256::repeat_byte(0xcd);
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber","params":["0x1487208",false]}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(21_525_000, block_hash, 1735689600))
+            .create_async()
+            .await;
+        let mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "method": "eth_call",
+                "params": [{}, {"blockHash": block_hash}]
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"{}"}}"#,
+                Bytes::from(output)
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let tool = BalanceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let response = tool
+            .get_balance(BalanceRequest {
+                address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
+                token_address: Some("0x6c3ea9036406852006290770BEdFcAbA0e23A0e8".to_string()),
+                block: Some("21525000".to_string()),
+                timestamp: None,
+                chain: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.balance, "100.5");
+        assert_eq!(response.decimals, 6);
+        assert_eq!(response.token_type, "PYUSD");
+        assert_eq!(response.block_number, 21_525_000);
+        assert_eq!(response.block_hash, block_hash.to_string());
+        assert_eq!(response.block_timestamp, 1735689600);
+        mock.assert_async().await;
+
+        // 首次解析到的代币元数据记录到注册表
+        let metadata = tool
+            .token_registry
+            .metadata(1, response.token_address.parse().unwrap())
+            .unwrap();
+        assert_eq!(metadata.decimals, 6);
+        assert_eq!(metadata.name.as_deref(), Some("PayPal USD"));
+    }
+
+    #[tokio::test]
+    async fn test_balance_on_selected_chain() {
+        use crate::config::RpcClientConfig;
+        use crate::network::NetworkProfile;
+        use crate::rpc::block::mock_block_response;
+        use alloy::primitives::B256;
+        use mockito::Matcher;
+
+        let mut mainnet = mockito::Server::new_async().await;
+        let untouched = mainnet.mock("POST", "/").expect(0).create_async().await;
+
+        let mut base = mockito::Server::new_async().await;
+        base.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(
+                24_000_000,
+                B256::repeat_byte(0xba),
+                1735689600,
+            ))
+            .create_async()
+            .await;
+        base.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBalance"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0xde0b6b3a7640000"}"#)
+            .create_async()
+            .await;
+
+        let client = |url: String, chain_id| {
+            RpcClient::with_config(url, &RpcClientConfig::default())
+                .unwrap()
+                .with_network(NetworkProfile::by_chain_id(chain_id).unwrap())
+        };
+        let chains =
+            ChainClients::new(vec![client(mainnet.url(), 1), client(base.url(), 8453)]).unwrap();
+        let tool = BalanceTool::with_chains(chains, Arc::new(TokenRegistry::new()));
+
+        let response = tool
+            .get_balance(BalanceRequest {
+                address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
+                token_address: None,
+                block: None,
+                timestamp: None,
+                chain: Some("base".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.chain_id, 8453);
+        assert_eq!(response.balance, "1");
+        assert_eq!(response.block_number, 24_000_000);
+        untouched.assert_async().await;
+
+        // 未配置的网络直接报错，不发出请求
+        let err = tool
+            .get_balance(BalanceRequest {
+                address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
+                token_address: None,
+                block: None,
+                timestamp: None,
+                chain: Some("arbitrum".to_string()),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::NetworkError(_)));
+    }
 }
@@ -1,9 +1,14 @@
 pub mod balance;
+pub mod networks;
 pub mod price;
+pub mod registry;
+pub mod schema;
 pub mod swap;
 
 pub use balance::BalanceTool;
+pub use networks::NetworksTool;
 pub use price::PriceTool;
+pub use registry::{CallToolResult, Tool, ToolContext, ToolDefinition, ToolError, ToolRegistry};
 pub use swap::SwapTool;
 
 use serde::{Deserialize, Serialize};
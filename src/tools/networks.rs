@@ -0,0 +1,223 @@
+use alloy::eips::BlockId;
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::rpc::{ChainClients, RpcClient};
+use crate::tools::registry::{parse_arguments, CallToolResult, Tool, ToolContext, ToolError};
+use crate::tools::schema::schema_for;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListNetworksRequest {}
+
+/// 网络的可用状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkHealth {
+    /// 能读取最新区块且所有端点健康
+    Ok,
+    /// 能读取最新区块，但部分端点被标记为不健康
+    Degraded,
+    /// 无法读取最新区块
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkStatus {
+    #[schemars(description = "Chain ID")]
+    pub chain_id: u64,
+    #[schemars(description = "Network name accepted by the chain argument of other tools")]
+    pub name: String,
+    #[schemars(description = "Human-readable network name")]
+    pub display_name: String,
+    #[schemars(description = "Symbol of the native currency")]
+    pub native_symbol: String,
+    #[schemars(description = "Whether tools use this network when no chain argument is given")]
+    pub is_default: bool,
+    #[schemars(
+        description = "ok: latest block readable and all endpoints healthy; degraded: some endpoints unhealthy; unavailable: latest block could not be read"
+    )]
+    pub status: NetworkHealth,
+    #[schemars(description = "Current block height (absent if unavailable)")]
+    pub block_number: Option<u64>,
+    #[schemars(
+        description = "Unix timestamp (seconds) of the current block (absent if unavailable)"
+    )]
+    pub block_timestamp: Option<u64>,
+    #[schemars(description = "Number of RPC endpoints currently marked healthy")]
+    pub healthy_endpoints: usize,
+    #[schemars(description = "Number of configured RPC endpoints")]
+    pub total_endpoints: usize,
+    #[schemars(description = "Why the latest block could not be read")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListNetworksResponse {
+    #[schemars(description = "Chain ID used when a tool call has no chain argument")]
+    pub default_chain_id: u64,
+    #[schemars(description = "Configured networks, default network first")]
+    pub networks: Vec<NetworkStatus>,
+}
+
+/// 列出服务器连接的各条链及其状态
+pub struct NetworksTool {
+    chains: ChainClients,
+}
+
+impl NetworksTool {
+    pub fn new(chains: ChainClients) -> Self {
+        NetworksTool { chains }
+    }
+
+    /// 并发读取每条链的最新区块，汇总网络状态
+    pub async fn list_networks(&self) -> ListNetworksResponse {
+        let default_chain_id = self.chains.default_client().network().chain_id;
+        let networks = futures::future::join_all(
+            self.chains
+                .iter()
+                .map(|rpc| Self::network_status(rpc, default_chain_id)),
+        )
+        .await;
+
+        ListNetworksResponse {
+            default_chain_id,
+            networks,
+        }
+    }
+
+    async fn network_status(rpc: &RpcClient, default_chain_id: u64) -> NetworkStatus {
+        let network = rpc.network();
+        let latest = rpc.pin_block(BlockId::latest()).await;
+        // 端点状态在读取区块之后统计，反映这次读取的结果
+        let endpoints = rpc.endpoint_health();
+        let healthy_endpoints = endpoints.iter().filter(|e| e.healthy).count();
+
+        let (status, block_number, block_timestamp, error) = match latest {
+            Ok(block) => {
+                debug!("{} 当前区块: {}", network.name, block.number);
+                let status = if healthy_endpoints == endpoints.len() {
+                    NetworkHealth::Ok
+                } else {
+                    NetworkHealth::Degraded
+                };
+                (status, Some(block.number), Some(block.timestamp), None)
+            }
+            Err(e) => {
+                warn!("无法读取 {} 的最新区块: {}", network.name, e);
+                (NetworkHealth::Unavailable, None, None, Some(e.to_string()))
+            }
+        };
+
+        NetworkStatus {
+            chain_id: network.chain_id,
+            name: network.name.to_string(),
+            display_name: network.display_name.to_string(),
+            native_symbol: network.native_symbol.to_string(),
+            is_default: network.chain_id == default_chain_id,
+            status,
+            block_number,
+            block_timestamp,
+            healthy_endpoints,
+            total_endpoints: endpoints.len(),
+            error,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for NetworksTool {
+    fn name(&self) -> &str {
+        "list_networks"
+    }
+
+    fn description(&self) -> &str {
+        "List the networks this server can query, with RPC status and current block height"
+    }
+
+    fn input_schema(&self) -> Value {
+        schema_for::<ListNetworksRequest>()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(schema_for::<ListNetworksResponse>())
+    }
+
+    async fn call(
+        &self,
+        arguments: Value,
+        _context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError> {
+        let _: ListNetworksRequest = parse_arguments(arguments)?;
+        CallToolResult::json(&self.list_networks().await, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcClientConfig;
+    use crate::network::NetworkProfile;
+    use crate::rpc::block::mock_block_response;
+    use alloy::primitives::B256;
+
+    #[tokio::test]
+    async fn test_list_networks_reports_status_and_height() {
+        let mut mainnet = mockito::Server::new_async().await;
+        mainnet
+            .mock("POST", "/")
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(
+                21_525_000,
+                B256::repeat_byte(0x01),
+                1735689600,
+            ))
+            .create_async()
+            .await;
+        let mut base = mockito::Server::new_async().await;
+        base.mock("POST", "/").with_status(500).create_async().await;
+
+        let config = RpcClientConfig {
+            retry: crate::rpc::RetryPolicy {
+                max_retries: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let client = |url: String, chain_id| {
+            RpcClient::with_config(url, &config)
+                .unwrap()
+                .with_network(NetworkProfile::by_chain_id(chain_id).unwrap())
+        };
+        let tool = NetworksTool::new(
+            ChainClients::new(vec![client(mainnet.url(), 1), client(base.url(), 8453)]).unwrap(),
+        );
+
+        let response = tool.list_networks().await;
+        assert_eq!(response.default_chain_id, 1);
+        assert_eq!(response.networks.len(), 2);
+
+        let ethereum = &response.networks[0];
+        assert_eq!(ethereum.name, "ethereum");
+        assert!(ethereum.is_default);
+        assert_eq!(ethereum.status, NetworkHealth::Ok);
+        assert_eq!(ethereum.block_number, Some(21_525_000));
+        assert_eq!(ethereum.block_timestamp, Some(1735689600));
+        assert_eq!(
+            (ethereum.healthy_endpoints, ethereum.total_endpoints),
+            (1, 1)
+        );
+
+        let base = &response.networks[1];
+        assert_eq!(base.chain_id, 8453);
+        assert!(!base.is_default);
+        assert_eq!(base.status, NetworkHealth::Unavailable);
+        assert!(base.block_number.is_none());
+        assert!(base.error.is_some());
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["networks"][1]["status"], "unavailable");
+    }
+}
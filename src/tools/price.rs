@@ -1,157 +1,584 @@
-use alloy::primitives::{Address, U256};
-use alloy::sol;
+use alloy::eips::BlockId;
+use alloy::primitives::{address, Address};
+use async_trait::async_trait;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, warn};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
 
 use crate::error::{EthereumError, Result};
-use crate::precision;
-use crate::rpc::RpcClient;
+use crate::network::FeedQuote;
+use crate::pricing::aggregate::{
+    self, AggregationMethod, SourceKind, SourcePrice, DEFAULT_MAX_DEVIATION_PERCENT,
+};
+use crate::pricing::chainlink::{self, FeedCalls, FeedLeg, FeedPrice};
+use crate::pricing::depth::{self, DepthCalls, PoolDepth};
+use crate::pricing::v3::{PoolTwap, DEFAULT_TWAP_WINDOW};
+use crate::pricing::{PoolPrice, PoolState, Protocol, SpotCalls};
+use crate::rpc::client::IERC20;
+use crate::rpc::{BlockSelector, CallResult, ChainClients, MulticallBatch, RpcClient};
 use crate::tokens::TokenRegistry;
+use crate::tools::registry::{parse_arguments, CallToolResult, Tool, ToolContext, ToolError};
+use crate::tools::schema::{decimal_schema, schema_for};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 代币列表中原生 ETH 使用的占位地址
+const NATIVE_TOKEN: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// 价格来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Uniswap V2/V3 中流动性最深的池的现货价格
+    #[default]
+    Dex,
+    /// 每一跳流动性最深的 Uniswap V3 池在窗口内的时间加权平均价格
+    Twap,
+    /// Chainlink 喂价
+    Chainlink,
+    /// 同一区块上读取 V2 现货、V3 现货、V3 TWAP 和 Chainlink，剔除偏离的来源后聚合
+    Aggregate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PriceRequest {
     // 代币名称或者代币地址
+    #[schemars(description = "Token symbol (e.g., ETH, USDC) or contract address")]
     pub token_identifier: String, // 可以是符号或合约地址
     // 报价货币，默认是 USD
+    #[schemars(description = "Quote currency: USD (default) or ETH")]
     pub quote_currency: Option<String>,
+    // 读取的区块，默认 latest
+    #[schemars(
+        description = "Block to read at: number, hash, or tag (latest, safe, finalized). Defaults to latest"
+    )]
+    pub block: Option<String>,
+    // 历史查询：取该时间之前的最后一个区块，与 block 互斥
+    #[schemars(
+        description = "Historical quote at a point in time: Unix seconds, RFC 3339 (e.g. 2026-06-30T00:00:00Z) or YYYY-MM-DD (UTC midnight). Uses the last block at or before it; mutually exclusive with block"
+    )]
+    pub timestamp: Option<String>,
+    // 查询的网络（名称或链 ID），默认为服务器的默认网络
+    #[schemars(
+        description = "Network to quote on: name (ethereum, arbitrum, base, optimism, sepolia) or chain ID. Defaults to the server's default network"
+    )]
+    pub chain: Option<String>,
+    // 价格来源，默认 DEX 现货价格
+    #[schemars(
+        description = "Price source: dex (spot price of the deepest Uniswap V2/V3 pool, default), twap (time-weighted average price of the deepest Uniswap V3 pool over twap_window), chainlink (Chainlink price feeds; fails if a feed has not updated within its heartbeat) or aggregate (V2 spot, V3 spot, V3 TWAP and Chainlink read at the same block, with outliers excluded)"
+    )]
+    pub source: Option<PriceSource>,
+    // TWAP 窗口（秒），默认 1800
+    #[schemars(
+        description = "twap and aggregate sources: TWAP window in seconds (default 1800, i.e. 30 minutes)"
+    )]
+    pub twap_window: Option<u32>,
+    // 聚合方式，仅 aggregate 来源使用
+    #[schemars(
+        description = "Aggregate source only: median (default) of the agreeing sources, or liquidity_weighted mean of the agreeing DEX sources (Chainlink then only serves as a reference)"
+    )]
+    pub aggregation: Option<AggregationMethod>,
+    // 聚合时与中位数的最大偏离百分比，默认 2
+    #[serde(default)]
+    #[schemars(
+        schema_with = "decimal_schema",
+        description = "Aggregate source only: maximum deviation from the median of all sources in percent (default 2); sources beyond it are flagged as outliers and excluded"
+    )]
+    pub max_deviation: Option<Decimal>,
+    // 是否返回各跳池的储备量、TVL 和价格冲击
+    #[schemars(
+        description = "Also return, for the deepest pool of each hop, its reserves and TVL in USD, how much can be traded before its price moves 0.5%, 1% and 2%, and the price impact of 1k to 1M USD trades. Works with every source; costs one or two extra reads at the same block for V3 pools"
+    )]
+    pub include_depth: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PriceResponse {
+    #[schemars(description = "Chain ID of the network the quote was read on")]
+    pub chain_id: u64,
+    #[schemars(description = "Quote currency of the price")]
     pub quote_currency: String,
+    #[schemars(description = "Price of one token in the quote currency")]
     pub price: String,
+    #[schemars(description = "Source the price was read from")]
+    pub source: PriceSource,
+    #[schemars(
+        description = "DEX source: pools that set the price, one per hop: token/WETH, then WETH/stablecoin for USD quotes"
+    )]
+    pub pools: Vec<PoolInfo>,
+    #[schemars(
+        description = "Chainlink source: feeds that set the price, e.g. TOKEN/ETH then ETH/USD when there is no direct TOKEN/USD feed"
+    )]
+    pub feeds: Vec<FeedInfo>,
+    #[schemars(
+        description = "Aggregate source: confidence from 0 to 1, min(agreeing, 3) / 3 * agreeing / available sources; a single source scores at most 0.33"
+    )]
+    pub confidence: Option<String>,
+    #[schemars(description = "Aggregate source: per-source breakdown")]
+    pub sources: Vec<SourceQuote>,
+    #[schemars(
+        description = "With include_depth: reserves, TVL and price-impact table of the deepest pool of each hop"
+    )]
+    pub depth: Vec<DepthInfo>,
+    #[schemars(
+        description = "Caveats about the quote, e.g. a deeper V3 pool skipped because its observation cardinality is too small for the TWAP window"
+    )]
+    pub notes: Vec<String>,
+    #[schemars(description = "Unix timestamp (seconds) of the block the quote was read at")]
     pub timestamp: u64,
+    #[schemars(description = "Number of the block all reads were pinned to")]
+    pub block_number: u64,
+    #[schemars(description = "Hash of the block all reads were pinned to")]
+    pub block_hash: String,
 }
 
-// Uniswap V2 Pair contract interface
-sol! {
-    #[allow(missing_docs)]
-    #[sol(rpc)]
-    contract IUniswapV2Pair {
-        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
-        function token0() external view returns (address);
-        function token1() external view returns (address);
+/// 价格路径中一跳所用的池
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PoolInfo {
+    #[schemars(description = "DEX protocol of the pool")]
+    pub protocol: Protocol,
+    #[schemars(description = "Pool (pair) contract address")]
+    pub address: String,
+    #[schemars(
+        description = "Uniswap V3 fee tier in hundredths of a basis point (500 = 0.05%); absent for V2 pairs"
+    )]
+    pub fee_tier: Option<u32>,
+    #[schemars(description = "Address of the token priced by this hop")]
+    pub base_token: String,
+    #[schemars(description = "Address of the token this hop is quoted in")]
+    pub quote_token: String,
+    #[schemars(description = "Spot price of base_token in quote_token")]
+    pub price: String,
+    #[schemars(
+        description = "Liquidity L at the current price (sqrt(reserve0 * reserve1) for V2); the deepest of the V2 pair and the V3 fee-tier pools is used"
+    )]
+    pub liquidity: String,
+    #[schemars(description = "TWAP source: observations the price was derived from")]
+    pub twap: Option<TwapInfo>,
+}
+
+impl From<&PoolPrice> for PoolInfo {
+    fn from(quote: &PoolPrice) -> Self {
+        PoolInfo {
+            protocol: quote.protocol,
+            address: quote.pool.to_string(),
+            fee_tier: quote.fee,
+            base_token: quote.token.to_string(),
+            quote_token: quote.quote.to_string(),
+            price: quote.price.normalize().to_string(),
+            liquidity: quote.liquidity.to_string(),
+            twap: None,
+        }
     }
 }
 
-// Uniswap V2 Factory contract interface
-sol! {
-    #[allow(missing_docs)]
-    #[sol(rpc)]
-    contract IUniswapV2Factory {
-        function getPair(address tokenA, address tokenB) external view returns (address pair);
+impl From<&PoolTwap> for PoolInfo {
+    fn from(twap: &PoolTwap) -> Self {
+        PoolInfo {
+            twap: Some(TwapInfo {
+                window_secs: twap.window,
+                mean_tick: twap.mean_tick,
+                harmonic_mean_liquidity: twap.harmonic_mean_liquidity.to_string(),
+                observation_cardinality: twap.observation_cardinality,
+                observation_cardinality_next: twap.observation_cardinality_next,
+            }),
+            ..PoolInfo::from(&twap.quote)
+        }
     }
 }
 
-pub struct PriceTool {
-    rpc: RpcClient,
-    token_registry: TokenRegistry,
+/// 池的 TWAP 观测信息
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TwapInfo {
+    #[schemars(description = "Length of the averaging window in seconds")]
+    pub window_secs: u32,
+    #[schemars(
+        description = "Arithmetic-mean tick over the window; the pool price is 1.0001^tick"
+    )]
+    pub mean_tick: i32,
+    #[schemars(description = "Harmonic-mean in-range liquidity over the window")]
+    pub harmonic_mean_liquidity: String,
+    #[schemars(description = "Number of observations the pool currently stores")]
+    pub observation_cardinality: u16,
+    #[schemars(
+        description = "Observation slots already paid for; cardinality grows to it as the pool is traded"
+    )]
+    pub observation_cardinality_next: u16,
 }
 
-// Uniswap V2 主网地址
-const UNISWAP_V2_FACTORY: &str = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f";
-const WETH_ADDRESS: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
-const USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+/// 价格路径中使用的 Chainlink 喂价
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeedInfo {
+    #[schemars(description = "Feed (aggregator proxy) contract address")]
+    pub address: String,
+    #[schemars(description = "Address of the token the feed prices")]
+    pub base_token: String,
+    #[schemars(description = "Currency the feed is denominated in: USD or ETH")]
+    pub quote_currency: String,
+    #[schemars(description = "Feed answer scaled by its decimals")]
+    pub price: String,
+    #[schemars(description = "Decimals of the feed answer")]
+    pub decimals: u8,
+    #[schemars(description = "Round ID of the answer")]
+    pub round_id: String,
+    #[schemars(description = "Unix timestamp (seconds) of the last feed update")]
+    pub updated_at: u64,
+    #[schemars(
+        description = "Maximum seconds between feed updates; older answers are rejected as stale"
+    )]
+    pub heartbeat_secs: u64,
+    #[schemars(description = "Whether the route divides by this feed instead of multiplying")]
+    pub inverted: bool,
+}
 
-impl PriceTool {
-    pub fn new(rpc: RpcClient) -> Self {
-        PriceTool {
-            rpc,
-            token_registry: TokenRegistry::new(),
+impl FeedInfo {
+    fn new(reading: &FeedPrice, inverted: bool) -> Self {
+        FeedInfo {
+            address: reading.feed.address.to_string(),
+            base_token: reading.feed.base.to_string(),
+            quote_currency: reading.feed.quote.symbol().to_string(),
+            price: reading.price.normalize().to_string(),
+            decimals: reading.decimals,
+            round_id: reading.round_id.to_string(),
+            updated_at: reading.updated_at,
+            heartbeat_secs: reading.feed.heartbeat.as_secs(),
+            inverted,
         }
     }
+}
 
-    /// 从 Uniswap V2 池获取代币价格
-    async fn get_price_from_uniswap_pool(
-        &self,
-        token_address: Address,
-        quote_token: Address,
-    ) -> Result<Decimal> {
-        debug!(
-            "从 Uniswap V2 池获取价格: token={:?}, quote={:?}",
-            token_address, quote_token
-        );
-
-        let factory_address = UNISWAP_V2_FACTORY
-            .parse::<Address>()
-            .map_err(|_| EthereumError::ConfigError("无效的工厂地址".to_string()))?;
+/// 聚合时一个价格来源的报价
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SourceQuote {
+    #[schemars(description = "Price source")]
+    pub source: SourceKind,
+    #[schemars(description = "Price from this source; absent when the source is unavailable")]
+    pub price: Option<String>,
+    #[schemars(description = "Deviation from the median of all available sources, in percent")]
+    pub deviation_percent: Option<String>,
+    #[schemars(
+        description = "Whether the deviation exceeds max_deviation; outliers are excluded from the price"
+    )]
+    pub outlier: bool,
+    #[schemars(
+        description = "Share of the liquidity-weighted price (by liquidity of the token's first-hop pool)"
+    )]
+    pub weight: Option<String>,
+    #[schemars(description = "Why the source is unavailable, e.g. no pool or stale feed")]
+    pub error: Option<String>,
+    #[schemars(description = "Pools used by a DEX source, one per hop")]
+    pub pools: Vec<PoolInfo>,
+    #[schemars(description = "Feeds used by the Chainlink source")]
+    pub feeds: Vec<FeedInfo>,
+}
 
-        let provider = self.rpc.get_provider()?;
-        let factory = IUniswapV2Factory::new(factory_address, provider.clone());
+/// 价格路径中一跳所用池的储备量和价格冲击
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DepthInfo {
+    #[schemars(description = "Pool the depth was read from")]
+    pub pool: PoolInfo,
+    #[schemars(
+        description = "Amount of base_token held by the pool (V2 reserve, or V3 token balance including uncollected fees)"
+    )]
+    pub base_reserve: String,
+    #[schemars(description = "Amount of quote_token held by the pool")]
+    pub quote_reserve: String,
+    #[schemars(description = "USD value of base_reserve")]
+    pub base_reserve_usd: String,
+    #[schemars(description = "USD value of quote_reserve")]
+    pub quote_reserve_usd: String,
+    #[schemars(description = "Total value locked in the pool in USD")]
+    pub tvl_usd: String,
+    #[schemars(
+        description = "How much can be traded before the pool price of base_token moves 0.5%, 1% and 2%"
+    )]
+    pub levels: Vec<DepthLevelInfo>,
+    #[schemars(
+        description = "Price impact of selling or buying base_token for 1k, 10k, 100k and 1M USD"
+    )]
+    pub price_impact: Vec<PriceImpactInfo>,
+}
 
-        // 获取交易对地址
-        let pair_address = factory
-            .getPair(token_address, quote_token)
-            .call()
-            .await
-            .map_err(|e| {
-                warn!("获取交易对失败: {}", e);
-                EthereumError::PriceOracleError(format!("无法获取交易对: {}", e))
-            })?
-            .pair;
-
-        if pair_address == Address::ZERO {
-            return Err(EthereumError::PriceOracleError("交易对不存在".to_string()));
+impl From<&PoolDepth> for DepthInfo {
+    fn from(depth: &PoolDepth) -> Self {
+        let amount = |value: Option<Decimal>| value.map(|v| v.normalize().to_string());
+        DepthInfo {
+            pool: PoolInfo::from(&depth.pool),
+            base_reserve: depth.base_reserve.normalize().to_string(),
+            quote_reserve: depth.quote_reserve.normalize().to_string(),
+            base_reserve_usd: depth.base_reserve_usd.normalize().to_string(),
+            quote_reserve_usd: depth.quote_reserve_usd.normalize().to_string(),
+            tvl_usd: depth.tvl_usd.normalize().to_string(),
+            levels: depth
+                .levels
+                .iter()
+                .map(|level| DepthLevelInfo {
+                    price_move_percent: (Decimal::from(level.bps) / Decimal::ONE_HUNDRED)
+                        .normalize()
+                        .to_string(),
+                    sell_amount: amount(level.sell),
+                    sell_usd: amount(level.sell_usd),
+                    buy_amount: amount(level.buy),
+                    buy_usd: amount(level.buy_usd),
+                })
+                .collect(),
+            price_impact: depth
+                .impacts
+                .iter()
+                .map(|impact| PriceImpactInfo {
+                    size_usd: impact.size_usd,
+                    sell_impact_percent: amount(impact.sell_percent),
+                    buy_impact_percent: amount(impact.buy_percent),
+                })
+                .collect(),
         }
+    }
+}
 
-        // 获取储备量
-        let pair = IUniswapV2Pair::new(pair_address, provider.clone());
-        let reserves_result = pair.getReserves().call().await.map_err(|e| {
-            warn!("获取储备量失败: {}", e);
-            EthereumError::PriceOracleError(format!("无法获取储备量: {}", e))
-        })?;
+/// 价格变动到某一幅度前可交易的数量
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DepthLevelInfo {
+    #[schemars(description = "Price move in percent")]
+    pub price_move_percent: String,
+    #[schemars(
+        description = "base_token sold (fee included) that pushes its pool price down by price_move_percent; absent when the move is beyond the V3 ticks read"
+    )]
+    pub sell_amount: Option<String>,
+    #[schemars(description = "USD value of sell_amount")]
+    pub sell_usd: Option<String>,
+    #[schemars(
+        description = "base_token bought that pushes its pool price up by price_move_percent; absent when the move is beyond the V3 ticks read"
+    )]
+    pub buy_amount: Option<String>,
+    #[schemars(description = "USD value of the quote_token spent on the buy, fee included")]
+    pub buy_usd: Option<String>,
+}
 
-        let token0 = pair
-            .token0()
-            .call()
-            .await
-            .map_err(|e| {
-                warn!("获取 token0 失败: {}", e);
-                EthereumError::PriceOracleError(format!("无法获取 token0: {}", e))
-            })?
-            ._0;
-
-        // 确定储备量的顺序
-        let (reserve_token, reserve_quote) = if token0 == token_address {
-            (reserves_result.reserve0, reserves_result.reserve1)
+/// 一笔标准规模交易的价格冲击
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PriceImpactInfo {
+    #[schemars(description = "Trade size in USD")]
+    pub size_usd: u64,
+    #[schemars(
+        description = "How far the average execution price (fee included) of selling size_usd of base_token falls below the spot price, in percent; absent beyond the V3 ticks read"
+    )]
+    pub sell_impact_percent: Option<String>,
+    #[schemars(
+        description = "How far the average execution price (fee included) of buying base_token with size_usd of quote_token rises above the spot price, in percent; absent beyond the V3 ticks read"
+    )]
+    pub buy_impact_percent: Option<String>,
+}
+
+/// 一个来源沿价格路径得到的价格
+struct SourceReading {
+    price: Decimal,
+    /// 第一跳池的流动性，用于加权
+    liquidity: Option<u128>,
+    pools: Vec<PoolInfo>,
+    feeds: Vec<FeedInfo>,
+    notes: Vec<String>,
+}
+
+/// 沿 DEX 价格路径相乘各跳报价，`protocol` 为 `None` 时每一跳取所有池中流动性最深的池
+fn dex_price(
+    pools: &[(Address, Address)],
+    spot_calls: &[SpotCalls],
+    results: &[CallResult],
+    decimals: &impl Fn(Address) -> Result<u8>,
+    protocol: Option<Protocol>,
+) -> Result<SourceReading> {
+    let mut price = Decimal::ONE;
+    let mut hops = Vec::new();
+    for (&(token, quote), calls) in pools.iter().zip(spot_calls) {
+        let (token_decimals, quote_decimals) = (decimals(token)?, decimals(quote)?);
+        let hop = match protocol {
+            Some(protocol) => calls.deepest_of(results, token_decimals, quote_decimals, protocol),
+            None => calls.deepest(results, token_decimals, quote_decimals),
+        }?;
+        price *= hop.price;
+        hops.push(hop);
+    }
+    Ok(SourceReading {
+        price,
+        liquidity: hops.first().map(|hop| hop.liquidity),
+        pools: hops.iter().map(PoolInfo::from).collect(),
+        feeds: Vec::new(),
+        notes: Vec::new(),
+    })
+}
+
+/// 沿 DEX 价格路径相乘各跳流动性最深的 V3 池的 TWAP，用于加权的流动性为第一跳的调和平均流动性
+fn twap_price(
+    pools: &[(Address, Address)],
+    spot_calls: &[SpotCalls],
+    results: &[CallResult],
+    decimals: &impl Fn(Address) -> Result<u8>,
+) -> Result<SourceReading> {
+    let mut price = Decimal::ONE;
+    let mut hops = Vec::new();
+    let mut notes = Vec::new();
+    for (&(token, quote), calls) in pools.iter().zip(spot_calls) {
+        let (hop, hop_notes) = calls.twap(results, decimals(token)?, decimals(quote)?)?;
+        price *= hop.quote.price;
+        notes.extend(hop_notes);
+        hops.push(hop);
+    }
+    Ok(SourceReading {
+        price,
+        liquidity: hops.first().map(|hop| hop.harmonic_mean_liquidity),
+        pools: hops.iter().map(PoolInfo::from).collect(),
+        feeds: Vec::new(),
+        notes,
+    })
+}
+
+/// 按喂价路径换算价格
+fn feed_price(
+    legs: &[FeedLeg],
+    feed_calls: &[FeedCalls],
+    results: &[CallResult],
+    block_timestamp: u64,
+) -> Result<SourceReading> {
+    let mut price = Decimal::ONE;
+    let mut feeds = Vec::new();
+    for (leg, calls) in legs.iter().zip(feed_calls) {
+        let reading = calls.price(results, block_timestamp)?;
+        if leg.invert {
+            price /= reading.price;
         } else {
-            (reserves_result.reserve1, reserves_result.reserve0)
-        };
+            price *= reading.price;
+        }
+        feeds.push(FeedInfo::new(&reading, leg.invert));
+    }
+    Ok(SourceReading {
+        price,
+        liquidity: None,
+        pools: Vec::new(),
+        feeds,
+        notes: Vec::new(),
+    })
+}
 
-        if reserve_quote == 0 {
-            return Err(EthereumError::PriceOracleError(
-                "报价代币储备为零".to_string(),
-            ));
+/// 代币地址到 USD 价格
+type UsdPrices = HashMap<Address, Decimal>;
+
+/// 深度计算所用的各跳流动性最深的池及其状态，以及各代币的 USD 价格
+///
+/// 稳定币按 1 美元计，沿价格路径从报价端向前换算；ETH 报价时 WETH 的 USD 价格来自 `valuation`
+/// 给出的 WETH/稳定币报价。
+fn depth_hops(
+    pools: &[(Address, Address)],
+    spot_calls: &[SpotCalls],
+    valuation: Option<(Address, &SpotCalls)>,
+    usd_quote: Address,
+    results: &[CallResult],
+    decimals: &impl Fn(Address) -> Result<u8>,
+) -> Result<(Vec<(PoolPrice, PoolState)>, UsdPrices)> {
+    let mut hops = Vec::new();
+    for (&(token, quote), calls) in pools.iter().zip(spot_calls) {
+        let hop = calls.deepest(results, decimals(token)?, decimals(quote)?)?;
+        let state = calls.state(results, &hop)?;
+        hops.push((hop, state));
+    }
+
+    let mut usd_prices = HashMap::from([(usd_quote, Decimal::ONE)]);
+    if let Some((weth, calls)) = valuation {
+        let hop = calls.deepest(results, decimals(weth)?, decimals(usd_quote)?)?;
+        usd_prices.insert(weth, hop.price);
+    }
+    for (hop, _) in hops.iter().rev() {
+        if let Some(&quote_usd) = usd_prices.get(&hop.quote) {
+            usd_prices.insert(hop.token, hop.price * quote_usd);
         }
+    }
+    Ok((hops, usd_prices))
+}
 
-        // 获取代币小数位数
-        let token_decimals = self.rpc.get_token_decimals(token_address).await?;
-        let quote_decimals = self.rpc.get_token_decimals(quote_token).await?;
+/// 在固定区块上读取各跳池的流动性分布并计算深度
+///
+/// V2 交易对只用已读取的储备量；有 V3 池时再读两轮：代币余额和 tick 位图，然后是已初始化的 tick。
+async fn pool_depths(
+    reader: &RpcClient,
+    block: BlockId,
+    hops: Vec<(PoolPrice, PoolState)>,
+    usd_prices: &UsdPrices,
+    decimals: &impl Fn(Address) -> Result<u8>,
+) -> Result<Vec<PoolDepth>> {
+    let mut batch = MulticallBatch::new();
+    let mut calls: Vec<DepthCalls> = hops
+        .into_iter()
+        .map(|(pool, state)| DepthCalls::add(&mut batch, pool, state))
+        .collect();
+    let first = reader.multicall(&batch, block).await?;
 
-        // 计算价格: (reserve_quote / 10^quote_decimals) / (reserve_token / 10^token_decimals)
-        let reserve_token_decimal =
-            precision::to_decimal(U256::from(reserve_token), token_decimals)?;
-        let reserve_quote_decimal =
-            precision::to_decimal(U256::from(reserve_quote), quote_decimals)?;
+    let mut batch = MulticallBatch::new();
+    for hop in &mut calls {
+        hop.add_ticks(&first, &mut batch)?;
+    }
+    let second = reader.multicall(&batch, block).await?;
 
-        let price = if reserve_token_decimal.is_zero() {
-            return Err(EthereumError::PriceOracleError("代币储备为零".to_string()));
-        } else {
-            reserve_quote_decimal / reserve_token_decimal
-        };
+    let usd = |token: Address| {
+        usd_prices.get(&token).copied().ok_or_else(|| {
+            EthereumError::PriceOracleError(format!("无法换算 {} 的 USD 价格", token))
+        })
+    };
+    calls
+        .iter()
+        .map(|hop| {
+            let (amount0, amount1, curve) = hop.liquidity(&first, &second)?;
+            let pool = hop.pool();
+            depth::pool_depth(
+                pool,
+                amount0,
+                amount1,
+                &curve,
+                decimals(pool.token)?,
+                decimals(pool.quote)?,
+                usd(pool.token)?,
+                usd(pool.quote)?,
+            )
+        })
+        .collect()
+}
 
-        info!(
-            "从 Uniswap 池获取价格: {} = {} (报价代币)",
-            token_address, price
-        );
+pub struct PriceTool {
+    chains: ChainClients,
+    token_registry: Arc<TokenRegistry>,
+}
 
-        Ok(price)
+impl PriceTool {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self::with_chains(rpc.into(), Arc::new(TokenRegistry::new()))
+    }
+
+    /// 使用各链的 RPC 客户端和共享的代币注册表创建
+    pub fn with_chains(chains: ChainClients, token_registry: Arc<TokenRegistry>) -> Self {
+        PriceTool {
+            chains,
+            token_registry,
+        }
     }
 
     /// 获取代币价格信息
     pub async fn get_price(&self, request: PriceRequest) -> Result<PriceResponse> {
+        self.get_price_with_context(request, &ToolContext::default())
+            .await
+    }
+
+    /// 获取代币价格信息，并通过 `context` 上报进度
+    ///
+    /// 先固定区块，代币符号、小数位数以及价格来源所需的数据（每一跳的 V2 储备量和 V3 池状态，
+    /// 或 Chainlink 喂价）再在该区块上通过一次 Multicall 读取。需要深度时，V3 池的余额和 tick
+    /// 在同一区块上再读两轮。
+    pub async fn get_price_with_context(
+        &self,
+        request: PriceRequest,
+        context: &ToolContext,
+    ) -> Result<PriceResponse> {
         debug!("正在获取代币价格: {}", request.token_identifier);
+        context.report_progress(1.0, Some(2.0), "resolving tokens");
 
         let token_identifier = &request.token_identifier.to_uppercase();
         let quote_currency = request
@@ -160,94 +587,1005 @@ impl PriceTool {
             .to_uppercase();
 
         // 验证报价货币
-        if quote_currency != "USD" && quote_currency != "ETH" {
-            return Err(EthereumError::PriceOracleError(format!(
-                "不支持的报价货币: {}",
-                quote_currency
+        let feed_quote = match quote_currency.as_str() {
+            "USD" => FeedQuote::Usd,
+            "ETH" => FeedQuote::Eth,
+            _ => {
+                return Err(EthereumError::PriceOracleError(format!(
+                    "不支持的报价货币: {}",
+                    quote_currency
+                )))
+            }
+        };
+        let source = request.source.unwrap_or_default();
+        let max_deviation = request
+            .max_deviation
+            .unwrap_or(DEFAULT_MAX_DEVIATION_PERCENT);
+        let twap_window = request.twap_window.unwrap_or(DEFAULT_TWAP_WINDOW);
+        let include_depth = request.include_depth.unwrap_or(false);
+        if twap_window == 0 {
+            return Err(EthereumError::InvalidAmount(
+                "TWAP 窗口必须大于 0 秒".to_string(),
+            ));
+        }
+        if max_deviation <= Decimal::ZERO {
+            return Err(EthereumError::InvalidAmount(format!(
+                "最大偏离百分比必须大于 0: {}",
+                max_deviation
             )));
         }
 
         // 解析代币地址
+        let chain = self.chains.select(request.chain.as_deref())?;
+        let network = chain.network();
         let token_address = if let Ok(addr) = token_identifier.parse::<Address>() {
             addr
         } else {
             self.token_registry
-                .symbol_to_address(token_identifier)
+                .symbol_to_address(network.chain_id, token_identifier)
                 .ok_or_else(|| {
                     EthereumError::TokenNotFound(format!("代币不存在: {}", token_identifier))
                 })?
         };
-
-        // 获取代币符号
-        let symbol = if let Ok(sym) = self.rpc.get_token_symbol(token_address).await {
-            sym
+        // 原生代币按包装原生代币报价
+        let weth = network.wrapped_native.address;
+        let token_address = if token_address == NATIVE_TOKEN {
+            weth
         } else {
-            self.token_registry
-                .address_to_symbol(token_address)
-                .unwrap_or_else(|| "UNKNOWN".to_string())
+            token_address
         };
 
-        // 获取价格
-        let price = if quote_currency == "ETH" {
-            // 直接获取相对于 WETH 的价格
-            let weth_address = WETH_ADDRESS
-                .parse::<Address>()
-                .map_err(|_| EthereumError::ConfigError("无效的 WETH 地址".to_string()))?;
-
-            if token_address == weth_address {
-                Decimal::from(1)
-            } else {
-                self.get_price_from_uniswap_pool(token_address, weth_address)
-                    .await?
+        let selector =
+            BlockSelector::from_request(request.block.as_deref(), request.timestamp.as_deref())?;
+
+        // DEX 价格路径：代币/包装原生代币（代币本身为 WETH 时价格为 1），
+        // USD 报价再乘以 WETH/首选稳定币，地址取自当前链的网络配置；Chainlink 来源只在需要深度时读取
+        let usd_quote = network.usd_quote().address;
+        let mut pools = Vec::new();
+        if source != PriceSource::Chainlink || include_depth {
+            if token_address != weth {
+                pools.push((token_address, weth));
             }
-        } else {
-            // USD 价格: 先获取相对于 WETH 的价格，再乘以 ETH/USD 价格
-            let weth_address = WETH_ADDRESS
-                .parse::<Address>()
-                .map_err(|_| EthereumError::ConfigError("无效的 WETH 地址".to_string()))?;
-
-            let usdc_address = USDC_ADDRESS
-                .parse::<Address>()
-                .map_err(|_| EthereumError::ConfigError("无效的 USDC 地址".to_string()))?;
-
-            let price_in_eth = if token_address == weth_address {
-                Decimal::from(1)
-            } else {
-                self.get_price_from_uniswap_pool(token_address, weth_address)
-                    .await?
-            };
+            if feed_quote == FeedQuote::Usd {
+                pools.push((weth, usd_quote));
+            }
+        }
+        // ETH 报价的深度还需要 WETH 的 USD 价格来换算 TVL
+        let valuation =
+            (include_depth && feed_quote == FeedQuote::Eth).then_some((weth, usd_quote));
+        // 聚合时没有喂价只让 Chainlink 来源不可用
+        let no_feed = || {
+            EthereumError::PriceOracleError(format!(
+                "{} 上没有 {} 的 Chainlink {} 喂价",
+                network.display_name, token_identifier, quote_currency
+            ))
+        };
+        let feed_legs = match source {
+            PriceSource::Dex | PriceSource::Twap => None,
+            PriceSource::Chainlink => {
+                Some(chainlink::route(network, token_address, feed_quote).ok_or_else(no_feed)?)
+            }
+            PriceSource::Aggregate => chainlink::route(network, token_address, feed_quote),
+        };
 
-            // 获取 ETH/USDC 价格
-            let eth_usdc_price = self
-                .get_price_from_uniswap_pool(weth_address, usdc_address)
-                .await?;
+        // 已解析过的代币直接使用注册表中的元数据，只为首次遇到的代币查询
+        let known = self
+            .token_registry
+            .metadata(network.chain_id, token_address);
+        let mut batch = MulticallBatch::new();
+        let metadata_calls = known.is_none().then(|| {
+            (
+                batch.add(token_address, IERC20::symbolCall {}),
+                batch.add(token_address, IERC20::nameCall {}),
+            )
+        });
+        let mut known_decimals = HashMap::new();
+        let mut decimals_calls = HashMap::new();
+        if known.is_none() {
+            decimals_calls.insert(
+                token_address,
+                batch.add(token_address, IERC20::decimalsCall {}),
+            );
+        }
+        for &(token, quote) in pools.iter().chain(&valuation) {
+            for address in [token, quote] {
+                match self.token_registry.metadata(network.chain_id, address) {
+                    Some(metadata) => {
+                        known_decimals.insert(address, metadata.decimals);
+                    }
+                    None => {
+                        decimals_calls
+                            .entry(address)
+                            .or_insert_with(|| batch.add(address, IERC20::decimalsCall {}));
+                    }
+                }
+            }
+        }
+        // 每一跳同时读取 V2 交易对和各费率档位的 V3 池，取流动性最深的池；TWAP 还读取各 V3 池的观测记录
+        let observe_window =
+            matches!(source, PriceSource::Twap | PriceSource::Aggregate).then_some(twap_window);
+        let spot_calls: Vec<SpotCalls> = pools
+            .iter()
+            .map(|&(token, quote)| {
+                SpotCalls::add(&mut batch, network, token, quote, observe_window)
+            })
+            .collect();
+        let valuation_calls = valuation
+            .map(|(weth, usd_quote)| SpotCalls::add(&mut batch, network, weth, usd_quote, None));
+        let feed_calls: Vec<FeedCalls> = feed_legs
+            .iter()
+            .flatten()
+            .map(|leg| FeedCalls::add(&mut batch, leg.feed))
+            .collect();
+
+        context.report_progress(2.0, Some(2.0), "quoting");
+        // 历史查询优先在归档节点上读取状态
+        let block = chain.resolve_block(selector).await?;
+        let results = chain
+            .state_reader(&selector)
+            .multicall(&batch, block.id())
+            .await?;
 
-            price_in_eth * eth_usdc_price
+        let decimals = |address: Address| match known_decimals.get(&address) {
+            Some(&decimals) => Ok(decimals),
+            None => results[decimals_calls[&address]]
+                .decode::<IERC20::decimalsCall>()
+                .map(|r| r._0),
         };
 
-        info!("获取 {} 的价格: {} {}", symbol, price, quote_currency);
+        // 获取代币符号，首次遇到的代币记录到注册表
+        let symbol = match (known, metadata_calls) {
+            (Some(metadata), _) => Some(metadata.symbol),
+            (None, Some((symbol_call, name_call))) => {
+                let symbol = results[symbol_call]
+                    .decode::<IERC20::symbolCall>()
+                    .map(|r| r._0)
+                    .ok();
+                let token_decimals = decimals_calls
+                    .get(&token_address)
+                    .and_then(|&call| results[call].decode::<IERC20::decimalsCall>().ok());
+                if let (Some(symbol), Some(decimals)) = (&symbol, token_decimals) {
+                    let name = results[name_call]
+                        .decode::<IERC20::nameCall>()
+                        .map(|r| r._0)
+                        .ok();
+                    self.token_registry.record_resolved(
+                        network.chain_id,
+                        token_address,
+                        symbol.clone(),
+                        name,
+                        decimals._0,
+                    );
+                }
+                symbol
+            }
+            (None, None) => None,
+        }
+        .or_else(|| {
+            self.token_registry
+                .address_to_symbol(network.chain_id, token_address)
+        })
+        .unwrap_or_else(|| "UNKNOWN".to_string());
+
+        // 获取价格
+        let mut confidence = None;
+        let mut sources = Vec::new();
+        let reading = match (source, &feed_legs) {
+            (PriceSource::Dex, _) => dex_price(&pools, &spot_calls, &results, &decimals, None)?,
+            (PriceSource::Twap, _) => twap_price(&pools, &spot_calls, &results, &decimals)?,
+            (PriceSource::Chainlink, legs) => feed_price(
+                legs.as_deref().unwrap_or_default(),
+                &feed_calls,
+                &results,
+                block.timestamp,
+            )?,
+            (PriceSource::Aggregate, legs) => {
+                let readings = [
+                    (
+                        SourceKind::UniswapV2Spot,
+                        dex_price(
+                            &pools,
+                            &spot_calls,
+                            &results,
+                            &decimals,
+                            Some(Protocol::UniswapV2),
+                        ),
+                    ),
+                    (
+                        SourceKind::UniswapV3Spot,
+                        dex_price(
+                            &pools,
+                            &spot_calls,
+                            &results,
+                            &decimals,
+                            Some(Protocol::UniswapV3),
+                        ),
+                    ),
+                    (
+                        SourceKind::UniswapV3Twap,
+                        twap_price(&pools, &spot_calls, &results, &decimals),
+                    ),
+                    (
+                        SourceKind::Chainlink,
+                        legs.as_deref().ok_or_else(no_feed).and_then(|legs| {
+                            feed_price(legs, &feed_calls, &results, block.timestamp)
+                        }),
+                    ),
+                ];
+                let available: Vec<SourcePrice> = readings
+                    .iter()
+                    .filter_map(|(kind, reading)| {
+                        reading.as_ref().ok().map(|reading| SourcePrice {
+                            kind: *kind,
+                            price: reading.price,
+                            liquidity: reading.liquidity,
+                        })
+                    })
+                    .collect();
+                if available.is_empty() {
+                    let errors: Vec<String> = readings
+                        .iter()
+                        .filter_map(|(kind, reading)| {
+                            reading.as_ref().err().map(|e| format!("{:?}: {}", kind, e))
+                        })
+                        .collect();
+                    return Err(EthereumError::PriceOracleError(format!(
+                        "所有价格来源均不可用: {}",
+                        errors.join("; ")
+                    )));
+                }
+                let result = aggregate::aggregate(
+                    &available,
+                    request.aggregation.unwrap_or_default(),
+                    max_deviation,
+                )?;
+
+                let mut index = 0;
+                let mut notes = Vec::new();
+                for (kind, reading) in readings {
+                    sources.push(match reading {
+                        Ok(reading) => {
+                            let i = index;
+                            index += 1;
+                            notes.extend(reading.notes);
+                            SourceQuote {
+                                source: kind,
+                                price: Some(reading.price.normalize().to_string()),
+                                deviation_percent: Some(
+                                    result.deviations[i].normalize().to_string(),
+                                ),
+                                outlier: result.outliers[i],
+                                weight: result.weights[i].map(|w| w.normalize().to_string()),
+                                error: None,
+                                pools: reading.pools,
+                                feeds: reading.feeds,
+                            }
+                        }
+                        Err(e) => SourceQuote {
+                            source: kind,
+                            price: None,
+                            deviation_percent: None,
+                            outlier: false,
+                            weight: None,
+                            error: Some(e.to_string()),
+                            pools: Vec::new(),
+                            feeds: Vec::new(),
+                        },
+                    });
+                }
+                confidence = Some(result.confidence.normalize().to_string());
+                SourceReading {
+                    price: result.price,
+                    liquidity: None,
+                    pools: Vec::new(),
+                    feeds: Vec::new(),
+                    notes,
+                }
+            }
+        };
+        let price = reading.price;
+        let mut notes = reading.notes;
+
+        // 深度需要额外的读取，失败时只记入说明，不影响价格
+        let depth = if include_depth {
+            let depth = async {
+                let (hops, usd_prices) = depth_hops(
+                    &pools,
+                    &spot_calls,
+                    valuation
+                        .map(|(weth, _)| weth)
+                        .zip(valuation_calls.as_ref()),
+                    usd_quote,
+                    &results,
+                    &decimals,
+                )?;
+                let reader = chain.state_reader(&selector);
+                pool_depths(&reader, block.id(), hops, &usd_prices, &decimals).await
+            };
+            match depth.await {
+                Ok(depth) => depth.iter().map(DepthInfo::from).collect(),
+                Err(e) => {
+                    notes.push(format!("无法计算池深度: {}", e));
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        info!(
+            "获取 {} 的价格: {} {} (区块 {})",
+            symbol, price, quote_currency, block.number
+        );
 
         Ok(PriceResponse {
+            chain_id: network.chain_id,
             quote_currency,
             price: price.normalize().to_string(),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            source,
+            pools: reading.pools,
+            feeds: reading.feeds,
+            confidence,
+            sources,
+            depth,
+            notes,
+            timestamp: block.timestamp,
+            block_number: block.number,
+            block_hash: block.hash.to_string(),
         })
     }
 }
 
+#[async_trait]
+impl Tool for PriceTool {
+    fn name(&self) -> &str {
+        "get_token_price"
+    }
+
+    fn description(&self) -> &str {
+        "Get current price of a token in USD or ETH from Uniswap spot prices, Uniswap V3 TWAPs, Chainlink price feeds, or an aggregate of them with outlier checks and a confidence score, optionally with pool TVL, liquidity depth and a price-impact table"
+    }
+
+    fn input_schema(&self) -> Value {
+        schema_for::<PriceRequest>()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(schema_for::<PriceResponse>())
+    }
+
+    async fn call(
+        &self,
+        arguments: Value,
+        context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError> {
+        let request: PriceRequest = parse_arguments(arguments)?;
+        CallToolResult::from_result(
+            self.get_price_with_context(request, &context).await,
+            "Price query failed",
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::NetworkProfile;
+    use crate::pricing::chainlink::feed_results;
+    use crate::pricing::v2::reserves_result;
+    use crate::pricing::v3;
+    use crate::rpc::block::mock_block_response;
+    use crate::rpc::multicall::IMulticall3;
+    use crate::rpc::CallResult;
+    use alloy::primitives::{Bytes, B256, U256};
+    use alloy::sol_types::SolCall;
+    use mockito::Matcher;
+
+    /// 网络 `network` 上的 (WETH, 首选稳定币, WETH/稳定币交易对)
+    fn weth_usd_pair(network: &NetworkProfile) -> (Address, Address, Address) {
+        let (weth, usd) = (network.wrapped_native.address, network.usd_quote().address);
+        (weth, usd, network.uniswap_v2.pair_address(weth, usd))
+    }
+
+    /// `aggregate3` 返回值：一跳的 V2 储备量，后接 V3 各费率档位池的 `slot0` 和 `liquidity`
+    fn multicall_output(v2_reserves: CallResult, v3_pools: &[Option<(U256, u128)>; 4]) -> Bytes {
+        aggregate3_output(spot_results(v2_reserves, v3_pools))
+    }
+
+    /// 一跳的 V2 储备量，后接 V3 各费率档位池的 `slot0` 和 `liquidity`
+    fn spot_results(
+        v2_reserves: CallResult,
+        v3_pools: &[Option<(U256, u128)>; 4],
+    ) -> Vec<CallResult> {
+        let mut results = vec![v2_reserves];
+        for pool in v3_pools {
+            let target = Address::ZERO;
+            match pool {
+                Some((sqrt_price_x96, liquidity)) => {
+                    results.push(v3::slot0_result(target, *sqrt_price_x96));
+                    results.push(v3::liquidity_result(target, *liquidity));
+                }
+                None => {
+                    results.push(CallResult::new(target, true, Bytes::new()));
+                    results.push(CallResult::new(target, true, Bytes::new()));
+                }
+            }
+        }
+        results
+    }
+
+    /// 与 `spot_results` 相同，但每个 V3 池后接 1800 秒窗口的 `observe`：
+    /// 平均 tick 为 `Some` 时观测记录覆盖窗口，为 `None` 时调用回退
+    fn twap_results(
+        v2_reserves: CallResult,
+        v3_pools: &[Option<(U256, u128, Option<i32>)>; 4],
+    ) -> Vec<CallResult> {
+        let mut results = vec![v2_reserves];
+        for pool in v3_pools {
+            let target = Address::ZERO;
+            match pool {
+                Some((sqrt_price_x96, liquidity, mean_tick)) => {
+                    results.push(v3::slot0_result(target, *sqrt_price_x96));
+                    results.push(v3::liquidity_result(target, *liquidity));
+                    results.push(match mean_tick {
+                        Some(tick) => v3::observe_result(target, 1800, *tick, *liquidity),
+                        None => CallResult::new(target, false, Bytes::new()),
+                    });
+                }
+                None => results.extend((0..3).map(|_| CallResult::new(target, true, Bytes::new()))),
+            }
+        }
+        results
+    }
+
+    fn aggregate3_output(results: Vec<CallResult>) -> Bytes {
+        IMulticall3::aggregate3Call::abi_encode_returns(&(results
+            .into_iter()
+            .map(|result| IMulticall3::Call3Result {
+                success: result.success(),
+                returnData: result.return_data().clone(),
+            })
+            .collect::<Vec<_>>(),))
+        .into()
+    }
+
+    /// 在区块 21,525,000（时间戳 1735689600）上对每个 `eth_call` 返回 `output` 的模拟节点
+    async fn mock_node(output: Bytes) -> mockito::ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(
+                21_525_000,
+                B256::repeat_byte(0xab),
+                1735689600,
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_call"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"{}"}}"#,
+                output
+            ))
+            .create_async()
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_price_lookup_reads_pinned_block_in_one_call() {
+        let (_, _, pair) = weth_usd_pair(NetworkProfile::mainnet());
+        // WETH 和 USDC 的符号与小数位数来自代币列表，只需读取 WETH/USDC 的各个池；V3 池均未部署
+        let output = multicall_output(
+            reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18)),
+            &[None; 4],
+        );
+
+        let block_hash = B256::repeat_byte(0xab);
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber","params":["latest",false]}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(21_525_000, block_hash, 1735689600))
+            .create_async()
+            .await;
+        // 储备量、小数位数和符号在固定区块上通过一次 eth_call 读取
+        let mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "method": "eth_call",
+                "params": [{}, {"blockHash": block_hash}]
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"{}"}}"#,
+                output
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let request = PriceRequest {
+            token_identifier: "WETH".to_string(),
+            ..Default::default()
+        };
+        let response = tool.get_price(request.clone()).await.unwrap();
+
+        assert_eq!(response.price, "2000");
+        assert_eq!(response.pools.len(), 1);
+        assert_eq!(response.pools[0].protocol, Protocol::UniswapV2);
+        assert_eq!(response.pools[0].address, pair.to_string());
+        assert_eq!(response.pools[0].fee_tier, None);
+        assert_eq!(response.block_number, 21_525_000);
+        assert_eq!(response.block_hash, block_hash.to_string());
+        assert_eq!(response.timestamp, 1735689600);
+
+        // 同一区块内的第二次查询完全命中缓存
+        let cached = tool.get_price(request).await.unwrap();
+        assert_eq!(cached.price, "2000");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_price_uses_network_profile() {
+        let base = NetworkProfile::by_chain_id(8453).unwrap();
+        let (_, _, pair) = weth_usd_pair(base);
+        assert_ne!(pair, weth_usd_pair(NetworkProfile::mainnet()).2);
+        // Base 上 WETH 地址较小，为 token0
+        let output = multicall_output(
+            reserves_result(pair, 1_000 * 10u128.pow(18), 3_000_000_000_000),
+            &[None; 4],
+        );
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"method":"eth_getBlockByNumber"}"#.to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(mock_block_response(
+                22_000_000,
+                B256::repeat_byte(0xcd),
+                1735689600,
+            ))
+            .create_async()
+            .await;
+        // 读取的是 Base 上 WETH/USDC 交易对的储备量
+        let mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex(
+                format!("{:x}", pair.into_word())[24..].to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"jsonrpc":"2.0","id":0,"result":"{}"}}"#,
+                output
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        // 默认链为主网，请求通过链 ID 选择 Base
+        let mainnet = RpcClient::new("http://127.0.0.1:1".to_string())
+            .await
+            .unwrap();
+        let base_rpc = RpcClient::new(server.url())
+            .await
+            .unwrap()
+            .with_network(base);
+        let chains = ChainClients::new(vec![mainnet, base_rpc]).unwrap();
+        let response = PriceTool::with_chains(chains, Arc::new(TokenRegistry::new()))
+            .get_price(PriceRequest {
+                token_identifier: "weth".to_string(),
+                quote_currency: Some("usd".to_string()),
+                chain: Some("8453".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.price, "3000");
+        assert_eq!(response.chain_id, 8453);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_price_prefers_deepest_v3_pool() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc, pair) = weth_usd_pair(network);
+        // V2: 2000 USDC/WETH (L ≈ 4.5e16)；0.05% 池: 2500 (L = 1e18)；0.3% 池: 1600 (L = 1e17)
+        let output = multicall_output(
+            reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18)),
+            &[
+                None,
+                Some((U256::from(20_000u64) << 96, 10u128.pow(18))),
+                Some((U256::from(25_000u64) << 96, 10u128.pow(17))),
+                None,
+            ],
+        );
+
+        let server = mock_node(output).await;
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let response = tool
+            .get_price(PriceRequest {
+                token_identifier: "WETH".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.price, "2500");
+        let pool = &response.pools[0];
+        assert_eq!(pool.protocol, Protocol::UniswapV3);
+        assert_eq!(pool.fee_tier, Some(500));
+        assert_eq!(
+            pool.address,
+            network.uniswap_v3.pool_address(weth, usdc, 500).to_string()
+        );
+        assert_eq!(pool.base_token, weth.to_string());
+        assert_eq!(pool.liquidity, "1000000000000000000");
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["pools"][0]["protocol"], "uniswap_v3");
+    }
+
+    #[tokio::test]
+    async fn test_price_from_chainlink_feeds() {
+        let network = NetworkProfile::mainnet();
+        let uni = network
+            .chainlink_feed(
+                address!("1f9840a85d5aF5bf1D1762F925BDADdC4201F984"),
+                FeedQuote::Usd,
+            )
+            .unwrap();
+        let eth_usd = network
+            .chainlink_feed(network.wrapped_native.address, FeedQuote::Usd)
+            .unwrap();
+        // UNI 没有 ETH 喂价：UNI/USD = 10 (30 分钟前更新) ÷ ETH/USD = 2500 (5 分钟前更新)
+        let [uni_decimals, uni_round] = feed_results(uni.address, 8, 1_000_000_000, 1735687800);
+        let [eth_decimals, eth_round] =
+            feed_results(eth_usd.address, 8, 250_000_000_000, 1735689300);
+        let server = mock_node(aggregate3_output(vec![
+            uni_decimals,
+            uni_round,
+            eth_decimals,
+            eth_round,
+        ]))
+        .await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let request = PriceRequest {
+            token_identifier: "UNI".to_string(),
+            quote_currency: Some("ETH".to_string()),
+            source: Some(PriceSource::Chainlink),
+            ..Default::default()
+        };
+        let response = tool.get_price(request).await.unwrap();
+        assert_eq!(response.price, "0.004");
+        assert_eq!(response.source, PriceSource::Chainlink);
+        assert!(response.pools.is_empty());
+        assert_eq!(response.feeds.len(), 2);
+        assert_eq!(response.feeds[0].address, uni.address.to_string());
+        assert_eq!(response.feeds[0].price, "10");
+        assert_eq!(response.feeds[0].updated_at, 1735687800);
+        assert!(!response.feeds[0].inverted);
+        assert_eq!(response.feeds[1].quote_currency, "USD");
+        assert!(response.feeds[1].inverted);
+    }
+
+    #[tokio::test]
+    async fn test_stale_chainlink_feed_rejected() {
+        let network = NetworkProfile::mainnet();
+        let eth_usd = network
+            .chainlink_feed(network.wrapped_native.address, FeedQuote::Usd)
+            .unwrap();
+        // ETH/USD 心跳 1 小时，最后更新在 2 小时前
+        let server = mock_node(aggregate3_output(
+            feed_results(eth_usd.address, 8, 250_000_000_000, 1735689600 - 7200).into(),
+        ))
+        .await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let err = tool
+            .get_price(PriceRequest {
+                token_identifier: "ETH".to_string(),
+                source: Some(PriceSource::Chainlink),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::PriceOracleError(_)));
+        assert!(err.to_string().contains("已过期"));
+
+        // 没有喂价的代币
+        let err = tool
+            .get_price(PriceRequest {
+                token_identifier: "FRAX".to_string(),
+                source: Some(PriceSource::Chainlink),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Chainlink"));
+    }
+
+    #[tokio::test]
+    async fn test_price_aggregates_sources() {
+        let network = NetworkProfile::mainnet();
+        let (weth, _, pair) = weth_usd_pair(network);
+        let eth_usd = network.chainlink_feed(weth, FeedQuote::Usd).unwrap();
+        // V2 被推高到 2600 USDC/WETH，0.05% V3 池现货为 2500、30 分钟 TWAP 为 tick 198079 ≈ 2500.16，
+        // ETH/USD 喂价为 2510
+        let mut results = twap_results(
+            reserves_result(pair, 2_600_000_000_000, 1_000 * 10u128.pow(18)),
+            &[
+                None,
+                Some((U256::from(20_000u64) << 96, 10u128.pow(18), Some(198079))),
+                None,
+                None,
+            ],
+        );
+        results.extend(feed_results(
+            eth_usd.address,
+            8,
+            251_000_000_000,
+            1735689300,
+        ));
+        let server = mock_node(aggregate3_output(results)).await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let request = PriceRequest {
+            token_identifier: "ETH".to_string(),
+            source: Some(PriceSource::Aggregate),
+            ..Default::default()
+        };
+        let response = tool.get_price(request.clone()).await.unwrap();
+        // 偏离全部四个来源中位数约 3.8% 的 V2 被剔除，其余三个来源取中位数（TWAP）
+        assert!(response.price.starts_with("2500.16"));
+        assert_eq!(response.confidence.as_deref(), Some("0.75"));
+        assert!(response.pools.is_empty());
+        let kinds: Vec<SourceKind> = response.sources.iter().map(|s| s.source).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SourceKind::UniswapV2Spot,
+                SourceKind::UniswapV3Spot,
+                SourceKind::UniswapV3Twap,
+                SourceKind::Chainlink
+            ]
+        );
+        let v2 = &response.sources[0];
+        assert_eq!(v2.price.as_deref(), Some("2600"));
+        assert!(v2.outlier);
+        assert_eq!(v2.pools[0].address, pair.to_string());
+        assert!(!response.sources[1].outlier);
+        let twap = response.sources[2].pools[0].twap.as_ref().unwrap();
+        assert_eq!(twap.window_secs, 1800);
+        assert_eq!(twap.mean_tick, 198079);
+        assert_eq!(response.sources[3].feeds.len(), 1);
+        assert!(response.notes.is_empty());
+
+        // 阈值放宽到 5% 后按流动性加权：V3 池的 L 远大于 V2，喂价只用于偏离检查
+        let response = tool
+            .get_price(PriceRequest {
+                aggregation: Some(AggregationMethod::LiquidityWeighted),
+                max_deviation: Some(Decimal::from(5)),
+                ..request
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.confidence.as_deref(), Some("1"));
+        let price: Decimal = response.price.parse().unwrap();
+        assert!(price > Decimal::from(2500) && price < Decimal::from(2510));
+        assert!(response.sources[3].weight.is_none());
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["sources"][0]["source"], "uniswap_v2_spot");
+    }
+
+    #[tokio::test]
+    async fn test_price_from_v3_twap() {
+        let network = NetworkProfile::mainnet();
+        let (weth, usdc, pair) = weth_usd_pair(network);
+        let v2 = || reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18));
+        // 0.05% 池最深但观测记录不足 30 分钟，0.3% 池的 TWAP 为 tick 198079 ≈ 2500.16
+        let server = mock_node(aggregate3_output(twap_results(
+            v2(),
+            &[
+                None,
+                Some((U256::from(20_000u64) << 96, 10u128.pow(18), None)),
+                Some((U256::from(20_000u64) << 96, 10u128.pow(17), Some(198079))),
+                None,
+            ],
+        )))
+        .await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let request = PriceRequest {
+            token_identifier: "WETH".to_string(),
+            source: Some(PriceSource::Twap),
+            twap_window: Some(1800),
+            ..Default::default()
+        };
+        let response = tool.get_price(request.clone()).await.unwrap();
+        assert!(response.price.starts_with("2500.16"));
+        assert_eq!(response.source, PriceSource::Twap);
+        let pool = &response.pools[0];
+        assert_eq!(
+            pool.address,
+            network
+                .uniswap_v3
+                .pool_address(weth, usdc, 3000)
+                .to_string()
+        );
+        let twap = pool.twap.as_ref().unwrap();
+        assert_eq!(twap.mean_tick, 198079);
+        assert_eq!(twap.observation_cardinality, 1);
+        assert_eq!(response.notes.len(), 1);
+        assert!(response.notes[0].contains("观测基数 1"));
+        assert!(response.notes[0].contains("1800 秒"));
+
+        // 所有池的观测记录都不足时报错
+        let server = mock_node(aggregate3_output(twap_results(
+            v2(),
+            &[
+                None,
+                Some((U256::from(20_000u64) << 96, 10u128.pow(18), None)),
+                None,
+                None,
+            ],
+        )))
+        .await;
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let err = tool.get_price(request.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("观测基数"));
+
+        let err = tool
+            .get_price(PriceRequest {
+                twap_window: Some(0),
+                ..request
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::InvalidAmount(_)));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_with_single_source() {
+        let (_, _, pair) = weth_usd_pair(NetworkProfile::mainnet());
+        // 只有 V2 交易对，ETH/USD 喂价未返回数据
+        let mut results = twap_results(
+            reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18)),
+            &[None; 4],
+        );
+        results.extend((0..2).map(|_| CallResult::new(Address::ZERO, true, Bytes::new())));
+        let server = mock_node(aggregate3_output(results)).await;
+
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let request = PriceRequest {
+            token_identifier: "WETH".to_string(),
+            source: Some(PriceSource::Aggregate),
+            ..Default::default()
+        };
+        let response = tool.get_price(request.clone()).await.unwrap();
+        assert_eq!(response.price, "2000");
+        assert_eq!(response.confidence.as_deref(), Some("0.33"));
+        assert!(response.sources[1].error.is_some());
+        assert!(response.sources[2].error.is_some());
+        assert!(response.sources[3]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("不存在"));
+
+        let err = tool
+            .get_price(PriceRequest {
+                max_deviation: Some(Decimal::ZERO),
+                ..request
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EthereumError::InvalidAmount(_)));
+    }
+
+    #[tokio::test]
+    async fn test_price_with_depth() {
+        let (weth, usdc, pair) = weth_usd_pair(NetworkProfile::mainnet());
+        // 只有 V2 交易对：2,000,000 USDC / 1,000 WETH，深度无需额外读取
+        let output = multicall_output(
+            reserves_result(pair, 2_000_000_000_000, 1_000 * 10u128.pow(18)),
+            &[None; 4],
+        );
+
+        let server = mock_node(output).await;
+        let tool = PriceTool::new(RpcClient::new(server.url()).await.unwrap());
+        let response = tool
+            .get_price(PriceRequest {
+                token_identifier: "WETH".to_string(),
+                include_depth: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.price, "2000");
+        assert!(response.notes.is_empty());
+        assert_eq!(response.depth.len(), 1);
+        let depth = &response.depth[0];
+        assert_eq!(depth.pool.address, pair.to_string());
+        assert_eq!(depth.pool.base_token, weth.to_string());
+        assert_eq!(depth.pool.quote_token, usdc.to_string());
+        assert_eq!(depth.base_reserve, "1000");
+        assert_eq!(depth.quote_reserve, "2000000");
+        assert_eq!(depth.base_reserve_usd, "2000000");
+        assert_eq!(depth.tvl_usd, "4000000");
+
+        let moves: Vec<&str> = depth
+            .levels
+            .iter()
+            .map(|level| level.price_move_percent.as_str())
+            .collect();
+        assert_eq!(moves, ["0.5", "1", "2"]);
+        // 卖出约 5.053 WETH 使价格下跌 1%
+        let sell: Decimal = depth.levels[1]
+            .sell_amount
+            .as_ref()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(sell.round_dp(3), "5.053".parse().unwrap());
+
+        // 规模越大价格冲击越大，最小的一笔也至少承担 0.3% 的手续费
+        let impacts: Vec<Decimal> = depth
+            .price_impact
+            .iter()
+            .map(|impact| {
+                impact
+                    .sell_impact_percent
+                    .as_ref()
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(impacts.len(), 4);
+        assert!(impacts[0] > "0.3".parse().unwrap());
+        assert!(impacts.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(depth.price_impact[3].size_usd, 1_000_000);
+
+        // 深度随响应一起序列化
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["depth"][0]["tvl_usd"], "4000000");
+    }
 
     #[test]
     fn test_price_response_serialization() {
         let response = PriceResponse {
+            chain_id: 1,
             quote_currency: "USD".to_string(),
             price: "2500".to_string(),
+            source: PriceSource::Dex,
+            pools: Vec::new(),
+            feeds: Vec::new(),
+            confidence: None,
+            sources: Vec::new(),
+            depth: Vec::new(),
+            notes: Vec::new(),
             timestamp: 1735689600,
+            block_number: 21_525_000,
+            block_hash: B256::ZERO.to_string(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -259,9 +1597,19 @@ mod tests {
     #[test]
     fn test_price_response_with_eth_quote() {
         let response = PriceResponse {
+            chain_id: 1,
             quote_currency: "ETH".to_string(),
             price: "0.5".to_string(),
+            source: PriceSource::Dex,
+            pools: Vec::new(),
+            feeds: Vec::new(),
+            confidence: None,
+            sources: Vec::new(),
+            depth: Vec::new(),
+            notes: Vec::new(),
             timestamp: 1735689600,
+            block_number: 21_525_000,
+            block_hash: B256::ZERO.to_string(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -273,7 +1621,7 @@ mod tests {
     fn test_token_symbol_normalization() {
         let request = PriceRequest {
             token_identifier: "eth".to_string(),
-            quote_currency: None,
+            ..Default::default()
         };
         assert_eq!(request.token_identifier.to_uppercase(), "ETH");
     }
@@ -283,6 +1631,7 @@ mod tests {
         let request = PriceRequest {
             token_identifier: "USDT".to_string(),
             quote_currency: Some("ETH".to_string()),
+            ..Default::default()
         };
         assert_eq!(request.quote_currency, Some("ETH".to_string()));
     }
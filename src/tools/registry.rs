@@ -0,0 +1,385 @@
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::tools::schema::ArgumentValidator;
+
+/// MCP 工具定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+}
+
+/// MCP 内容块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ContentBlock {
+    Text { text: String },
+}
+
+/// MCP `tools/call` 结果
+///
+/// 工具执行失败通过 `isError` 返回给模型，JSON-RPC 错误仅用于协议层面的问题。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    pub content: Vec<ContentBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    pub is_error: bool,
+}
+
+impl CallToolResult {
+    /// 以结构化结果构造，同时附带序列化后的文本块以兼容旧客户端
+    pub fn structured(value: Value, is_error: bool) -> Self {
+        CallToolResult {
+            content: vec![ContentBlock::Text {
+                text: value.to_string(),
+            }],
+            structured_content: Some(value),
+            is_error,
+        }
+    }
+
+    /// 工具执行失败，错误信息作为文本返回给模型
+    pub fn error(message: String) -> Self {
+        CallToolResult {
+            content: vec![ContentBlock::Text { text: message }],
+            structured_content: None,
+            is_error: true,
+        }
+    }
+
+    /// 将可序列化的工具输出转换为结构化结果
+    pub fn json<T: Serialize>(value: &T, is_error: bool) -> std::result::Result<Self, ToolError> {
+        let value = serde_json::to_value(value).map_err(|e| ToolError::Internal(e.to_string()))?;
+        Ok(CallToolResult::structured(value, is_error))
+    }
+
+    /// 将工具的业务结果转换为 MCP 结果，失败时在错误信息前加上 `context`
+    pub fn from_result<T: Serialize, E: fmt::Display>(
+        result: std::result::Result<T, E>,
+        context: &str,
+    ) -> std::result::Result<Self, ToolError> {
+        match result {
+            Ok(response) => CallToolResult::json(&response, false),
+            Err(e) => Ok(CallToolResult::error(format!("{}: {}", context, e))),
+        }
+    }
+}
+
+/// 工具调用中属于协议层面的错误（会以 JSON-RPC error 返回）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolError {
+    /// 参数不符合工具的输入格式
+    InvalidArguments(String),
+    /// 服务器内部错误（序列化失败等）
+    Internal(String),
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::InvalidArguments(msg) => write!(f, "Invalid arguments: {}", msg),
+            ToolError::Internal(msg) => write!(f, "Internal error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+/// 进度回调：(progress, total, message)
+type ProgressSink = Arc<dyn Fn(f64, Option<f64>, &str) + Send + Sync>;
+
+/// 工具调用上下文
+///
+/// 目前用于上报进度；调用方未提供 `progressToken` 时上报为空操作。
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    progress: Option<ProgressSink>,
+}
+
+impl ToolContext {
+    /// 使用进度回调构造上下文
+    pub fn with_progress<F>(sink: F) -> Self
+    where
+        F: Fn(f64, Option<f64>, &str) + Send + Sync + 'static,
+    {
+        ToolContext {
+            progress: Some(Arc::new(sink)),
+        }
+    }
+
+    /// 上报当前阶段，`progress` 必须随每次上报递增
+    pub fn report_progress(&self, progress: f64, total: Option<f64>, message: &str) {
+        if let Some(sink) = &self.progress {
+            sink(progress, total, message);
+        }
+    }
+}
+
+/// 可注册到 MCP 服务器的工具
+///
+/// 下游 crate 实现该 trait 并通过 `McpServer::register_tool` 注册即可暴露新工具。
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// 工具名称（在注册表中唯一）
+    fn name(&self) -> &str;
+
+    /// 面向模型的工具描述
+    fn description(&self) -> &str;
+
+    /// 输入参数的 JSON Schema，调用前会按此校验参数
+    fn input_schema(&self) -> Value;
+
+    /// 结构化输出的 JSON Schema（可选）
+    fn output_schema(&self) -> Option<Value> {
+        None
+    }
+
+    /// 执行工具，可通过 `context` 上报进度
+    async fn call(
+        &self,
+        arguments: Value,
+        context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError>;
+
+    /// 工具定义（`tools/list` 中的一项）
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            input_schema: self.input_schema(),
+            output_schema: self.output_schema(),
+        }
+    }
+}
+
+/// 将工具参数反序列化为请求结构体
+pub fn parse_arguments<T: DeserializeOwned>(arguments: Value) -> std::result::Result<T, ToolError> {
+    serde_json::from_value(arguments).map_err(|e| ToolError::InvalidArguments(e.to_string()))
+}
+
+/// 注册表中的工具及其编译好的参数校验器
+pub struct RegisteredTool {
+    tool: Arc<dyn Tool>,
+    validator: Option<ArgumentValidator>,
+}
+
+impl RegisteredTool {
+    fn new(tool: Arc<dyn Tool>) -> Self {
+        let validator = match ArgumentValidator::new(&tool.input_schema()) {
+            Ok(validator) => Some(validator),
+            Err(e) => {
+                warn!(
+                    "工具 {} 的 inputSchema 无效，跳过参数校验: {}",
+                    tool.name(),
+                    e
+                );
+                None
+            }
+        };
+        RegisteredTool { tool, validator }
+    }
+
+    pub fn tool(&self) -> &Arc<dyn Tool> {
+        &self.tool
+    }
+
+    /// 按 inputSchema 校验参数后执行工具
+    pub async fn call(
+        &self,
+        arguments: Value,
+        context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError> {
+        if let Some(validator) = &self.validator {
+            validator.validate(&arguments)?;
+        }
+        self.tool.call(arguments, context).await
+    }
+}
+
+/// 工具注册表，按注册顺序保存工具
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<RegisteredTool>>,
+    index: HashMap<String, usize>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个工具，同名工具会被替换并返回旧工具
+    pub fn register(&mut self, tool: Arc<dyn Tool>) -> Option<Arc<dyn Tool>> {
+        let name = tool.name().to_string();
+        let entry = Arc::new(RegisteredTool::new(tool));
+        match self.index.get(&name) {
+            Some(&position) => {
+                let previous = std::mem::replace(&mut self.tools[position], entry);
+                Some(Arc::clone(&previous.tool))
+            }
+            None => {
+                self.index.insert(name, self.tools.len());
+                self.tools.push(entry);
+                None
+            }
+        }
+    }
+
+    /// 按名称查找工具
+    pub fn get(&self, name: &str) -> Option<Arc<RegisteredTool>> {
+        self.index.get(name).map(|&i| Arc::clone(&self.tools[i]))
+    }
+
+    /// 所有工具的定义
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .iter()
+            .map(|entry| entry.tool.definition())
+            .collect()
+    }
+
+    /// 所有工具的名称
+    pub fn names(&self) -> Vec<String> {
+        self.tools
+            .iter()
+            .map(|entry| entry.tool.name().to_string())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct EchoTool(&'static str);
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            self.0
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": {"a": {"type": "integer"}},
+                "required": ["a"]
+            })
+        }
+
+        async fn call(
+            &self,
+            arguments: Value,
+            context: ToolContext,
+        ) -> std::result::Result<CallToolResult, ToolError> {
+            context.report_progress(1.0, None, "echoing");
+            Ok(CallToolResult::structured(arguments, false))
+        }
+    }
+
+    #[test]
+    fn test_call_tool_result_serialization() {
+        let result = CallToolResult::structured(json!({"price": "1"}), false);
+        let value = serde_json::to_value(&result).unwrap();
+
+        assert_eq!(value["content"][0]["type"], "text");
+        assert_eq!(value["structuredContent"]["price"], "1");
+        assert_eq!(value["isError"], false);
+
+        let value = serde_json::to_value(CallToolResult::error("boom".to_string())).unwrap();
+        assert_eq!(value["content"][0]["text"], "boom");
+        assert_eq!(value["isError"], true);
+        assert!(value.get("structuredContent").is_none());
+    }
+
+    #[test]
+    fn test_register_replaces_same_name() {
+        let mut registry = ToolRegistry::new();
+        assert!(registry.register(Arc::new(EchoTool("first"))).is_none());
+        assert!(registry.register(Arc::new(EchoTool("second"))).is_some());
+
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.definitions()[0].description, "second");
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool("echo")));
+
+        let tool = registry.get("echo").unwrap();
+        let result = tool
+            .call(json!({"a": 1}), ToolContext::default())
+            .await
+            .unwrap();
+        assert_eq!(result.structured_content, Some(json!({"a": 1})));
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_arguments_validated_before_dispatch() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool("echo")));
+
+        let err = registry
+            .get("echo")
+            .unwrap()
+            .call(json!({"a": "one"}), ToolContext::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(ref msg) if msg.contains("/a")));
+    }
+
+    #[test]
+    fn test_definition_serializes_camel_case() {
+        let definition = EchoTool("echo").definition();
+        let value = serde_json::to_value(&definition).unwrap();
+        assert!(value.get("inputSchema").is_some());
+        assert!(value.get("outputSchema").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_context_reports_progress() {
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let context = ToolContext::with_progress(move |progress, _, message| {
+            sink.lock().unwrap().push((progress, message.to_string()));
+        });
+
+        EchoTool("echo")
+            .call(json!({"a": 1}), context)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![(1.0, "echoing".to_string())]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde_json::Value;
+
+use crate::tools::registry::ToolError;
+
+/// 从请求/响应结构体生成 JSON Schema
+///
+/// 去掉 `$schema` 和 `title`，只保留 MCP `inputSchema`/`outputSchema` 需要的部分。
+pub fn schema_for<T: JsonSchema>() -> Value {
+    let mut schema = schemars::schema_for!(T).to_value();
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("$schema");
+        object.remove("title");
+    }
+    schema
+}
+
+/// `Decimal` 字段的 Schema：既接受数字也接受字符串（与 `rust_decimal` 的反序列化行为一致）
+pub fn decimal_schema(_generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": ["number", "string"],
+        "pattern": "^-?[0-9]+(\\.[0-9]+)?$"
+    })
+}
+
+/// 编译后的参数校验器
+pub struct ArgumentValidator {
+    validator: jsonschema::Validator,
+}
+
+impl ArgumentValidator {
+    /// 编译 Schema，Schema 本身无效时返回错误
+    pub fn new(schema: &Value) -> std::result::Result<Self, String> {
+        jsonschema::validator_for(schema)
+            .map(|validator| ArgumentValidator { validator })
+            .map_err(|e| e.to_string())
+    }
+
+    /// 校验参数，错误信息中包含出错字段的 JSON Pointer
+    pub fn validate(&self, arguments: &Value) -> std::result::Result<(), ToolError> {
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(arguments)
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                let field = if path.is_empty() { "/" } else { path.as_str() };
+                format!("{}: {}", field, error)
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ToolError::InvalidArguments(errors.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::balance::BalanceRequest;
+    use crate::tools::price::PriceRequest;
+    use crate::tools::swap::SwapRequest;
+    use serde_json::json;
+
+    #[test]
+    fn test_price_schema_includes_quote_currency() {
+        let schema = schema_for::<PriceRequest>();
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["quote_currency"].is_object());
+        assert_eq!(schema["required"], json!(["token_identifier"]));
+        assert!(schema.get("$schema").is_none());
+    }
+
+    #[test]
+    fn test_swap_slippage_accepts_number_and_string() {
+        let validator = ArgumentValidator::new(&schema_for::<SwapRequest>()).unwrap();
+        let mut arguments = json!({
+            "from_token": "ETH",
+            "to_token": "USDC",
+            "amount": "1",
+            "slippage": 0.5,
+            "wallet_address": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+        });
+        assert!(validator.validate(&arguments).is_ok());
+
+        arguments["slippage"] = json!("0.5");
+        assert!(validator.validate(&arguments).is_ok());
+
+        arguments["slippage"] = json!(true);
+        let err = validator.validate(&arguments).unwrap_err();
+        assert!(err.to_string().contains("/slippage"));
+    }
+
+    #[test]
+    fn test_missing_required_field() {
+        let validator = ArgumentValidator::new(&schema_for::<BalanceRequest>()).unwrap();
+        let err = validator
+            .validate(&json!({"token_address": "USDT"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("address"));
+    }
+}
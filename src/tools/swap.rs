@@ -1,57 +1,123 @@
 use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::error::{EthereumError, Result};
+use crate::network::NetworkProfile;
 use crate::precision;
-use crate::rpc::RpcClient;
+use crate::rpc::{BlockSelector, ChainClients, RpcClient};
 use crate::tokens::TokenRegistry;
 use crate::tools::balance::BalanceTool;
+use crate::tools::registry::{parse_arguments, CallToolResult, Tool, ToolContext, ToolError};
+use crate::tools::schema::{decimal_schema, schema_for};
 
 const ETH_IDENTIFIER: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
-const WETH_ADDRESS: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 交换模拟上报进度的阶段总数
+const SWAP_STAGES: f64 = 4.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SwapRequest {
+    #[schemars(description = "Source token symbol or address")]
     pub from_token: String, // 符号或地址
-    pub to_token: String,   // 符号或地址
-    pub amount: String,     // 人类可读格式的金额
-    pub slippage: Decimal,  // 滑点容差百分比（例如 0.5 表示 0.5%）
+    #[schemars(description = "Destination token symbol or address")]
+    pub to_token: String, // 符号或地址
+    #[schemars(description = "Amount to swap (in human-readable format)")]
+    pub amount: String, // 人类可读格式的金额
+    #[schemars(
+        schema_with = "decimal_schema",
+        description = "Slippage tolerance in percentage (e.g., 0.5 for 0.5%)"
+    )]
+    pub slippage: Decimal, // 滑点容差百分比（例如 0.5 表示 0.5%）
+    #[schemars(description = "Wallet address initiating the swap")]
     pub wallet_address: String,
+    #[schemars(
+        description = "Block to simulate at: number, hash, or tag (latest, safe, finalized). Defaults to latest"
+    )]
+    pub block: Option<String>, // 模拟使用的区块，默认 latest
+    #[schemars(
+        description = "Network to simulate on: name (ethereum, arbitrum, base, optimism, sepolia) or chain ID. Defaults to the server's default network"
+    )]
+    pub chain: Option<String>, // 模拟使用的网络（名称或链 ID），默认为服务器的默认网络
+}
+
+/// `gas_cost_eth` 所用 Gas 价格的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GasPriceSource {
+    /// 固定区块的基础费用，不含优先费
+    BlockBaseFee,
+    /// 节点当前的 `eth_gasPrice`，不随区块固定；只在区块没有基础费用（伦敦升级之前）时使用
+    NodeGasPrice,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SwapResponse {
+    #[schemars(description = "Chain ID of the network the swap was simulated on")]
+    pub chain_id: u64,
+    #[schemars(description = "Source token as requested")]
     pub from_token: String,
+    #[schemars(description = "Destination token as requested")]
     pub to_token: String,
+    #[schemars(description = "Input amount as requested")]
     pub input_amount: String,
+    #[schemars(description = "Estimated output amount")]
     pub estimated_output: String,
+    #[schemars(description = "Minimum output after applying slippage tolerance")]
     pub min_output: String,
+    #[schemars(description = "Estimated gas cost in ETH")]
     pub gas_cost_eth: String,
+    #[schemars(
+        description = "Where the gas price behind gas_cost_eth came from: block_base_fee (base fee of the pinned block, excluding any priority fee) or node_gas_price (the node's current eth_gasPrice, not pinned to the block; only used when the block has no base fee). Absent when gas was not estimated"
+    )]
+    pub gas_price_source: Option<GasPriceSource>,
+    #[schemars(description = "Slippage tolerance in percentage")]
     pub slippage_percentage: String,
+    #[schemars(description = "Whether the simulation succeeded")]
     pub simulation_success: bool,
+    #[schemars(description = "Failure reason when the simulation did not succeed")]
     pub error: Option<String>,
+    #[schemars(
+        description = "Number of the block all reads were pinned to (absent if the request failed before reading the chain)"
+    )]
+    pub block_number: Option<u64>,
+    #[schemars(
+        description = "Hash of the block all reads were pinned to (absent if the request failed before reading the chain)"
+    )]
+    pub block_hash: Option<String>,
 }
 
 pub struct SwapTool {
-    rpc: RpcClient,
-    token_registry: TokenRegistry,
+    chains: ChainClients,
+    token_registry: Arc<TokenRegistry>,
     balance_tool: Option<BalanceTool>,
 }
 
 impl SwapTool {
     pub fn new(rpc: RpcClient) -> Self {
-        let balance_tool = Some(BalanceTool::new(rpc.clone()));
+        Self::with_chains(rpc.into(), Arc::new(TokenRegistry::new()))
+    }
+
+    /// 使用各链的 RPC 客户端和共享的代币注册表创建
+    pub fn with_chains(chains: ChainClients, token_registry: Arc<TokenRegistry>) -> Self {
+        let balance_tool = Some(BalanceTool::with_chains(
+            chains.clone(),
+            token_registry.clone(),
+        ));
         SwapTool {
-            rpc,
-            token_registry: TokenRegistry::new(),
+            chains,
+            token_registry,
             balance_tool,
         }
     }
 
-    /// 验证并将代币标识符解析为地址
-    fn resolve_token(&self, identifier: &str) -> Result<Address> {
+    /// 验证并将代币标识符解析为链 `chain_id` 上的地址
+    fn resolve_token(&self, chain_id: u64, identifier: &str) -> Result<Address> {
         let identifier_upper = identifier.to_uppercase();
 
         // 首先尝试解析为地址
@@ -61,7 +127,7 @@ impl SwapTool {
 
         // 尝试作为符号查找
         self.token_registry
-            .symbol_to_address(&identifier_upper)
+            .symbol_to_address(chain_id, &identifier_upper)
             .ok_or_else(|| EthereumError::InvalidTokenPair(format!("无法解析代币: {}", identifier)))
     }
 
@@ -71,12 +137,10 @@ impl SwapTool {
         identifier_upper == "ETH" || identifier_upper == ETH_IDENTIFIER.to_uppercase()
     }
 
-    /// 将 ETH 转换为 WETH 地址用于 Uniswap 交换
-    fn eth_to_weth(&self, token_addr: Address) -> Result<Address> {
+    /// 将 ETH 转换为 `network` 的 WETH 地址用于 Uniswap 交换
+    fn eth_to_weth(&self, network: &NetworkProfile, token_addr: Address) -> Result<Address> {
         if token_addr.to_string().to_uppercase() == ETH_IDENTIFIER.to_uppercase() {
-            WETH_ADDRESS
-                .parse::<Address>()
-                .map_err(|_| EthereumError::ConfigError("无效的 WETH 地址".to_string()))
+            Ok(network.wrapped_native.address)
         } else {
             Ok(token_addr)
         }
@@ -84,58 +148,83 @@ impl SwapTool {
 
     /// 模拟代币交换（使用 Uniswap V2 真实数据）
     pub async fn simulate_swap(&self, request: SwapRequest) -> Result<SwapResponse> {
+        self.simulate_swap_with_context(request, &ToolContext::default())
+            .await
+    }
+
+    /// 模拟代币交换，并通过 `context` 上报各阶段进度
+    pub async fn simulate_swap_with_context(
+        &self,
+        request: SwapRequest,
+        context: &ToolContext,
+    ) -> Result<SwapResponse> {
         info!(
             "模拟交换: {} {} -> {}",
             request.amount, request.from_token, request.to_token
         );
+        context.report_progress(1.0, Some(SWAP_STAGES), "resolving tokens");
+
+        let chain = self.chains.select(request.chain.as_deref())?;
+        let network = chain.network();
+        let chain_id = network.chain_id;
 
         // 验证地址
-        let from_token_raw = self.resolve_token(&request.from_token)?;
-        let to_token_raw = self.resolve_token(&request.to_token)?;
+        let from_token_raw = self.resolve_token(chain_id, &request.from_token)?;
+        let to_token_raw = self.resolve_token(chain_id, &request.to_token)?;
 
         // 检查是否为 ETH，用于后续小数位数判断
         let from_is_eth = self.is_eth(&request.from_token);
         let to_is_eth = self.is_eth(&request.to_token);
 
         // 将 ETH 转换为 WETH 用于 Uniswap 交换
-        let from_token = self.eth_to_weth(from_token_raw)?;
-        let to_token = self.eth_to_weth(to_token_raw)?;
+        let from_token = self.eth_to_weth(network, from_token_raw)?;
+        let to_token = self.eth_to_weth(network, to_token_raw)?;
         let wallet_address = request
             .wallet_address
             .parse::<Address>()
             .map_err(|_| EthereumError::InvalidAddress("无效的钱包地址".to_string()))?;
+        let selector = BlockSelector::from_request(request.block.as_deref(), None)?;
 
         // 验证金额
         let input_amount_decimal = match request.amount.parse::<Decimal>() {
             Ok(amt) => amt,
             Err(_) => {
                 return Ok(SwapResponse {
+                    chain_id,
                     from_token: request.from_token,
                     to_token: request.to_token,
                     input_amount: request.amount,
                     estimated_output: "0".to_string(),
                     min_output: "0".to_string(),
                     gas_cost_eth: "0".to_string(),
+                    gas_price_source: None,
                     slippage_percentage: request.slippage.to_string(),
                     simulation_success: false,
                     error: Some("无效的金额格式".to_string()),
+                    block_number: None,
+                    block_hash: None,
                 });
             }
         };
 
+        // 固定区块：余额、小数位数、报价和 Gas 估算都读取同一区块
+        // 指定历史区块时优先在归档节点上读取状态
+        let block = chain.resolve_block(selector).await?;
+        let rpc = chain.state_reader(&selector);
+        let block_number = Some(block.number);
+        let block_hash = Some(block.hash.to_string());
+
         // 检查钱包余额
+        context.report_progress(2.0, Some(SWAP_STAGES), "checking balance");
         let balance_check = async {
             let bt = self
                 .balance_tool
                 .as_ref()
                 .ok_or_else(|| EthereumError::Unknown("余额工具不可用".to_string()))?;
 
-            let req = crate::tools::balance::BalanceRequest {
-                address: wallet_address.to_string(),
-                token_address: Some(from_token.to_string()),
-            };
-            info!("检查钱包余额请求: {:?}", req);
-            bt.get_balance(req).await
+            info!("检查钱包余额: {:?} (代币 {:?})", wallet_address, from_token);
+            bt.get_balance_at(&rpc, wallet_address, Some(from_token.to_string()), &block)
+                .await
         };
 
         match balance_check.await {
@@ -144,18 +233,22 @@ impl SwapTool {
 
                 if wallet_balance < input_amount_decimal {
                     return Ok(SwapResponse {
+                        chain_id,
                         from_token: request.from_token,
                         to_token: request.to_token,
                         input_amount: request.amount,
                         estimated_output: "0".to_string(),
                         min_output: "0".to_string(),
                         gas_cost_eth: "0".to_string(),
+                        gas_price_source: None,
                         slippage_percentage: request.slippage.to_string(),
                         simulation_success: false,
                         error: Some(format!(
                             "余额不足: {} 可用, {} 需要",
                             wallet_balance, input_amount_decimal
                         )),
+                        block_number,
+                        block_hash,
                     });
                 }
             }
@@ -170,20 +263,24 @@ impl SwapTool {
         let from_decimals = if from_is_eth {
             18 // ETH/WETH 固定为 18 位小数
         } else {
-            match self.rpc.get_token_decimals(from_token).await {
+            match rpc.get_token_decimals(from_token, block.id()).await {
                 Ok(d) => d,
                 Err(e) => {
                     warn!("获取源代币小数位数失败: {}", e);
                     return Ok(SwapResponse {
+                        chain_id,
                         from_token: request.from_token,
                         to_token: request.to_token,
                         input_amount: request.amount,
                         estimated_output: "0".to_string(),
                         min_output: "0".to_string(),
                         gas_cost_eth: "0".to_string(),
+                        gas_price_source: None,
                         slippage_percentage: request.slippage.to_string(),
                         simulation_success: false,
                         error: Some(format!("无法获取源代币信息: {}", e)),
+                        block_number,
+                        block_hash,
                     });
                 }
             }
@@ -192,20 +289,24 @@ impl SwapTool {
         let to_decimals = if to_is_eth {
             18 // ETH/WETH 固定为 18 位小数
         } else {
-            match self.rpc.get_token_decimals(to_token).await {
+            match rpc.get_token_decimals(to_token, block.id()).await {
                 Ok(d) => d,
                 Err(e) => {
                     warn!("获取目标代币小数位数失败: {}", e);
                     return Ok(SwapResponse {
+                        chain_id,
                         from_token: request.from_token,
                         to_token: request.to_token,
                         input_amount: request.amount,
                         estimated_output: "0".to_string(),
                         min_output: "0".to_string(),
                         gas_cost_eth: "0".to_string(),
+                        gas_price_source: None,
                         slippage_percentage: request.slippage.to_string(),
                         simulation_success: false,
                         error: Some(format!("无法获取目标代币信息: {}", e)),
+                        block_number,
+                        block_hash,
                     });
                 }
             }
@@ -216,37 +317,49 @@ impl SwapTool {
             Ok(amt) => amt,
             Err(e) => {
                 return Ok(SwapResponse {
+                    chain_id,
                     from_token: request.from_token,
                     to_token: request.to_token,
                     input_amount: request.amount,
                     estimated_output: "0".to_string(),
                     min_output: "0".to_string(),
                     gas_cost_eth: "0".to_string(),
+                    gas_price_source: None,
                     slippage_percentage: request.slippage.to_string(),
                     simulation_success: false,
                     error: Some(format!("金额转换失败: {}", e)),
+                    block_number,
+                    block_hash,
                 });
             }
         };
 
         // 构建交换路径
+        context.report_progress(3.0, Some(SWAP_STAGES), "quoting");
         let path = vec![from_token, to_token];
 
         // 从 Uniswap V2 Router 获取实际输出金额
-        let amounts_out = match self.rpc.get_amounts_out(amount_in_u256, path.clone()).await {
+        let amounts_out = match rpc
+            .get_amounts_out(amount_in_u256, path.clone(), block.id())
+            .await
+        {
             Ok(amounts) => amounts,
             Err(e) => {
                 warn!("从 Uniswap 获取输出金额失败: {}", e);
                 return Ok(SwapResponse {
+                    chain_id,
                     from_token: request.from_token,
                     to_token: request.to_token,
                     input_amount: request.amount,
                     estimated_output: "0".to_string(),
                     min_output: "0".to_string(),
                     gas_cost_eth: "0".to_string(),
+                    gas_price_source: None,
                     slippage_percentage: request.slippage.to_string(),
                     simulation_success: false,
                     error: Some(format!("无法从 Uniswap 获取价格: {}", e)),
+                    block_number,
+                    block_hash,
                 });
             }
         };
@@ -254,15 +367,19 @@ impl SwapTool {
         // 获取输出金额（路径中的最后一个元素）
         if amounts_out.is_empty() {
             return Ok(SwapResponse {
+                chain_id,
                 from_token: request.from_token,
                 to_token: request.to_token,
                 input_amount: request.amount,
                 estimated_output: "0".to_string(),
                 min_output: "0".to_string(),
                 gas_cost_eth: "0".to_string(),
+                gas_price_source: None,
                 slippage_percentage: request.slippage.to_string(),
                 simulation_success: false,
                 error: Some("Uniswap 返回空的输出金额".to_string()),
+                block_number,
+                block_hash,
             });
         }
 
@@ -271,15 +388,19 @@ impl SwapTool {
             Ok(amt) => amt,
             Err(e) => {
                 return Ok(SwapResponse {
+                    chain_id,
                     from_token: request.from_token,
                     to_token: request.to_token,
                     input_amount: request.amount,
                     estimated_output: "0".to_string(),
                     min_output: "0".to_string(),
                     gas_cost_eth: "0".to_string(),
+                    gas_price_source: None,
                     slippage_percentage: request.slippage.to_string(),
                     simulation_success: false,
                     error: Some(format!("输出金额转换失败: {}", e)),
+                    block_number,
+                    block_hash,
                 });
             }
         };
@@ -291,23 +412,25 @@ impl SwapTool {
                 Ok(amt) => amt,
                 Err(e) => {
                     return Ok(SwapResponse {
+                        chain_id,
                         from_token: request.from_token,
                         to_token: request.to_token,
                         input_amount: request.amount,
                         estimated_output: estimated_output.normalize().to_string(),
                         min_output: "0".to_string(),
                         gas_cost_eth: "0".to_string(),
+                        gas_price_source: None,
                         slippage_percentage: request.slippage.to_string(),
                         simulation_success: false,
                         error: Some(format!("滑点计算失败: {}", e)),
+                        block_number,
+                        block_hash,
                     });
                 }
             };
 
-        // 获取当前 Gas 价格
-        let gas_price = self.rpc.get_gas_price().await.unwrap_or(20_000_000_000u128);
-
         // 估算 Gas（使用 eth_estimateGas）
+        context.report_progress(4.0, Some(SWAP_STAGES), "estimating gas");
         let min_output_u256 = match precision::from_decimal(min_output, to_decimals) {
             Ok(amt) => amt,
             Err(_) => U256::ZERO,
@@ -321,8 +444,8 @@ impl SwapTool {
                 + 300,
         ); // 5 分钟后过期
 
-        let gas_estimate = match self
-            .rpc
+        // 估算失败通常说明交易在该区块上会回退（如未授权），不以默认值代替
+        let gas_estimate = match rpc
             .simulate_swap_exact_tokens_for_tokens(
                 amount_in_u256,
                 min_output_u256,
@@ -330,16 +453,57 @@ impl SwapTool {
                 wallet_address,
                 deadline,
                 wallet_address,
+                block.id(),
             )
             .await
         {
             Ok((_, gas)) => gas,
             Err(e) => {
-                warn!("Gas 估算失败，使用默认值: {}", e);
-                150_000u64 // 默认 Gas 估算
+                warn!("Gas 估算失败: {}", e);
+                return Ok(SwapResponse {
+                    chain_id,
+                    from_token: request.from_token,
+                    to_token: request.to_token,
+                    input_amount: request.amount,
+                    estimated_output: estimated_output.normalize().to_string(),
+                    min_output: min_output.normalize().to_string(),
+                    gas_cost_eth: "0".to_string(),
+                    gas_price_source: None,
+                    slippage_percentage: request.slippage.to_string(),
+                    simulation_success: false,
+                    error: Some(format!("Gas 估算失败: {}", e)),
+                    block_number,
+                    block_hash,
+                });
             }
         };
 
+        // Gas 价格取固定区块的基础费用，与报价和 Gas 估算来自同一区块；
+        // 区块没有基础费用时才使用节点当前的 Gas 价格，并在响应中标明
+        let (gas_price, gas_price_source) = match block.base_fee_per_gas {
+            Some(base_fee) => (u128::from(base_fee), GasPriceSource::BlockBaseFee),
+            None => match chain.get_gas_price().await {
+                Ok(price) => (price, GasPriceSource::NodeGasPrice),
+                Err(e) => {
+                    return Ok(SwapResponse {
+                        chain_id,
+                        from_token: request.from_token,
+                        to_token: request.to_token,
+                        input_amount: request.amount,
+                        estimated_output: estimated_output.normalize().to_string(),
+                        min_output: min_output.normalize().to_string(),
+                        gas_cost_eth: "0".to_string(),
+                        gas_price_source: None,
+                        slippage_percentage: request.slippage.to_string(),
+                        simulation_success: false,
+                        error: Some(format!("无法获取 Gas 价格: {}", e)),
+                        block_number,
+                        block_hash,
+                    });
+                }
+            },
+        };
+
         let gas_cost_wei = U256::from(gas_estimate) * U256::from(gas_price);
         let gas_cost_eth = match precision::to_decimal(gas_cost_wei, 18) {
             Ok(cost) => cost,
@@ -356,19 +520,59 @@ impl SwapTool {
         );
 
         Ok(SwapResponse {
+            chain_id,
             from_token: request.from_token,
             to_token: request.to_token,
             input_amount: request.amount,
             estimated_output: estimated_output.normalize().to_string(),
             min_output: min_output.normalize().to_string(),
             gas_cost_eth: gas_cost_eth.normalize().to_string(),
+            gas_price_source: Some(gas_price_source),
             slippage_percentage: request.slippage.to_string(),
             simulation_success: true,
             error: None,
+            block_number,
+            block_hash,
         })
     }
 }
 
+#[async_trait]
+impl Tool for SwapTool {
+    fn name(&self) -> &str {
+        "swap_tokens"
+    }
+
+    fn description(&self) -> &str {
+        "Simulate a token swap on Uniswap (no actual transaction executed)"
+    }
+
+    fn input_schema(&self) -> Value {
+        schema_for::<SwapRequest>()
+    }
+
+    fn output_schema(&self) -> Option<Value> {
+        Some(schema_for::<SwapResponse>())
+    }
+
+    async fn call(
+        &self,
+        arguments: Value,
+        context: ToolContext,
+    ) -> std::result::Result<CallToolResult, ToolError> {
+        let request: SwapRequest = parse_arguments(arguments)?;
+
+        // 模拟未成功时（余额不足、无流动性等）同样标记为 isError，原因在结构化结果的 error 字段中
+        match self.simulate_swap_with_context(request, &context).await {
+            Ok(response) => CallToolResult::json(&response, !response.simulation_success),
+            Err(e) => Ok(CallToolResult::error(format!(
+                "Swap simulation failed: {}",
+                e
+            ))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,15 +580,19 @@ mod tests {
     #[test]
     fn test_swap_response_serialization() {
         let response = SwapResponse {
+            chain_id: 1,
             from_token: "ETH".to_string(),
             to_token: "USDC".to_string(),
             input_amount: "1".to_string(),
             estimated_output: "2500".to_string(),
             min_output: "2487.5".to_string(),
             gas_cost_eth: "0.003".to_string(),
+            gas_price_source: Some(GasPriceSource::BlockBaseFee),
             slippage_percentage: "0.5".to_string(),
             simulation_success: true,
             error: None,
+            block_number: Some(21_525_000),
+            block_hash: None,
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -396,7 +604,7 @@ mod tests {
     #[test]
     fn test_token_identifier_parsing() {
         let registry = TokenRegistry::new();
-        let usdc = registry.symbol_to_address("USDC");
+        let usdc = registry.symbol_to_address(1, "USDC");
         assert!(usdc.is_some());
     }
 
@@ -413,6 +621,8 @@ mod tests {
                 amount: "invalid".to_string(),
                 slippage: Decimal::from_str_exact("0.5").unwrap(),
                 wallet_address: "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string(),
+                block: None,
+                chain: None,
             };
 
             let result = swap_tool.simulate_swap(request).await;
@@ -430,7 +640,7 @@ mod tests {
         assert!("1.5".parse::<Decimal>().is_ok());
         assert!("0.001".parse::<Decimal>().is_ok());
         assert!("1000.25".parse::<Decimal>().is_ok());
-        assert!(!Decimal::from_str_exact("invalid").is_ok());
+        assert!(Decimal::from_str_exact("invalid").is_err());
     }
 
     #[test]